@@ -14,88 +14,309 @@
 // limitations under the License.
 //
 
-use crate::types::{Matrix, Note, PitchGroup};
-use std::{cmp::Ordering, fmt};
+use crate::runtime::NoteEvent;
+use crate::types::{Matrix, Note, PitchClass, PitchClassSet, PitchGroup, Steps};
+use std::{cmp::Ordering, collections::HashMap, fmt, ops::Range, sync::Arc, time::Duration};
+
+/// Major and minor key profiles, C first, that a [PitchHistogram] is correlated against.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum KeyProfile {
+    /// Krumhansl and Kessler's probe tone ratings (1982).
+    #[default]
+    KrumhanslKessler,
+    /// Temperley's profiles from the Kostka-Payne corpus (2007).
+    Temperley,
+    /// Aarden's profiles from the Essen folksong collection (2003).
+    Aarden,
+}
+
+impl KeyProfile {
+    pub fn all() -> [KeyProfile; 3] { [KeyProfile::KrumhanslKessler, KeyProfile::Temperley, KeyProfile::Aarden] }
+
+    pub fn major(&self) -> [f64; 12] {
+        match self {
+            KeyProfile::KrumhanslKessler => [6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88],
+            KeyProfile::Temperley => [0.748, 0.060, 0.488, 0.082, 0.670, 0.460, 0.096, 0.715, 0.104, 0.366, 0.057, 0.400],
+            KeyProfile::Aarden => [17.7661, 0.145624, 14.9265, 0.160186, 19.8049, 11.3587, 0.291248, 22.062, 0.145624, 8.15494, 0.232998, 4.95122],
+        }
+    }
+
+    pub fn minor(&self) -> [f64; 12] {
+        match self {
+            KeyProfile::KrumhanslKessler => [6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17],
+            KeyProfile::Temperley => [0.712, 0.084, 0.474, 0.618, 0.049, 0.460, 0.105, 0.747, 0.404, 0.067, 0.133, 0.330],
+            KeyProfile::Aarden => [18.2648, 0.737619, 14.0499, 16.8599, 0.702494, 14.4362, 0.702494, 18.6161, 4.56621, 1.93186, 7.37619, 1.75623],
+        }
+    }
+}
+
+/// How much each [PitchClass](audiotheorem::types::PitchClass) sounds, weighted by how long and how loud it is played.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct PitchHistogram {
+    weights: [f64; 12],
+}
+
+impl PitchHistogram {
+    pub fn new() -> PitchHistogram { PitchHistogram::default() }
+
+    pub fn weights(&self) -> [f64; 12] { self.weights }
+
+    pub fn weight(&self, pc: &PitchClass) -> f64 { self.weights[pc.to_index() as usize] }
+
+    pub fn is_empty(&self) -> bool { self.weights.iter().all(|w| *w <= 0.0) }
+
+    /// Add a [PitchClass](audiotheorem::types::PitchClass) sounding for some time at a MIDI velocity.
+    pub fn add(&mut self, pc: &PitchClass, duration: Duration, velocity: u8) {
+        self.weights[pc.to_index() as usize] += duration.as_secs_f64() * velocity.min(127) as f64 / 127.0;
+    }
+
+    /// Add a [Note](audiotheorem::types::Note) counted once.
+    pub fn add_note(&mut self, note: &Note) { self.weights[note.pitch_class().to_index() as usize] += 1.0; }
+
+    /// Weight every note-on by the time until its note-off on the same channel and index.
+    /// Notes still held at the end last until the final event.
+    pub fn from_events(events: &[NoteEvent]) -> PitchHistogram {
+        let mut histogram = PitchHistogram::new();
+        let mut held: HashMap<(u8, u8), (Duration, u8)> = HashMap::new();
+        let end = events.iter().map(|e| e.time).max().unwrap_or_default();
+        for event in events {
+            if let Some((start, velocity)) = held.remove(&(event.channel, event.index)) {
+                histogram.add(&PitchClass::from_index(event.index % 12), event.time.saturating_sub(start), velocity);
+            }
+            if event.velocity > 0 { held.insert((event.channel, event.index), (event.time, event.velocity)); }
+        }
+        for ((_, index), (start, velocity)) in held {
+            histogram.add(&PitchClass::from_index(index % 12), end.saturating_sub(start), velocity);
+        }
+        histogram
+    }
+
+    /// Pearson correlation with a profile whose first entry is the tonic, 0 when either side is flat.
+    pub fn correlate(&self, profile: &[f64; 12], tonic: &PitchClass) -> f64 {
+        let rotated: [f64; 12] = std::array::from_fn(|i| profile[(i + 12 - tonic.to_index() as usize) % 12]);
+        let mean = |v: &[f64; 12]| v.iter().sum::<f64>() / 12.0;
+        let (mx, my) = (mean(&self.weights), mean(&rotated));
+        let (mut sxy, mut sxx, mut syy) = (0.0, 0.0, 0.0);
+        for (x, y) in self.weights.iter().zip(rotated.iter()) {
+            sxy += (x - mx) * (y - my);
+            sxx += (x - mx) * (x - mx);
+            syy += (y - my) * (y - my);
+        }
+        if sxx == 0.0 || syy == 0.0 { 0.0 } else { sxy / (sxx * syy).sqrt() }
+    }
+}
+
+impl From<&[Note]> for PitchHistogram {
+    fn from(notes: &[Note]) -> PitchHistogram {
+        let mut histogram = PitchHistogram::new();
+        for note in notes { histogram.add_note(note); }
+        histogram
+    }
+}
+
+/// A major or minor key and how well a [PitchHistogram] correlates with its profile.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct KeyEstimate {
+    pub tonic: Note,
+    pub minor: bool,
+    /// The group of the key, or of its relative major.
+    pub pitch_group: PitchGroup,
+    pub correlation: f64,
+}
+
+impl fmt::Display for KeyEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} ({:.3})", self.tonic, if self.minor { "minor" } else { "major" }, self.correlation)
+    }
+}
 
 #[derive(Debug)]
 pub struct Analysis {
     notes: Vec<Note>,
     records: Vec<AnalysisRecord>,
+    keys: Vec<KeyEstimate>,
     enharmonic: bool,
     natural: bool,
     sharp: bool,
     flat: bool,
 }
 
-#[derive(Debug)]
 pub struct AnalysisRecord {
     pitch_group: PitchGroup,
+    // Best correlation of the group's major or relative minor key
     probability: f64,
-    members: Vec<Note>,
-    offnotes: Vec<Note>,
+    // Shared by every record of an analysis, the ranges pick out this group's notes
+    spelled: Arc<[Note]>,
+    members: Range<usize>,
+    offnotes: Range<usize>,
 }
 
 pub struct Analyzer;
 
 impl Analyzer {
     /// Find which [PitchGroups](audiotheorem::types::PitchGroup) a given set of provided
-    /// [Note](audiotheorem::types::Note) belong to.
-    #[rustfmt::skip]
+    /// [Note](audiotheorem::types::Note) belong to, ranked by the Krumhansl-Kessler profiles.
     pub fn score(notes: &[Note]) -> Result<Analysis, &'static str> {
+        Analyzer::score_weighted(notes, &PitchHistogram::from(notes), KeyProfile::default())
+    }
+
+    /// Score a set of provided [Note](audiotheorem::types::Note) against a [PitchHistogram] of how they
+    /// were played - the members of each group come from the spelling, the ranking from the key correlations.
+    #[rustfmt::skip]
+    pub fn score_weighted(notes: &[Note], histogram: &PitchHistogram, profile: KeyProfile) -> Result<Analysis, &'static str> {
         let mut a = Analysis {
             notes: notes.to_vec(),
             records: Vec::with_capacity(12),
+            keys: Analyzer::find_keys(histogram, profile),
             enharmonic: notes.iter().any(|n| n.enharmonic()),
             natural: notes.iter().all(|n| n.natural()),
             sharp: notes.iter().any(|n| n.sharp()),
             flat: notes.iter().any(|n| n.flat()),
         };
 
-        for pitch_group in PitchGroup::all().iter() {
+        // Every group's members then its offnotes, one after the other in a single buffer
+        let mut spelled = Vec::with_capacity(12 * notes.len());
+        let mut splits = [0; 12];
+        for (split, pitch_group) in splits.iter_mut().zip(PitchGroup::all().iter()) {
             //println!("Assessing PitchGroup: {:?} ", pitch_group);
-            let mut found = Vec::new();
-            let mut missing = Vec::new();
-            for note in notes {
-                if let Some(matrix_note) = match (a.natural, a.sharp, a.flat) {
+            let pitch_classes = PitchClassSet::from(*pitch_group);
+            let member = |note: &Note| {
+                pitch_classes.contains(&note.pitch_class()) && match (a.natural, a.sharp, a.flat) {
                     (true, false, false) => Matrix::natural(&note.pitch_class(), pitch_group),
                     (false, true, false) => Matrix::sharp(&note.pitch_class(), pitch_group),
                     (false, false, true) => Matrix::flat(&note.pitch_class(), pitch_group),
-                    (_, _, _) => None
-                } {
-                    if matrix_note == *note {
-                        found.push(*note);
-                    } else {
-                        missing.push(*note);
-                    }
-                } else {
-                    missing.push(*note);
-                }
-            }
-            let p = found.len() as f64 / notes.len() as f64;
+                    // Mixed spellings can't all match, so anything in the group counts
+                    (_, _, _) => Some(*note)
+                } == Some(*note)
+            };
+            let start = spelled.len();
+            spelled.extend(notes.iter().filter(|note| member(note)));
+            *split = spelled.len() - start;
+            spelled.extend(notes.iter().filter(|note| !member(note)));
+        }
+        let spelled: Arc<[Note]> = spelled.into();
+        for (i, (split, pitch_group)) in splits.iter().zip(PitchGroup::all().iter()).enumerate() {
+            let p = a.keys.iter()
+                .find(|k| k.pitch_group == *pitch_group)
+                .map(|k| k.correlation)
+                .unwrap_or(0.0);
+                let start = i * notes.len();
                 a.records.push(AnalysisRecord {
                     pitch_group: pitch_group.clone(),
                     probability: p,
-                    members: found,
-                    offnotes: missing,
+                    spelled: Arc::clone(&spelled),
+                    members: start..start + split,
+                    offnotes: start + split..start + notes.len(),
                 });
         }
         a.records.sort_by(|a, b| b.probability.partial_cmp(&a.probability).unwrap());
-        /*
-        if let Some(ar) = a.records.first() {
-            let p = ar.probability;
-            a.records.iter().filter(|r| r.probability>=p).collect()
+        Ok(a)
+    }
 
+    /// Correlate a [PitchHistogram] with all 24 major and minor keys of a [KeyProfile], best first.
+    pub fn find_keys(histogram: &PitchHistogram, profile: KeyProfile) -> Vec<KeyEstimate> {
+        let (major, minor) = (profile.major(), profile.minor());
+        let mut keys = Vec::with_capacity(24);
+        for pitch_group in PitchGroup::all() {
+            let tonic = pitch_group.ionian().tonic();
+            let relative = tonic + Steps::from(-3);
+            for (pc, profile, is_minor) in [(tonic, &major, false), (relative, &minor, true)] {
+                keys.push(KeyEstimate {
+                    tonic: Matrix::natural(&pc, &pitch_group).expect("tonic is in its own group"),
+                    minor: is_minor,
+                    pitch_group,
+                    correlation: histogram.correlate(profile, &pc),
+                });
+            }
         }
-        */
-        Ok(a)
+        keys.sort_by(|a, b| b.correlation.partial_cmp(&a.correlation).unwrap_or(Ordering::Equal));
+        keys
+    }
+}
+
+impl Analysis {
+    pub fn records(&self) -> &[AnalysisRecord] { &self.records }
+
+    /// Every major and minor key, best correlation first.
+    pub fn keys(&self) -> &[KeyEstimate] { &self.keys }
+
+    pub fn key(&self) -> Option<&KeyEstimate> { self.keys.first() }
+}
+
+impl AnalysisRecord {
+    pub fn pitch_group(&self) -> PitchGroup { self.pitch_group }
+    pub fn probability(&self) -> f64 { self.probability }
+    pub fn members(&self) -> &[Note] { &self.spelled[self.members.clone()] }
+    pub fn offnotes(&self) -> &[Note] { &self.spelled[self.offnotes.clone()] }
+}
+
+impl fmt::Debug for AnalysisRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AnalysisRecord")
+            .field("pitch_group", &self.pitch_group)
+            .field("probability", &self.probability)
+            .field("members", &self.members())
+            .field("offnotes", &self.offnotes())
+            .finish_non_exhaustive()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::PitchGroup;
-    use crate::analysis::Analyzer;
+    use crate::analysis::{Analyzer, KeyProfile, PitchHistogram};
+    use crate::runtime::NoteEvent;
     use crate::types::{Accidental::*, Note::*, PitchClass::*};
+    use std::time::Duration;
+
+    #[test]
+    fn test_find_keys() {
+        let c_major = [C(Natural), D(Natural), E(Natural), F(Natural), G(Natural), A(Natural), B(Natural), C(Natural), G(Natural), E(Natural)];
+        for profile in KeyProfile::all() {
+            let keys = Analyzer::find_keys(&PitchHistogram::from(&c_major[..]), profile);
+            assert_eq!(keys.len(), 24);
+            assert_eq!((keys[0].tonic, keys[0].minor), (C(Natural), false), "{:?}", profile);
+            assert!(keys.windows(2).all(|w| w[0].correlation >= w[1].correlation));
+        }
+
+        // A held long and loud with E and C under it reads as A minor
+        let mut histogram = PitchHistogram::new();
+        histogram.add(&An, Duration::from_secs(4), 120);
+        histogram.add(&En, Duration::from_secs(2), 100);
+        histogram.add(&Cn, Duration::from_secs(2), 100);
+        histogram.add(&Bn, Duration::from_millis(500), 60);
+        histogram.add(&Dn, Duration::from_millis(500), 60);
+        let key = Analyzer::find_keys(&histogram, KeyProfile::KrumhanslKessler)[0];
+        assert_eq!((key.tonic, key.minor, key.pitch_group), (A(Natural), true, PitchGroup::Cn));
+        assert_eq!(key.to_string(), format!("A minor ({:.3})", key.correlation));
+
+        // Nothing played correlates with nothing
+        assert!(Analyzer::find_keys(&PitchHistogram::new(), KeyProfile::Aarden).iter().all(|k| k.correlation == 0.0));
+    }
+
+    #[test]
+    fn test_weighted_histogram() {
+        let event = |ms: u64, index: u8, velocity: u8| NoteEvent { tick: ms, time: Duration::from_millis(ms), channel: 0, index, velocity };
+        let events = [event(0, 60, 127), event(0, 64, 127), event(1000, 60, 0), event(1000, 67, 127), event(1500, 64, 0), event(2000, 72, 127)];
+        let histogram = PitchHistogram::from_events(&events);
+        // C4 for a second (C5 starts at the end and adds nothing), E until 1.5s, G held to the end
+        assert!((histogram.weight(&Cn) - 1.0).abs() < 1e-9);
+        assert!((histogram.weight(&En) - 1.5).abs() < 1e-9);
+        assert!((histogram.weight(&Gn) - 1.0).abs() < 1e-9);
+        assert!(PitchHistogram::from(&[D(Flat), C(Sharp)][..]).weight(&Cs) == 2.0);
+    }
+
+    #[test]
+    fn test_score_mixed_spellings() {
+        // Sharps and flats together no longer drop every note
+        let analysis = Analyzer::score(&[B(Flat), D(Natural), F(Sharp), G(Natural)]).unwrap();
+        let best = &analysis.records()[0];
+        assert_eq!(best.pitch_group(), PitchGroup::As);
+        assert_eq!(analysis.key().map(|k| (k.tonic, k.minor)), Some((G(Natural), true)));
+        let g_major = analysis.records().iter().find(|r| r.pitch_group() == PitchGroup::Gn).unwrap();
+        assert_eq!(g_major.members(), &[D(Natural), F(Sharp), G(Natural)]);
+        assert_eq!(g_major.offnotes(), &[B(Flat)]);
+    }
 
     #[test]
     fn test_score_pitchgroup() {
@@ -24,7 +24,7 @@ fn main() {
     use std::sync::{Arc, Mutex};
     use tokio::time::{self, sleep, Duration};
     use rodio::{OutputStream, Source, dynamic_mixer};
-    use audiotheorem::{runtime::{Events, Engine, Sequence, Waveform}, types::Tuning};
+    use audiotheorem::{runtime::{Events, Engine, Sequence, FileSource, VoiceManager, WaveformType}, types::Tuning};
 
     const GRID_SIZE: u8 = 12;
 
@@ -41,7 +41,18 @@ fn main() {
     //////////
 
     // Midi Loop = // Used as a buffer to store the midi events for the graphics loop
-    rt.spawn(async move { Events::read_midi(move |index, velocity| { write_theorem.lock().unwrap().process_input(index, velocity); })});
+    // Passing a Standard MIDI File plays it in place of a midi device
+    let midi_file = std::env::args().nth(1);
+    rt.spawn(async move {
+        let process = move |index, velocity| { write_theorem.lock().unwrap().process_input(index, velocity); };
+        match midi_file {
+            Some(path) => match FileSource::open(&path) {
+                Ok(source) => Events::read_from(&mut source.realtime(), process),
+                Err(err) => println!("Error: {}", err)
+            },
+            None => Events::read_midi(process)
+        }
+    });
 
 
 
@@ -49,44 +60,23 @@ fn main() {
     // AUDIO //
     ///////////
 
-    // Audio Loop
-    // rt.spawn(async move {
-    //     // Audio Settings
-    //     let wave_table_size = 1440;     // 120 samples per octave - 10 samples per pitchclass
-    //     let sample_rate = 44100;
-
-    //     let mut wave_table: Vec<f32> = Vec::with_capacity(wave_table_size);
-    //     for i in 0..wave_table_size { wave_table.push((i as f32 / wave_table_size as f32 * 2.0 * std::f32::consts::PI).sin()); }
-
-    //     let (_stream, stream_handle) = OutputStream::try_default().unwrap();
-    //     let sink = Sink::try_new(&stream_handle).unwrap();
-
-    //     loop {
-    //         // get our midi data
-    //         let read_theorem = audio_read_theorem.lock().unwrap().deref().clone();
-    //         let size = read_theorem.get_size();
-            
-    //         // clear the sink
-    //         sink.clear();
-
-    //         // if we have no data, sleep for a bit
-    //         if size <= 0 { let _ = sleep(Duration::from_millis(10)); continue; } 
-
-    //         // create a new mixer
-    //         let (controller, mixer) = dynamic_mixer::mixer::<f32>(2, sample_rate);
-
-    //         // get all the tones and add them to the mixer, and throw them into the sink
-    //         for tone in read_theorem.tones() {
-    //             let mut oscillator = Waveform::new(sample_rate, wave_table.clone());
-    //             oscillator.set_frequency(tone.pitch().frequency(Tuning::A4_440Hz));
-    //             controller.add(oscillator.convert_samples());
-    //         }
-
-    //         // play the sink
-    //         sink.append(mixer);
-    //         sink.sleep_until_end();
-    //     }
-    // });
+    // Audio Loop - the voices follow the sequence, and rodio pulls samples from them on its own thread
+    std::thread::spawn(move || {
+        let sample_rate = 44100;
+        let voices = VoiceManager::new(sample_rate, 32, WaveformType::Sine);
+
+        let (_stream, stream_handle) = match OutputStream::try_default() {
+            Ok(output) => output,
+            Err(err) => { println!("Error: {}", err); return; }
+        };
+        if let Err(err) = stream_handle.play_raw(voices.source()) { println!("Error: {}", err); return; }
+
+        loop {
+            let tones = audio_read_theorem.lock().unwrap().tones();
+            voices.sync(&tones);
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    });
 
 
     //////////////
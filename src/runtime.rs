@@ -2,8 +2,12 @@ mod waveform;
 mod midi;
 mod graphics;
 mod theorem;
+mod render;
+mod voice;
 
 pub use self::graphics::{Engine,TexturedSquare};
-pub use self::midi::Events;
-pub use self::waveform::{Waveform, WaveformType};
-pub use self::theorem::{Sequence, Subsequence, Chord, Tonic, Key, PitchGroupKernel};
\ No newline at end of file
+pub use self::midi::{Events, MidiMessage, StandardMidiFile, NoteEvent, MidiSource, MidiEvent, PortSource, ScriptedSource, FileSource};
+pub use self::waveform::{Waveform, WaveformType, WavetableSet};
+pub use self::render::{Renderer, AudioBuffer, SampleFormat};
+pub use self::voice::{Adsr, Envelope, Stage, Voice, VoiceManager, VoiceSource};
+pub use self::theorem::{Sequence, Subsequence, Chord, ChordQuality, RomanNumeral, HarmonicFunction, Tonic, Key, PitchGroupKernel, Timeline, TimedTonic};
\ No newline at end of file
@@ -1,3 +1,9 @@
 mod events;
+mod message;
+mod smf;
+mod source;
 
-pub use self::events::Events;
\ No newline at end of file
+pub use self::events::Events;
+pub use self::message::{MidiMessage, SUSTAIN_PEDAL};
+pub use self::source::{MidiSource, MidiEvent, MidiCallback, PortSource, ScriptedSource, FileSource};
+pub use self::smf::{StandardMidiFile, Division, Track, TrackEvent, EventKind, MetaEvent, NoteEvent};
\ No newline at end of file
@@ -2,122 +2,148 @@
 // Copyright 2023 Richard I. Christopher, NeoTec Digital. All Rights Reserved.
 //
 
-extern crate midir;
-use std::process::Command;
-use crate::types::*;
-use std::io::{stdin, stdout, Write};
+use std::collections::HashSet;
 use std::error::Error;
-use midir::{MidiInput, MidiOutput, MidiOutputPort, Ignore};
-use rustysynth::{SoundFont, Synthesizer, SynthesizerSettings};
-use std::fs::File;
-use std::sync::Arc;    
+use super::message::{MidiMessage, SUSTAIN_PEDAL};
+use super::source::{MidiSource, PortSource};
 
 #[derive(Copy, Clone, Debug)]
 pub struct Events;
 
 impl Events {
 
+    /// Prompt for a midir input and output port and forward every note to `f` as (index, velocity)
+    pub fn read_midi(f: impl FnMut(u8, u8) + Send + 'static) {
+        match PortSource::select() {
+            Ok(mut source) => Events::read_from(&mut source, f),
+            Err(err) => println!("Error: {}", err)
+        }
+    }
 
-    pub fn read_midi(f: impl FnMut(u8, u8) + Send + Sync + 'static) {
-        match Events::midi_in(f) {
+    /// Forward every note from any [MidiSource] to `f` as (index, velocity)
+    pub fn read_from(source: &mut dyn MidiSource, f: impl FnMut(u8, u8) + Send + 'static) {
+        match Events::listen(source, f) {
             Ok(_) => (),
             Err(err) => println!("Error: {}", err)
         }
     }
 
-    fn midi_in(mut f: impl FnMut(u8, u8) + Send + Sync + 'static) -> Result<(), Box<dyn Error>> {
-        let mut input = String::new();
-        
-        // Midi Input
-        let mut midi_in = MidiInput::new("AudioTheorem_In")?;
-        let midi_out = MidiOutput::new("AudioTheorem_Out")?;
-        midi_in.ignore(Ignore::None);
-
-        // Synthesizer
-        let mut free_pats = File::open("/usr/share/soundfonts/freepats-general-midi.sf2").unwrap();
-        let sound_font = Arc::new(SoundFont::new(&mut free_pats).unwrap());
-        let settings = SynthesizerSettings::new(44100);
-        let mut synthesizer: Synthesizer = Synthesizer::new(&sound_font, &settings).unwrap();
-        let sample_count = (3 * settings.sample_rate) as usize;
-        
-        let in_ports = midi_in.ports();
-        let out_ports = midi_out.ports();
-        let in_port = match in_ports.len() {
-            _ => {
-                //println!("\x1B[2J\x1B[1;1H");
-                println!("=====================");
-                println!("!!! Audio Theorem !!!");
-                println!("=====================\n");
-                println!("\nAvailable Input Devices:");
-                for (i, p) in in_ports.iter().enumerate() {
-                    println!("\t{}: {}", i, midi_in.port_name(p).unwrap());
-                }
-                print!("\nSelect an Input Device: ");
-                stdout().flush()?;
-                let mut input = String::new();
-                stdin().read_line(&mut input)?;
-                in_ports.get(input.trim().parse::<usize>()?)
-                        .ok_or("invalid input port selected")?
-            }
-        };
-
-        print!("\x1B[2J\x1B[1;1H");
-        println!("=====================");
-        println!("!!! Audio Theorem !!!");
-        println!("=====================\n");
-
-        let out_port: &MidiOutputPort = match out_ports.len() {
-            _ => {
-                println!("\nAvailable Output Devices:");
-                for (i, p) in out_ports.iter().enumerate() {
-                    println!("\t{}: {}", i, midi_out.port_name(p).unwrap());
-                }
-                print!("\nSelect an Output Device: ");
-                stdout().flush()?;
-                let mut input = String::new();
-                stdin().read_line(&mut input)?;
-                out_ports.get(input.trim().parse::<usize>()?)
-                         .ok_or("invalid output port selected")?
+    /// Listen to a [MidiSource], passing note-ons and note-offs (velocity 0) to `f` - note-offs are held while the sustain pedal is down
+    pub fn listen(source: &mut dyn MidiSource, mut f: impl FnMut(u8, u8) + Send + 'static) -> Result<(), Box<dyn Error>> {
+        let mut sustain = Sustain::default();
+        source.listen(Box::new(move |event| {
+            if let Ok(message) = event.message() { sustain.process(&message, &mut f); }
+        }))
+    }
+}
+
+// Tracks the sustain pedal of each channel and the notes released while it was down
+#[derive(Clone, Debug, Default)]
+struct Sustain {
+    pedal: [bool; 16],
+    held: HashSet<(u8, u8)>,    // (channel, index)
+}
+
+impl Sustain {
+    fn process(&mut self, message: &MidiMessage, f: &mut impl FnMut(u8, u8)) {
+        match *message {
+            MidiMessage::ControlChange { channel, controller: SUSTAIN_PEDAL, value } => {
+                let down = value >= 64;
+                if !down { self.lift(channel, f); }
+                self.pedal[(channel & 0x0F) as usize] = down;
+                return;
             }
-        };
-        
-        print!("\x1B[2J\x1B[1;1H");
-        println!("!!! Audio Theorem !!!");
-        println!("=====================\n");
-        println!("Connected to Input: {}.\nSending to Output: {}.\n", midi_in.port_name(in_port)?, midi_out.port_name(out_port)?);
-        println!("Press [enter] to Exit.\n");
-
-        let mut conn_out = midi_out.connect(out_port, "audiotheorem")?;
-        let a_ = midi_in.connect(in_port, "readin", move |stamp, message, _| { 
-            let velocity: u8 = message[2];
-            let index: u8 = message[1];
-
-            // Main Audio Processing Loop
-                // process audio as Sequence<Tone>
-                f(index, velocity); 
-
-            
-
-            // play audio
-            if velocity > 0 {
-                let _ = conn_out.send(&[0x90, index, velocity]);
-            } else {
-                let _ = conn_out.send(&[0x80, index, velocity]);
+            // Both put the pedal back up, so nothing is left held with no way to release it
+            MidiMessage::AllNotesOff { channel } | MidiMessage::ResetAllControllers { channel } => {
+                self.lift(channel, f);
+                return;
             }
+            _ => {}
+        }
+
+        let (Some((index, velocity)), Some(channel)) = (message.note(), message.channel()) else { return; };
+        if velocity == 0 && self.pedal[(channel & 0x0F) as usize] {
+            self.held.insert((channel, index));
+            return;
+        }
 
-            // Audio Synthesizer
-            synthesizer.note_on(0, index.into(), velocity.into());
+        // Striking a held note again takes it back from the pedal
+        self.held.remove(&(channel, index));
+        f(index, velocity);
+    }
 
-            let mut left = vec![0.0; sample_count];
-            let mut right = vec![0.0; sample_count];
+    // Lets go of the pedal on a channel, releasing the notes it was holding
+    fn lift(&mut self, channel: u8, f: &mut impl FnMut(u8, u8)) {
+        self.pedal[(channel & 0x0F) as usize] = false;
+        let mut released: Vec<u8> = self.held.iter().filter(|(c, _)| *c == channel).map(|(_, i)| *i).collect();
+        released.sort();
+        self.held.retain(|(c, _)| *c != channel);
+        for index in released { f(index, 0); }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use crate::runtime::{MidiEvent, ScriptedSource};
+
+    fn received(events: Vec<MidiEvent>) -> Vec<(u8, u8)> {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&received);
+        Events::listen(&mut ScriptedSource::new(events), move |index, velocity| sink.lock().unwrap().push((index, velocity))).unwrap();
+        let received = received.lock().unwrap().clone();
+        received
+    }
 
-            synthesizer.render(&mut left[..], &mut right[..]);
+    #[test]
+    fn test_only_notes() {
+        let t = Duration::ZERO;
+        let events = vec![
+            MidiEvent::new(t, 0xB0, &[7, 100]),
+            MidiEvent::new(t, 0xE0, &[0, 64]),
+            MidiEvent::new(t, 0xD0, &[50]),
+            MidiEvent::new(t, 0xF8, &[]),
+            MidiEvent::new(t, 0x90, &[60]),     // too short
+            MidiEvent::note_on(t, 0, 60, 100),
+            MidiEvent::new(t, 0x80, &[60, 64]),
+        ];
+        assert_eq!(received(events), vec![(60, 100), (60, 0)]);
+    }
 
-        }, ())?;
-        
-        input.clear();
-        stdin().read_line(&mut input)?; // waiting for exit 
+    #[test]
+    fn test_sustain() {
+        let t = Duration::ZERO;
+        let pedal = |value| MidiEvent::new(t, 0xB0, &[SUSTAIN_PEDAL, value]);
+        let events = vec![
+            MidiEvent::note_on(t, 0, 60, 100),
+            pedal(127),
+            MidiEvent::note_off(t, 0, 60),
+            MidiEvent::note_on(t, 0, 64, 90),
+            MidiEvent::note_off(t, 0, 64),
+            MidiEvent::note_on(t, 0, 64, 80),     // restruck while held
+            MidiEvent::note_on(t, 1, 67, 70),     // other channels are not sustained
+            MidiEvent::note_off(t, 1, 67),
+            pedal(0),
+        ];
+        assert_eq!(received(events), vec![(60, 100), (64, 90), (64, 80), (67, 70), (67, 0), (60, 0)]);
+    }
 
-        Ok(())
+    #[test]
+    fn test_reset_lifts_sustain() {
+        let t = Duration::ZERO;
+        let pedal = |value| MidiEvent::new(t, 0xB0, &[SUSTAIN_PEDAL, value]);
+        for reset in [123, 121] {
+            let events = vec![
+                MidiEvent::note_on(t, 0, 60, 100),
+                pedal(127),
+                MidiEvent::note_off(t, 0, 60),
+                MidiEvent::new(t, 0xB0, &[reset, 0]),   // all notes off, reset all controllers
+                MidiEvent::note_on(t, 0, 64, 90),
+                MidiEvent::note_off(t, 0, 64),          // no longer held
+            ];
+            assert_eq!(received(events), vec![(60, 100), (60, 0), (64, 90), (64, 0)]);
+        }
     }
-}
\ No newline at end of file
+}
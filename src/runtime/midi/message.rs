@@ -0,0 +1,251 @@
+//
+// Copyright 2024 Richard I. Christopher, NeoTec Digital. All Rights Reserved.
+//
+
+/// Controller number of the sustain (damper) pedal.
+pub const SUSTAIN_PEDAL: u8 = 64;
+
+/// A MIDI 1.0 message. Channels are 0-15 and data values are 7 bit unless noted otherwise.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MidiMessage {
+    // Channel Voice
+    NoteOff { channel: u8, note: u8, velocity: u8 },
+    /// A velocity of 0 is a note-off by convention - see [MidiMessage::note]
+    NoteOn { channel: u8, note: u8, velocity: u8 },
+    PolyphonicAftertouch { channel: u8, note: u8, pressure: u8 },
+    /// Controllers 0-119 - 120-127 are parsed as channel mode messages
+    ControlChange { channel: u8, controller: u8, value: u8 },
+    ProgramChange { channel: u8, program: u8 },
+    ChannelAftertouch { channel: u8, pressure: u8 },
+    /// 14 bit value, 8192 is centered
+    PitchBend { channel: u8, value: u16 },
+
+    // Channel Mode
+    AllSoundOff { channel: u8 },
+    ResetAllControllers { channel: u8 },
+    LocalControl { channel: u8, on: bool },
+    AllNotesOff { channel: u8 },
+    OmniOff { channel: u8 },
+    OmniOn { channel: u8 },
+    /// Number of channels, 0 meaning as many as the receiver has voices
+    MonoOn { channel: u8, channels: u8 },
+    PolyOn { channel: u8 },
+
+    // System Common
+    /// Data between 0xF0 and 0xF7, exclusive
+    SystemExclusive(Vec<u8>),
+    TimeCodeQuarterFrame(u8),
+    /// 14 bit count of sixteenth notes since the start of the song
+    SongPosition(u16),
+    SongSelect(u8),
+    TuneRequest,
+
+    // System Realtime
+    TimingClock,
+    Start,
+    Continue,
+    Stop,
+    ActiveSensing,
+    SystemReset,
+}
+
+impl MidiMessage {
+    /// Parse a complete message - running status must already be expanded.
+    pub fn parse(bytes: &[u8]) -> Result<MidiMessage, &'static str> {
+        let (&status, data) = bytes.split_first().ok_or("Empty MIDI message")?;
+        if status < 0x80 { return Err("MIDI message does not start with a status byte"); }
+
+        // System exclusive carries its own terminator, everything else is strictly 7 bit
+        if status == 0xF0 {
+            let data = data.strip_suffix(&[0xF7]).unwrap_or(data);
+            if data.iter().any(|b| *b > 0x7F) { return Err("Invalid data byte in system exclusive message"); }
+            return Ok(MidiMessage::SystemExclusive(data.to_vec()));
+        }
+        if data.iter().any(|b| *b > 0x7F) { return Err("Invalid data byte in MIDI message"); }
+
+        let length = match status {
+            0x80..=0xBF | 0xE0..=0xEF | 0xF2 => 2,
+            0xC0..=0xDF | 0xF1 | 0xF3 => 1,
+            _ => 0,
+        };
+        if data.len() < length { return Err("MIDI message is too short"); }
+
+        let channel = status & 0x0F;
+        let message = match status & 0xF0 {
+            0x80 => MidiMessage::NoteOff { channel, note: data[0], velocity: data[1] },
+            0x90 => MidiMessage::NoteOn { channel, note: data[0], velocity: data[1] },
+            0xA0 => MidiMessage::PolyphonicAftertouch { channel, note: data[0], pressure: data[1] },
+            0xB0 => match (data[0], data[1]) {
+                (120, _) => MidiMessage::AllSoundOff { channel },
+                (121, _) => MidiMessage::ResetAllControllers { channel },
+                (122, value) => MidiMessage::LocalControl { channel, on: value >= 64 },
+                (123, _) => MidiMessage::AllNotesOff { channel },
+                (124, _) => MidiMessage::OmniOff { channel },
+                (125, _) => MidiMessage::OmniOn { channel },
+                (126, channels) => MidiMessage::MonoOn { channel, channels },
+                (127, _) => MidiMessage::PolyOn { channel },
+                (controller, value) => MidiMessage::ControlChange { channel, controller, value },
+            },
+            0xC0 => MidiMessage::ProgramChange { channel, program: data[0] },
+            0xD0 => MidiMessage::ChannelAftertouch { channel, pressure: data[0] },
+            0xE0 => MidiMessage::PitchBend { channel, value: data[0] as u16 | (data[1] as u16) << 7 },
+            _ => match status {
+                0xF1 => MidiMessage::TimeCodeQuarterFrame(data[0]),
+                0xF2 => MidiMessage::SongPosition(data[0] as u16 | (data[1] as u16) << 7),
+                0xF3 => MidiMessage::SongSelect(data[0]),
+                0xF6 => MidiMessage::TuneRequest,
+                0xF8 => MidiMessage::TimingClock,
+                0xFA => MidiMessage::Start,
+                0xFB => MidiMessage::Continue,
+                0xFC => MidiMessage::Stop,
+                0xFE => MidiMessage::ActiveSensing,
+                0xFF => MidiMessage::SystemReset,
+                _ => return Err("Undefined MIDI status byte"),
+            },
+        };
+
+        Ok(message)
+    }
+
+    /// Serialize the message to its wire format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mode = |channel: u8, controller: u8, value: u8| vec![0xB0 | (channel & 0x0F), controller, value & 0x7F];
+
+        match self {
+            MidiMessage::NoteOff { channel, note, velocity } => vec![0x80 | (channel & 0x0F), note & 0x7F, velocity & 0x7F],
+            MidiMessage::NoteOn { channel, note, velocity } => vec![0x90 | (channel & 0x0F), note & 0x7F, velocity & 0x7F],
+            MidiMessage::PolyphonicAftertouch { channel, note, pressure } => vec![0xA0 | (channel & 0x0F), note & 0x7F, pressure & 0x7F],
+            MidiMessage::ControlChange { channel, controller, value } => mode(*channel, controller & 0x7F, *value),
+            MidiMessage::ProgramChange { channel, program } => vec![0xC0 | (channel & 0x0F), program & 0x7F],
+            MidiMessage::ChannelAftertouch { channel, pressure } => vec![0xD0 | (channel & 0x0F), pressure & 0x7F],
+            MidiMessage::PitchBend { channel, value } => vec![0xE0 | (channel & 0x0F), (value & 0x7F) as u8, ((value >> 7) & 0x7F) as u8],
+            MidiMessage::AllSoundOff { channel } => mode(*channel, 120, 0),
+            MidiMessage::ResetAllControllers { channel } => mode(*channel, 121, 0),
+            MidiMessage::LocalControl { channel, on } => mode(*channel, 122, if *on { 127 } else { 0 }),
+            MidiMessage::AllNotesOff { channel } => mode(*channel, 123, 0),
+            MidiMessage::OmniOff { channel } => mode(*channel, 124, 0),
+            MidiMessage::OmniOn { channel } => mode(*channel, 125, 0),
+            MidiMessage::MonoOn { channel, channels } => mode(*channel, 126, *channels),
+            MidiMessage::PolyOn { channel } => mode(*channel, 127, 0),
+            MidiMessage::SystemExclusive(data) => {
+                let mut bytes = vec![0xF0];
+                bytes.extend(data.iter().map(|b| b & 0x7F));
+                bytes.push(0xF7);
+                bytes
+            }
+            MidiMessage::TimeCodeQuarterFrame(value) => vec![0xF1, value & 0x7F],
+            MidiMessage::SongPosition(value) => vec![0xF2, (value & 0x7F) as u8, ((value >> 7) & 0x7F) as u8],
+            MidiMessage::SongSelect(song) => vec![0xF3, song & 0x7F],
+            MidiMessage::TuneRequest => vec![0xF6],
+            MidiMessage::TimingClock => vec![0xF8],
+            MidiMessage::Start => vec![0xFA],
+            MidiMessage::Continue => vec![0xFB],
+            MidiMessage::Stop => vec![0xFC],
+            MidiMessage::ActiveSensing => vec![0xFE],
+            MidiMessage::SystemReset => vec![0xFF],
+        }
+    }
+
+    /// Channel of channel voice and channel mode messages.
+    pub fn channel(&self) -> Option<u8> {
+        match self {
+            MidiMessage::NoteOff { channel, .. }
+            | MidiMessage::NoteOn { channel, .. }
+            | MidiMessage::PolyphonicAftertouch { channel, .. }
+            | MidiMessage::ControlChange { channel, .. }
+            | MidiMessage::ProgramChange { channel, .. }
+            | MidiMessage::ChannelAftertouch { channel, .. }
+            | MidiMessage::PitchBend { channel, .. }
+            | MidiMessage::AllSoundOff { channel }
+            | MidiMessage::ResetAllControllers { channel }
+            | MidiMessage::LocalControl { channel, .. }
+            | MidiMessage::AllNotesOff { channel }
+            | MidiMessage::OmniOff { channel }
+            | MidiMessage::OmniOn { channel }
+            | MidiMessage::MonoOn { channel, .. }
+            | MidiMessage::PolyOn { channel } => Some(*channel),
+            _ => None,
+        }
+    }
+
+    /// Note as `(index, velocity)` for note-ons and note-offs - note-offs have a velocity of 0.
+    pub fn note(&self) -> Option<(u8, u8)> {
+        match self {
+            MidiMessage::NoteOn { note, velocity, .. } => Some((*note, *velocity)),
+            MidiMessage::NoteOff { note, .. } => Some((*note, 0)),
+            _ => None,
+        }
+    }
+
+    pub fn is_realtime(&self) -> bool {
+        matches!(self, MidiMessage::TimingClock | MidiMessage::Start | MidiMessage::Continue
+                     | MidiMessage::Stop | MidiMessage::ActiveSensing | MidiMessage::SystemReset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let messages = vec![
+            MidiMessage::NoteOff { channel: 0, note: 60, velocity: 64 },
+            MidiMessage::NoteOn { channel: 15, note: 127, velocity: 1 },
+            MidiMessage::PolyphonicAftertouch { channel: 2, note: 61, pressure: 30 },
+            MidiMessage::ControlChange { channel: 3, controller: SUSTAIN_PEDAL, value: 127 },
+            MidiMessage::ProgramChange { channel: 4, program: 5 },
+            MidiMessage::ChannelAftertouch { channel: 5, pressure: 99 },
+            MidiMessage::PitchBend { channel: 6, value: 8192 },
+            MidiMessage::PitchBend { channel: 6, value: 16383 },
+            MidiMessage::AllSoundOff { channel: 7 },
+            MidiMessage::ResetAllControllers { channel: 7 },
+            MidiMessage::LocalControl { channel: 7, on: false },
+            MidiMessage::LocalControl { channel: 7, on: true },
+            MidiMessage::AllNotesOff { channel: 8 },
+            MidiMessage::OmniOff { channel: 8 },
+            MidiMessage::OmniOn { channel: 8 },
+            MidiMessage::MonoOn { channel: 9, channels: 4 },
+            MidiMessage::PolyOn { channel: 9 },
+            MidiMessage::SystemExclusive(vec![0x7E, 0x7F, 0x09, 0x01]),
+            MidiMessage::TimeCodeQuarterFrame(0x35),
+            MidiMessage::SongPosition(0x1234),
+            MidiMessage::SongSelect(3),
+            MidiMessage::TuneRequest,
+            MidiMessage::TimingClock,
+            MidiMessage::Start,
+            MidiMessage::Continue,
+            MidiMessage::Stop,
+            MidiMessage::ActiveSensing,
+            MidiMessage::SystemReset,
+        ];
+
+        for message in messages {
+            assert_eq!(MidiMessage::parse(&message.to_bytes()), Ok(message.clone()), "{:02X?}", message.to_bytes());
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(MidiMessage::parse(&[0xE0, 0x00, 0x40]), Ok(MidiMessage::PitchBend { channel: 0, value: 8192 }));
+        assert_eq!(MidiMessage::parse(&[0xB1, 123, 0]), Ok(MidiMessage::AllNotesOff { channel: 1 }));
+        assert_eq!(MidiMessage::parse(&[0x92, 60, 0]).unwrap().note(), Some((60, 0)));
+        assert_eq!(MidiMessage::parse(&[0x82, 60, 90]).unwrap().note(), Some((60, 0)));
+        assert_eq!(MidiMessage::parse(&[0xB0, 7, 100]).unwrap().note(), None);
+        assert_eq!(MidiMessage::parse(&[0xF0, 1, 2]), Ok(MidiMessage::SystemExclusive(vec![1, 2])));
+        assert!(MidiMessage::parse(&[0xF8]).unwrap().is_realtime());
+        assert_eq!(MidiMessage::parse(&[0xF8]).unwrap().channel(), None);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(MidiMessage::parse(&[]).is_err());
+        assert!(MidiMessage::parse(&[0x90]).is_err());
+        assert!(MidiMessage::parse(&[0x90, 60]).is_err());
+        assert!(MidiMessage::parse(&[0xC0]).is_err());
+        assert!(MidiMessage::parse(&[0x3C, 0x40]).is_err());
+        assert!(MidiMessage::parse(&[0x90, 0x80, 0x40]).is_err());
+        assert!(MidiMessage::parse(&[0xF4]).is_err());
+        assert!(MidiMessage::parse(&[0xFD]).is_err());
+    }
+}
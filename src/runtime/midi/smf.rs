@@ -0,0 +1,660 @@
+//
+// Copyright 2024 Richard I. Christopher, NeoTec Digital. All Rights Reserved.
+//
+//  Standard MIDI File (SMF) reader used to feed recorded performances into a
+//  Sequence without a live midir port, and writer used to export a Timeline.
+//
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{Cursor, Read, Write};
+use std::path::Path;
+use std::time::Duration;
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use crate::runtime::{Sequence, Timeline};
+use super::message::MidiMessage;
+
+/// Default tempo of a Standard MIDI File (120 bpm) in microseconds per quarter note.
+pub const DEFAULT_TEMPO: u32 = 500_000;
+
+/// Controller (General Purpose, undefined) carrying [Tonic::harmony](crate::runtime::Tonic) ahead of each exported note.
+pub const HARMONY_CONTROLLER: u8 = 20;
+/// Channel used for played tones when exporting a [Timeline](crate::runtime::Timeline).
+pub const PLAYED_CHANNEL: u8 = 0;
+/// Channel used for speculative tones when exporting a [Timeline](crate::runtime::Timeline).
+pub const HARMONY_CHANNEL: u8 = 1;
+
+/// Timing resolution stored in the header chunk of a Standard MIDI File.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Division {
+    /// Metrical time - ticks per quarter note.
+    TicksPerQuarter(u16),
+    /// Timecode - SMPTE frames per second and ticks per frame.
+    Smpte { frames_per_second: u8, ticks_per_frame: u8 },
+}
+
+/// Meta events found in a Standard MIDI File track.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MetaEvent {
+    SequenceNumber(u16),
+    /// Text, Copyright, Track Name, Instrument, Lyric, Marker and Cue events (0x01 - 0x0F).
+    Text { kind: u8, text: String },
+    ChannelPrefix(u8),
+    EndOfTrack,
+    /// Microseconds per quarter note.
+    Tempo(u32),
+    SmpteOffset([u8; 5]),
+    /// Denominator is stored as the actual note value (4 = quarter note) rather than the power of 2.
+    TimeSignature { numerator: u8, denominator: u8, clocks_per_click: u8, notated_32nds_per_quarter: u8 },
+    /// Positive for sharps, negative for flats.
+    KeySignature { sharps: i8, minor: bool },
+    SequencerSpecific(Vec<u8>),
+    Unknown { kind: u8, data: Vec<u8> },
+}
+
+/// Body of an event found in a Standard MIDI File track.
+#[derive(Clone, Debug, PartialEq)]
+pub enum EventKind {
+    /// Channel message as raw bytes (status followed by data), with running status already expanded.
+    Midi(Vec<u8>),
+    /// System exclusive message, without the length prefix.
+    SysEx(Vec<u8>),
+    Meta(MetaEvent),
+}
+
+/// An event in a track stamped with its absolute tick.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrackEvent {
+    pub tick: u64,
+    pub kind: EventKind,
+}
+
+/// A single MTrk chunk.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Track {
+    pub events: Vec<TrackEvent>,
+}
+
+/// A note-on or note-off in real time, in the same `(index, velocity)` form given to
+/// [Sequence::process_input](crate::runtime::Sequence::process_input) - note-offs have a velocity of 0.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NoteEvent {
+    pub tick: u64,
+    pub time: Duration,
+    pub channel: u8,
+    pub index: u8,
+    pub velocity: u8,
+}
+
+/// A parsed Standard MIDI File (format 0 or 1).
+#[derive(Clone, Debug, PartialEq)]
+pub struct StandardMidiFile {
+    pub format: u16,
+    pub division: Division,
+    pub tracks: Vec<Track>,
+}
+
+impl StandardMidiFile {
+    /// Read a Standard MIDI File from disk.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<StandardMidiFile, Box<dyn Error>> {
+        let mut bytes = Vec::new();
+        File::open(path)?.read_to_end(&mut bytes)?;
+        Ok(StandardMidiFile::from_bytes(&bytes)?)
+    }
+
+    /// Parse a Standard MIDI File from memory.
+    pub fn from_bytes(bytes: &[u8]) -> Result<StandardMidiFile, &'static str> {
+        let mut cursor = Cursor::new(bytes);
+
+        let (id, length) = read_chunk_header(&mut cursor)?;
+        if &id != b"MThd" || length < 6 { return Err("Missing MThd header chunk"); }
+        let format = cursor.read_u16::<BigEndian>().map_err(|_| EOF)?;
+        let track_count = cursor.read_u16::<BigEndian>().map_err(|_| EOF)?;
+        let raw_division = cursor.read_u16::<BigEndian>().map_err(|_| EOF)?;
+        skip(&mut cursor, length as u64 - 6)?;
+
+        if format > 1 { return Err("Unsupported SMF format - only formats 0 and 1 are supported"); }
+        if format == 0 && track_count != 1 { return Err("Format 0 files must contain a single track"); }
+
+        let division = if raw_division & 0x8000 == 0 {
+            if raw_division == 0 { return Err("Division must not be zero"); }
+            Division::TicksPerQuarter(raw_division)
+        } else {
+            // The high byte is the frame rate negated - only -24, -25, -29 (30 drop frame) and -30 are valid
+            let frames_per_second = ((raw_division >> 8) as u8 as i8).wrapping_neg() as u8;
+            let ticks_per_frame = (raw_division & 0xFF) as u8;
+            if !matches!(frames_per_second, 24 | 25 | 29 | 30) { return Err("Invalid SMPTE frame rate"); }
+            if ticks_per_frame == 0 { return Err("Invalid SMPTE division"); }
+            Division::Smpte { frames_per_second, ticks_per_frame }
+        };
+
+        let mut tracks = Vec::with_capacity(track_count as usize);
+        while tracks.len() < track_count as usize {
+            let (id, length) = read_chunk_header(&mut cursor)?;
+            let start = cursor.position() as usize;
+            let end = start.checked_add(length as usize).filter(|end| *end <= bytes.len()).ok_or(EOF)?;
+            cursor.set_position(end as u64);
+
+            // Alien chunks are to be ignored
+            if &id != b"MTrk" { continue; }
+            tracks.push(read_track(&bytes[start..end])?);
+        }
+
+        Ok(StandardMidiFile { format, division, tracks })
+    }
+
+    /// First tempo (microseconds per quarter note) found in the file, or 120bpm if there is none.
+    pub fn tempo(&self) -> u32 {
+        self.meta_events().find_map(|e| match e { MetaEvent::Tempo(t) => Some(*t), _ => None }).unwrap_or(DEFAULT_TEMPO)
+    }
+
+    /// First time signature found in the file as (numerator, denominator).
+    pub fn time_signature(&self) -> Option<(u8, u8)> {
+        self.meta_events().find_map(|e| match e { MetaEvent::TimeSignature { numerator, denominator, .. } => Some((*numerator, *denominator)), _ => None })
+    }
+
+    /// First key signature found in the file as (sharps, minor).
+    pub fn key_signature(&self) -> Option<(i8, bool)> {
+        self.meta_events().find_map(|e| match e { MetaEvent::KeySignature { sharps, minor } => Some((*sharps, *minor)), _ => None })
+    }
+
+    fn meta_events(&self) -> impl Iterator<Item = &MetaEvent> {
+        self.tracks.iter()
+                   .flat_map(|t| t.events.iter())
+                   .filter_map(|e| match &e.kind { EventKind::Meta(m) => Some(m), _ => None })
+    }
+
+    /// Every event of every track merged in time order as (tick, time), with tempo changes applied.
+    pub fn timed_events(&self) -> Vec<(u64, Duration, &EventKind)> {
+        // Merge the tracks - a stable sort keeps the track order for events on the same tick
+        let mut events: Vec<&TrackEvent> = self.tracks.iter().flat_map(|t| t.events.iter()).collect();
+        events.sort_by_key(|e| e.tick);
+
+        let mut timed = Vec::with_capacity(events.len());
+        let mut tempo = DEFAULT_TEMPO as u64;
+        let mut last_tick = 0u64;
+        let mut elapsed = 0u128; // nanoseconds
+
+        for event in events {
+            elapsed += self.tick_nanos(event.tick - last_tick, tempo);
+            last_tick = event.tick;
+
+            if let EventKind::Meta(MetaEvent::Tempo(t)) = &event.kind { tempo = *t as u64; }
+            timed.push((event.tick, Duration::from_nanos(elapsed as u64), &event.kind));
+        }
+
+        timed
+    }
+
+    /// All note-on and note-off events of every track merged in time order, with tempo changes applied.
+    pub fn notes(&self) -> Vec<NoteEvent> {
+        self.timed_events().into_iter().filter_map(|(tick, time, kind)| match kind {
+            EventKind::Midi(bytes) => {
+                let message = MidiMessage::parse(bytes).ok()?;
+                let (index, velocity) = message.note()?;
+                Some(NoteEvent { tick, time, channel: message.channel()?, index, velocity })
+            }
+            _ => None,
+        }).collect()
+    }
+
+    // Length of a number of ticks in nanoseconds at the given tempo
+    fn tick_nanos(&self, ticks: u64, tempo: u64) -> u128 {
+        match self.division {
+            Division::TicksPerQuarter(tpq) => ticks as u128 * tempo as u128 * 1000 / tpq as u128,
+            Division::Smpte { frames_per_second, ticks_per_frame } => {
+                // 29 is drop frame - 29.97 frames per second
+                let (num, den) = if frames_per_second == 29 { (1001u128, 30_000u128) } else { (1u128, frames_per_second as u128) };
+                ticks as u128 * 1_000_000_000 * num / (den * ticks_per_frame as u128)
+            }
+        }
+    }
+
+    /// Replay every note of the file into a [Sequence](crate::runtime::Sequence) as if it were played live.
+    pub fn replay(&self, sequence: &mut Sequence) {
+        for note in self.notes() {
+            sequence.process_input(note.index, note.velocity);
+        }
+    }
+
+    /// Build a format 1 file from a [Timeline](crate::runtime::Timeline) - a tempo track, a track of
+    /// played tones and a track of speculative tones, with each tone's harmony sent on [HARMONY_CONTROLLER].
+    pub fn from_timeline(timeline: &Timeline, ticks_per_quarter: u16, tempo: u32) -> StandardMidiFile {
+        let to_tick = |d: Duration| (d.as_nanos() * ticks_per_quarter as u128 / (tempo as u128 * 1000)) as u64;
+
+        let conductor = Track { events: vec![
+            TrackEvent { tick: 0, kind: EventKind::Meta(MetaEvent::Tempo(tempo)) },
+            TrackEvent { tick: 0, kind: EventKind::Meta(MetaEvent::TimeSignature { numerator: 4, denominator: 4, clocks_per_click: 24, notated_32nds_per_quarter: 8 }) },
+            TrackEvent { tick: to_tick(timeline.duration()), kind: EventKind::Meta(MetaEvent::EndOfTrack) },
+        ]};
+
+        let mut tracks = vec![conductor];
+        for (name, channel, played) in [("Played", PLAYED_CHANNEL, true), ("Harmony", HARMONY_CHANNEL, false)] {
+            // (tick, order, event) - note-offs sort ahead of note-ons on the same tick so repeated notes retrigger
+            let mut events: Vec<(u64, u8, EventKind)> = Vec::new();
+            for timed in timeline.tones.iter().filter(|t| t.is_played() == played && t.tonic.index < 128) {
+                let (start, end) = (to_tick(timed.start), to_tick(timed.end()));
+                let index = timed.tonic.index;
+                events.push((start, 1, EventKind::Midi(vec![0xB0 | channel, HARMONY_CONTROLLER, timed.tonic.harmony.min(127)])));
+                events.push((start, 2, EventKind::Midi(vec![0x90 | channel, index, timed.tonic.velocity.clamp(1, 127)])));
+                events.push((end, 0, EventKind::Midi(vec![0x80 | channel, index, 0])));
+            }
+            events.sort_by_key(|(tick, order, _)| (*tick, *order));
+
+            let mut track = Track { events: vec![TrackEvent { tick: 0, kind: EventKind::Meta(MetaEvent::Text { kind: 0x03, text: name.to_string() }) }] };
+            track.events.extend(events.into_iter().map(|(tick, _, kind)| TrackEvent { tick, kind }));
+            let last = track.events.last().map_or(0, |e| e.tick);
+            track.events.push(TrackEvent { tick: last, kind: EventKind::Meta(MetaEvent::EndOfTrack) });
+            tracks.push(track);
+        }
+
+        StandardMidiFile { format: 1, division: Division::TicksPerQuarter(ticks_per_quarter), tracks }
+    }
+
+    /// Write the file to disk.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        File::create(path)?.write_all(&self.to_bytes())?;
+        Ok(())
+    }
+
+    /// Serialize the file - events are written in the order they appear in each track.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let division = match self.division {
+            Division::TicksPerQuarter(tpq) => tpq,
+            Division::Smpte { frames_per_second, ticks_per_frame } => ((frames_per_second as i8).wrapping_neg() as u8 as u16) << 8 | ticks_per_frame as u16,
+        };
+
+        bytes.extend(b"MThd");
+        bytes.write_u32::<BigEndian>(6).unwrap();
+        bytes.write_u16::<BigEndian>(self.format).unwrap();
+        bytes.write_u16::<BigEndian>(self.tracks.len() as u16).unwrap();
+        bytes.write_u16::<BigEndian>(division).unwrap();
+
+        for track in &self.tracks {
+            let body = write_track(track);
+            bytes.extend(b"MTrk");
+            bytes.write_u32::<BigEndian>(body.len() as u32).unwrap();
+            bytes.extend(body);
+        }
+
+        bytes
+    }
+}
+
+const EOF: &str = "Unexpected end of file";
+
+fn read_chunk_header(cursor: &mut Cursor<&[u8]>) -> Result<([u8; 4], u32), &'static str> {
+    let mut id = [0u8; 4];
+    cursor.read_exact(&mut id).map_err(|_| EOF)?;
+    let length = cursor.read_u32::<BigEndian>().map_err(|_| EOF)?;
+    Ok((id, length))
+}
+
+fn skip(cursor: &mut Cursor<&[u8]>, count: u64) -> Result<(), &'static str> {
+    let position = cursor.position() + count;
+    if position > cursor.get_ref().len() as u64 { return Err(EOF); }
+    cursor.set_position(position);
+    Ok(())
+}
+
+// Variable length quantity - 7 bits per byte, most significant first, at most 4 bytes
+fn read_vlq(cursor: &mut Cursor<&[u8]>) -> Result<u32, &'static str> {
+    let mut value = 0u32;
+    for _ in 0..4 {
+        let byte = cursor.read_u8().map_err(|_| EOF)?;
+        value = (value << 7) | (byte & 0x7F) as u32;
+        if byte & 0x80 == 0 { return Ok(value); }
+    }
+    Err("Variable length quantity is longer than 4 bytes")
+}
+
+fn read_bytes(cursor: &mut Cursor<&[u8]>, length: usize) -> Result<Vec<u8>, &'static str> {
+    let mut data = vec![0u8; length];
+    cursor.read_exact(&mut data).map_err(|_| EOF)?;
+    Ok(data)
+}
+
+// Number of data bytes following a channel status byte
+fn data_length(status: u8) -> usize {
+    match status & 0xF0 {
+        0xC0 | 0xD0 => 1,
+        _ => 2,
+    }
+}
+
+fn read_track(bytes: &[u8]) -> Result<Track, &'static str> {
+    let mut cursor = Cursor::new(bytes);
+    let mut events = Vec::new();
+    let mut tick = 0u64;
+    let mut running_status: Option<u8> = None;
+
+    while (cursor.position() as usize) < bytes.len() {
+        tick += read_vlq(&mut cursor)? as u64;
+        let first = cursor.read_u8().map_err(|_| EOF)?;
+
+        let kind = match first {
+            0xFF => {
+                running_status = None;
+                let kind = cursor.read_u8().map_err(|_| EOF)?;
+                let length = read_vlq(&mut cursor)? as usize;
+                let data = read_bytes(&mut cursor, length)?;
+                EventKind::Meta(parse_meta(kind, data)?)
+            }
+            0xF0 | 0xF7 => {
+                running_status = None;
+                let length = read_vlq(&mut cursor)? as usize;
+                EventKind::SysEx(read_bytes(&mut cursor, length)?)
+            }
+            0x80..=0xEF => {
+                running_status = Some(first);
+                let mut message = vec![first];
+                message.extend(read_bytes(&mut cursor, data_length(first))?);
+                EventKind::Midi(message)
+            }
+            0x00..=0x7F => {
+                // Running Status - the byte we just read is the first data byte
+                let status = running_status.ok_or("Data byte without running status")?;
+                let mut message = vec![status, first];
+                message.extend(read_bytes(&mut cursor, data_length(status) - 1)?);
+                EventKind::Midi(message)
+            }
+            _ => return Err("Unexpected system message in track"),
+        };
+
+        let end = kind == EventKind::Meta(MetaEvent::EndOfTrack);
+        events.push(TrackEvent { tick, kind });
+        if end { break; }
+    }
+
+    Ok(Track { events })
+}
+
+fn parse_meta(kind: u8, data: Vec<u8>) -> Result<MetaEvent, &'static str> {
+    let event = match (kind, data.len()) {
+        (0x00, 2) => MetaEvent::SequenceNumber(u16::from_be_bytes([data[0], data[1]])),
+        (0x01..=0x0F, _) => MetaEvent::Text { kind, text: String::from_utf8_lossy(&data).into_owned() },
+        (0x20, 1) => MetaEvent::ChannelPrefix(data[0]),
+        (0x2F, _) => MetaEvent::EndOfTrack,
+        (0x51, 3) => MetaEvent::Tempo(u32::from_be_bytes([0, data[0], data[1], data[2]])),
+        (0x54, 5) => MetaEvent::SmpteOffset([data[0], data[1], data[2], data[3], data[4]]),
+        (0x58, 4) => MetaEvent::TimeSignature {
+            numerator: data[0],
+            denominator: 1u8.checked_shl(data[1] as u32).ok_or("Invalid time signature denominator")?,
+            clocks_per_click: data[2],
+            notated_32nds_per_quarter: data[3],
+        },
+        (0x59, 2) => MetaEvent::KeySignature { sharps: data[0] as i8, minor: data[1] == 1 },
+        (0x7F, _) => MetaEvent::SequencerSpecific(data),
+        (0x00, _) | (0x20, _) | (0x51, _) | (0x54, _) | (0x58, _) | (0x59, _) => return Err("Invalid meta event length"),
+        (_, _) => MetaEvent::Unknown { kind, data },
+    };
+    Ok(event)
+}
+
+fn write_vlq(bytes: &mut Vec<u8>, value: u32) {
+    let value = value & 0x0FFF_FFFF;
+    for shift in [21, 14, 7] {
+        if value >> shift > 0 { bytes.push(((value >> shift) & 0x7F) as u8 | 0x80); }
+    }
+    bytes.push((value & 0x7F) as u8);
+}
+
+fn write_track(track: &Track) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut last_tick = 0u64;
+
+    for event in &track.events {
+        write_vlq(&mut bytes, event.tick.saturating_sub(last_tick) as u32);
+        last_tick = last_tick.max(event.tick);
+
+        match &event.kind {
+            EventKind::Midi(message) => bytes.extend(message),
+            EventKind::SysEx(data) => {
+                bytes.push(0xF0);
+                write_vlq(&mut bytes, data.len() as u32);
+                bytes.extend(data);
+            }
+            EventKind::Meta(meta) => {
+                let (kind, data) = unparse_meta(meta);
+                bytes.push(0xFF);
+                bytes.push(kind);
+                write_vlq(&mut bytes, data.len() as u32);
+                bytes.extend(data);
+            }
+        }
+    }
+
+    // Every track must be terminated
+    if track.events.last().map(|e| &e.kind) != Some(&EventKind::Meta(MetaEvent::EndOfTrack)) {
+        bytes.extend([0x00, 0xFF, 0x2F, 0x00]);
+    }
+
+    bytes
+}
+
+fn unparse_meta(meta: &MetaEvent) -> (u8, Vec<u8>) {
+    match meta {
+        MetaEvent::SequenceNumber(n) => (0x00, n.to_be_bytes().to_vec()),
+        MetaEvent::Text { kind, text } => (*kind, text.as_bytes().to_vec()),
+        MetaEvent::ChannelPrefix(c) => (0x20, vec![*c]),
+        MetaEvent::EndOfTrack => (0x2F, vec![]),
+        MetaEvent::Tempo(t) => (0x51, t.to_be_bytes()[1..].to_vec()),
+        MetaEvent::SmpteOffset(o) => (0x54, o.to_vec()),
+        MetaEvent::TimeSignature { numerator, denominator, clocks_per_click, notated_32nds_per_quarter } =>
+            (0x58, vec![*numerator, denominator.trailing_zeros() as u8, *clocks_per_click, *notated_32nds_per_quarter]),
+        MetaEvent::KeySignature { sharps, minor } => (0x59, vec![*sharps as u8, *minor as u8]),
+        MetaEvent::SequencerSpecific(data) => (0x7F, data.clone()),
+        MetaEvent::Unknown { kind, data } => (*kind, data.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::Tonic;
+
+    fn vlq(mut value: u32) -> Vec<u8> {
+        let mut bytes = vec![(value & 0x7F) as u8];
+        value >>= 7;
+        while value > 0 {
+            bytes.insert(0, (value & 0x7F) as u8 | 0x80);
+            value >>= 7;
+        }
+        bytes
+    }
+
+    fn chunk(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut bytes = id.to_vec();
+        bytes.extend((body.len() as u32).to_be_bytes());
+        bytes.extend(body);
+        bytes
+    }
+
+    fn header(format: u16, tracks: u16, division: u16) -> Vec<u8> {
+        let mut body = format.to_be_bytes().to_vec();
+        body.extend(tracks.to_be_bytes());
+        body.extend(division.to_be_bytes());
+        chunk(b"MThd", &body)
+    }
+
+    #[test]
+    fn test_vlq() {
+        for value in [0u32, 0x40, 0x7F, 0x80, 0x2000, 0x3FFF, 0x4000, 0x0FFF_FFFF] {
+            let bytes = vlq(value);
+            assert_eq!(read_vlq(&mut Cursor::new(&bytes[..])), Ok(value), "{:X} -> {:02X?}", value, bytes);
+        }
+        assert!(read_vlq(&mut Cursor::new(&[0xFF, 0xFF, 0xFF, 0xFF, 0x7F][..])).is_err());
+    }
+
+    #[test]
+    fn test_format_0_running_status() {
+        let mut track = Vec::new();
+        // Tempo 60bpm, 4/4, E minor
+        track.extend([0x00, 0xFF, 0x51, 0x03, 0x0F, 0x42, 0x40]);
+        track.extend([0x00, 0xFF, 0x58, 0x04, 0x04, 0x02, 0x18, 0x08]);
+        track.extend([0x00, 0xFF, 0x59, 0x02, 0x01, 0x01]);
+        // C major triad using running status, released by note-on with velocity 0 and a note-off
+        track.extend([0x00, 0x90, 60, 100]);
+        track.extend([0x00, 64, 90]);
+        track.extend([0x00, 67, 80]);
+        track.extend(vlq(96)); track.extend([60, 0]);
+        track.extend([0x00, 0x80, 64, 64]);
+        track.extend([0x00, 67, 0]);
+        track.extend([0x00, 0xFF, 0x2F, 0x00]);
+
+        let mut bytes = header(0, 1, 96);
+        bytes.extend(chunk(b"MTrk", &track));
+
+        let smf = StandardMidiFile::from_bytes(&bytes).unwrap();
+        assert_eq!(smf.format, 0);
+        assert_eq!(smf.division, Division::TicksPerQuarter(96));
+        assert_eq!(smf.tempo(), 1_000_000);
+        assert_eq!(smf.time_signature(), Some((4, 4)));
+        assert_eq!(smf.key_signature(), Some((1, true)));
+
+        let notes = smf.notes();
+        let pairs: Vec<(u8, u8)> = notes.iter().map(|n| (n.index, n.velocity)).collect();
+        assert_eq!(pairs, vec![(60, 100), (64, 90), (67, 80), (60, 0), (64, 0), (67, 0)]);
+        assert_eq!(notes[2].time, Duration::ZERO);
+        assert_eq!(notes[3].tick, 96);
+        assert_eq!(notes[3].time, Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_format_1_tempo_map() {
+        // Conductor track: 120bpm, then 60bpm from the second beat
+        let mut conductor = Vec::new();
+        conductor.extend([0x00, 0xFF, 0x51, 0x03, 0x07, 0xA1, 0x20]);
+        conductor.extend(vlq(480)); conductor.extend([0xFF, 0x51, 0x03, 0x0F, 0x42, 0x40]);
+        conductor.extend([0x00, 0xFF, 0x2F, 0x00]);
+
+        let mut melody = Vec::new();
+        melody.extend([0x00, 0xFF, 0x03, 0x04]); melody.extend(b"Lead");
+        melody.extend([0x00, 0x91, 69, 100]);
+        melody.extend(vlq(960)); melody.extend([0x81, 69, 0]);
+        melody.extend([0x00, 0xB1, 64, 127]);
+        melody.extend([0x00, 0xC1, 5]);
+        melody.extend([0x00, 0xFF, 0x2F, 0x00]);
+
+        let mut bytes = header(1, 2, 480);
+        bytes.extend(chunk(b"MTrk", &conductor));
+        bytes.extend(chunk(b"XFIH", &[1, 2, 3]));
+        bytes.extend(chunk(b"MTrk", &melody));
+
+        let smf = StandardMidiFile::from_bytes(&bytes).unwrap();
+        assert_eq!(smf.tracks.len(), 2);
+        assert_eq!(smf.tracks[1].events[0].kind, EventKind::Meta(MetaEvent::Text { kind: 0x03, text: "Lead".to_string() }));
+        assert_eq!(smf.tracks[1].events[4].kind, EventKind::Midi(vec![0xC1, 5]));
+
+        let notes = smf.notes();
+        assert_eq!(notes.len(), 2);
+        assert_eq!(notes[0].channel, 1);
+        // One beat at 120bpm and one beat at 60bpm
+        assert_eq!(notes[1].time, Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn test_smpte_division() {
+        let mut track = vec![0x00, 0x90, 60, 100];
+        track.extend(vlq(100)); track.extend([0x80, 60, 0]);
+        track.extend([0x00, 0xFF, 0x2F, 0x00]);
+
+        // 25 frames per second, 40 ticks per frame = 1ms per tick
+        let mut bytes = header(0, 1, ((-25i8 as u8 as u16) << 8) | 40);
+        bytes.extend(chunk(b"MTrk", &track));
+
+        let smf = StandardMidiFile::from_bytes(&bytes).unwrap();
+        assert_eq!(smf.division, Division::Smpte { frames_per_second: 25, ticks_per_frame: 40 });
+        assert_eq!(smf.notes()[1].time, Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_invalid_files() {
+        assert!(StandardMidiFile::from_bytes(b"RIFF").is_err());
+        assert!(StandardMidiFile::from_bytes(&header(2, 1, 96)).is_err());
+        // Truncated track
+        let mut bytes = header(0, 1, 96);
+        bytes.extend(chunk(b"MTrk", &[0x00, 0x90, 60]));
+        assert!(StandardMidiFile::from_bytes(&bytes).is_err());
+        // Data byte without a status byte
+        let mut bytes = header(0, 1, 96);
+        bytes.extend(chunk(b"MTrk", &[0x00, 60, 100]));
+        assert!(StandardMidiFile::from_bytes(&bytes).is_err());
+        // SMPTE frame rates other than 24, 25, 29 and 30 - 0x80 would overflow when negated
+        for high in [0x80u16, 0xE0, 0xFF] {
+            let mut bytes = header(0, 1, high << 8 | 40);
+            bytes.extend(chunk(b"MTrk", &[0x00, 0xFF, 0x2F, 0x00]));
+            assert_eq!(StandardMidiFile::from_bytes(&bytes).err(), Some("Invalid SMPTE frame rate"));
+        }
+    }
+
+    #[test]
+    fn test_replay_into_sequence() {
+        let mut track = Vec::new();
+        track.extend([0x00, 0x90, 60, 100, 0x00, 64, 100, 0x00, 67, 100]);
+        track.extend(vlq(96)); track.extend([64, 0]);
+        track.extend([0x00, 0xFF, 0x2F, 0x00]);
+
+        let mut bytes = header(0, 1, 96);
+        bytes.extend(chunk(b"MTrk", &track));
+        let smf = StandardMidiFile::from_bytes(&bytes).unwrap();
+
+        let mut sequence = Sequence::new();
+        smf.replay(&mut sequence);
+        let mut held: Vec<u8> = sequence.sequences.iter().flat_map(|s| s.tones.iter().map(|t| t.index)).collect();
+        held.sort();
+        assert_eq!(held, vec![60, 67]);
+    }
+
+    #[test]
+    fn test_write_round_trip() {
+        for value in [0u32, 0x7F, 0x80, 0x3FFF, 0x4000, 0x0FFF_FFFF] {
+            let mut bytes = Vec::new();
+            write_vlq(&mut bytes, value);
+            assert_eq!(bytes, vlq(value));
+        }
+
+        let mut track = Vec::new();
+        track.extend([0x00, 0xFF, 0x51, 0x03, 0x0F, 0x42, 0x40]);
+        track.extend([0x00, 0xFF, 0x58, 0x04, 0x03, 0x03, 0x18, 0x08]);
+        track.extend([0x00, 0xFF, 0x59, 0x02, 0xFD, 0x00]);
+        track.extend([0x00, 0xF0, 0x03, 0x7E, 0x7F, 0xF7]);
+        track.extend([0x00, 0x90, 60, 100, 0x00, 64, 90]);
+        track.extend(vlq(200)); track.extend([0x80, 60, 0, 0x00, 64, 0]);
+        track.extend([0x00, 0xFF, 0x2F, 0x00]);
+        let mut bytes = header(0, 1, 96);
+        bytes.extend(chunk(b"MTrk", &track));
+
+        let smf = StandardMidiFile::from_bytes(&bytes).unwrap();
+        assert_eq!(StandardMidiFile::from_bytes(&smf.to_bytes()).unwrap(), smf);
+        assert_eq!(smf.key_signature(), Some((-3, false)));
+    }
+
+    #[test]
+    fn test_write_timeline() {
+        let mut timeline = Timeline::new();
+        timeline.push(Duration::ZERO, Duration::from_secs(1), Tonic::new(60, 100, 0));
+        timeline.push(Duration::ZERO, Duration::from_secs(1), Tonic::new(67, 40, 3));
+        timeline.push(Duration::from_secs(1), Duration::from_millis(500), Tonic::new(60, 90, 0));
+
+        let smf = StandardMidiFile::from_bytes(&StandardMidiFile::from_timeline(&timeline, 480, DEFAULT_TEMPO).to_bytes()).unwrap();
+        assert_eq!(smf.format, 1);
+        assert_eq!(smf.tracks.len(), 3);
+
+        let played: Vec<&EventKind> = smf.tracks[1].events.iter().map(|e| &e.kind).collect();
+        assert_eq!(played[0], &EventKind::Meta(MetaEvent::Text { kind: 0x03, text: "Played".to_string() }));
+        assert_eq!(played[1], &EventKind::Midi(vec![0xB0, HARMONY_CONTROLLER, 0]));
+        assert_eq!(played[2], &EventKind::Midi(vec![0x90, 60, 100]));
+        // The repeated note is released before it is struck again
+        assert_eq!(played[3], &EventKind::Midi(vec![0x80, 60, 0]));
+        assert_eq!(played[5], &EventKind::Midi(vec![0x90, 60, 90]));
+        assert_eq!(smf.tracks[2].events[1].kind, EventKind::Midi(vec![0xB1, HARMONY_CONTROLLER, 3]));
+
+        let notes = smf.notes();
+        let harmony: Vec<(u8, u8, u8)> = notes.iter().filter(|n| n.channel == HARMONY_CHANNEL).map(|n| (n.index, n.velocity, n.tick as u8)).collect();
+        assert_eq!(harmony.len(), 2);
+        assert_eq!(harmony[0], (67, 40, 0));
+        assert_eq!(notes.last().unwrap().time, Duration::from_millis(1500));
+    }
+}
@@ -0,0 +1,329 @@
+//
+// Copyright 2024 Richard I. Christopher, NeoTec Digital. All Rights Reserved.
+//
+//  Sources of MIDI input - hardware ports through midir, scripted events and
+//  Standard MIDI Files - so the runtime can be driven without a device.
+//
+
+use std::error::Error;
+use std::io::{stdin, stdout, Write};
+use std::path::Path;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+use midir::{Ignore, MidiInput, MidiInputPort, MidiOutput, MidiOutputConnection};
+use super::message::MidiMessage;
+use super::smf::{EventKind, StandardMidiFile};
+
+/// Callback given every message produced by a [MidiSource].
+pub type MidiCallback = Box<dyn FnMut(MidiEvent) + Send + 'static>;
+
+/// A complete channel or system message received from a [MidiSource].
+#[derive(Clone, Debug, PartialEq)]
+pub struct MidiEvent {
+    /// Time since the source started.
+    pub timestamp: Duration,
+    /// Low nibble of the status byte - only meaningful for channel messages.
+    pub channel: u8,
+    /// Full status byte, including the channel.
+    pub status: u8,
+    /// Data bytes following the status byte.
+    pub data: Vec<u8>,
+}
+
+impl MidiEvent {
+    pub fn new(timestamp: Duration, status: u8, data: &[u8]) -> MidiEvent {
+        let channel = if status < 0xF0 { status & 0x0F } else { 0 };
+        MidiEvent { timestamp, channel, status, data: data.to_vec() }
+    }
+
+    /// Split a raw message into status and data. Returns None if the message has no status byte.
+    pub fn from_bytes(timestamp: Duration, bytes: &[u8]) -> Option<MidiEvent> {
+        match bytes.split_first() {
+            Some((status, data)) if *status >= 0x80 => Some(MidiEvent::new(timestamp, *status, data)),
+            _ => None,
+        }
+    }
+
+    pub fn note_on(timestamp: Duration, channel: u8, index: u8, velocity: u8) -> MidiEvent {
+        MidiEvent::new(timestamp, 0x90 | (channel & 0x0F), &[index, velocity])
+    }
+
+    pub fn note_off(timestamp: Duration, channel: u8, index: u8) -> MidiEvent {
+        MidiEvent::new(timestamp, 0x80 | (channel & 0x0F), &[index, 0])
+    }
+
+    /// Parse the event into a typed [MidiMessage].
+    pub fn message(&self) -> Result<MidiMessage, &'static str> { MidiMessage::parse(&self.bytes()) }
+
+    /// Note as `(index, velocity)` if this is a note-on or note-off - note-offs have a velocity of 0.
+    pub fn note(&self) -> Option<(u8, u8)> { self.message().ok()?.note() }
+
+    /// Raw message bytes - status followed by data.
+    pub fn bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![self.status];
+        bytes.extend(&self.data);
+        bytes
+    }
+}
+
+/// Something that produces MIDI messages.
+pub trait MidiSource {
+    /// Deliver messages to the callback, blocking until the source is exhausted or closed.
+    fn listen(&mut self, callback: MidiCallback) -> Result<(), Box<dyn Error>>;
+}
+
+// Deliver events in order, waiting for each timestamp to arrive if playing in realtime
+fn play(events: &[MidiEvent], realtime: bool, mut callback: MidiCallback) {
+    let start = Instant::now();
+    for event in events {
+        if realtime {
+            if let Some(wait) = event.timestamp.checked_sub(start.elapsed()) { sleep(wait); }
+        }
+        callback(event.clone());
+    }
+}
+
+/// A hardware or virtual port opened through midir, with optional MIDI thru to an output port.
+pub struct PortSource {
+    input: Option<MidiInput>,
+    port: MidiInputPort,
+    thru: Option<MidiOutputConnection>,
+}
+
+impl PortSource {
+    /// Names of the available input ports.
+    pub fn ports() -> Result<Vec<String>, Box<dyn Error>> {
+        let input = MidiInput::new("AudioTheorem_In")?;
+        Ok(input.ports().iter().filter_map(|p| input.port_name(p).ok()).collect())
+    }
+
+    /// Open the first input port whose name contains `name`.
+    pub fn connect(name: &str) -> Result<PortSource, Box<dyn Error>> {
+        let mut input = MidiInput::new("AudioTheorem_In")?;
+        input.ignore(Ignore::None);
+        let port = input.ports().into_iter()
+                        .find(|p| input.port_name(p).is_ok_and(|n| n.contains(name)))
+                        .ok_or("no matching input port")?;
+        Ok(PortSource { input: Some(input), port, thru: None })
+    }
+
+    /// Echo every message received to the first output port whose name contains `name`.
+    pub fn thru(mut self, name: &str) -> Result<PortSource, Box<dyn Error>> {
+        let output = MidiOutput::new("AudioTheorem_Out")?;
+        let port = output.ports().into_iter()
+                         .find(|p| output.port_name(p).is_ok_and(|n| n.contains(name)))
+                         .ok_or("no matching output port")?;
+        self.thru = Some(output.connect(&port, "audiotheorem")?);
+        Ok(self)
+    }
+
+    /// Prompt on stdin for the input and output ports.
+    pub fn select() -> Result<PortSource, Box<dyn Error>> {
+        let mut input = MidiInput::new("AudioTheorem_In")?;
+        let output = MidiOutput::new("AudioTheorem_Out")?;
+        input.ignore(Ignore::None);
+
+        let in_ports = input.ports();
+        let out_ports = output.ports();
+
+        println!("=====================");
+        println!("!!! Audio Theorem !!!");
+        println!("=====================\n");
+        println!("\nAvailable Input Devices:");
+        for (i, p) in in_ports.iter().enumerate() {
+            println!("\t{}: {}", i, input.port_name(p)?);
+        }
+        let port = in_ports.get(PortSource::prompt("Select an Input Device: ")?)
+                           .ok_or("invalid input port selected")?
+                           .clone();
+
+        print!("\x1B[2J\x1B[1;1H");
+        println!("=====================");
+        println!("!!! Audio Theorem !!!");
+        println!("=====================\n");
+        println!("\nAvailable Output Devices:");
+        for (i, p) in out_ports.iter().enumerate() {
+            println!("\t{}: {}", i, output.port_name(p)?);
+        }
+        let out_port = out_ports.get(PortSource::prompt("Select an Output Device: ")?)
+                                .ok_or("invalid output port selected")?;
+
+        print!("\x1B[2J\x1B[1;1H");
+        println!("!!! Audio Theorem !!!");
+        println!("=====================\n");
+        println!("Connected to Input: {}.\nSending to Output: {}.\n", input.port_name(&port)?, output.port_name(out_port)?);
+
+        let thru = Some(output.connect(out_port, "audiotheorem")?);
+        Ok(PortSource { input: Some(input), port, thru })
+    }
+
+    fn prompt(message: &str) -> Result<usize, Box<dyn Error>> {
+        print!("\n{}", message);
+        stdout().flush()?;
+        let mut input = String::new();
+        stdin().read_line(&mut input)?;
+        Ok(input.trim().parse::<usize>()?)
+    }
+}
+
+impl MidiSource for PortSource {
+    /// Listens until [enter] is pressed.
+    fn listen(&mut self, mut callback: MidiCallback) -> Result<(), Box<dyn Error>> {
+        let input = self.input.take().ok_or("input port is already listening")?;
+        let mut thru = self.thru.take();
+
+        let connection = input.connect(&self.port, "readin", move |stamp, message, _| {
+            if let Some(out) = thru.as_mut() { let _ = out.send(message); }
+            if let Some(event) = MidiEvent::from_bytes(Duration::from_micros(stamp), message) { callback(event); }
+        }, ())?;
+
+        println!("Press [enter] to Exit.\n");
+        let mut line = String::new();
+        stdin().read_line(&mut line)?; // waiting for exit
+
+        let (input, _) = connection.close();
+        self.input = Some(input);
+        Ok(())
+    }
+}
+
+/// Events scripted in memory, delivered immediately or paced by their timestamps.
+#[derive(Clone, Debug, Default)]
+pub struct ScriptedSource {
+    pub events: Vec<MidiEvent>,
+    pub realtime: bool,
+}
+
+impl ScriptedSource {
+    pub fn new(events: Vec<MidiEvent>) -> ScriptedSource { ScriptedSource { events, realtime: false } }
+
+    /// Pace the events by their timestamps rather than delivering them all at once.
+    pub fn realtime(mut self) -> ScriptedSource { self.realtime = true; self }
+
+    pub fn push(&mut self, event: MidiEvent) { self.events.push(event); }
+
+    /// Strike a note at `start` and release it after `duration`.
+    pub fn note(&mut self, start: Duration, duration: Duration, channel: u8, index: u8, velocity: u8) {
+        self.push(MidiEvent::note_on(start, channel, index, velocity));
+        self.push(MidiEvent::note_off(start + duration, channel, index));
+        self.events.sort_by_key(|e| e.timestamp);
+    }
+}
+
+impl MidiSource for ScriptedSource {
+    fn listen(&mut self, callback: MidiCallback) -> Result<(), Box<dyn Error>> {
+        play(&self.events, self.realtime, callback);
+        Ok(())
+    }
+}
+
+/// Replays the channel messages of a [StandardMidiFile].
+#[derive(Clone, Debug)]
+pub struct FileSource {
+    pub smf: StandardMidiFile,
+    pub realtime: bool,
+}
+
+impl FileSource {
+    pub fn new(smf: StandardMidiFile) -> FileSource { FileSource { smf, realtime: false } }
+
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<FileSource, Box<dyn Error>> {
+        Ok(FileSource::new(StandardMidiFile::open(path)?))
+    }
+
+    /// Pace the events as the file would be played rather than delivering them all at once.
+    pub fn realtime(mut self) -> FileSource { self.realtime = true; self }
+
+    pub fn events(&self) -> Vec<MidiEvent> {
+        self.smf.timed_events()
+                .into_iter()
+                .filter_map(|(_, time, kind)| match kind {
+                    EventKind::Midi(bytes) => MidiEvent::from_bytes(time, bytes),
+                    _ => None,
+                })
+                .collect()
+    }
+}
+
+impl MidiSource for FileSource {
+    fn listen(&mut self, callback: MidiCallback) -> Result<(), Box<dyn Error>> {
+        play(&self.events(), self.realtime, callback);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use crate::runtime::{Events, Sequence};
+
+    #[test]
+    fn test_midi_event() {
+        let event = MidiEvent::from_bytes(Duration::ZERO, &[0x93, 60, 100]).unwrap();
+        assert_eq!((event.channel, event.status, event.data.clone()), (3, 0x93, vec![60, 100]));
+        assert_eq!(event.note(), Some((60, 100)));
+        assert_eq!(event.bytes(), vec![0x93, 60, 100]);
+        assert_eq!(MidiEvent::from_bytes(Duration::ZERO, &[0x83, 60, 64]).unwrap().note(), Some((60, 0)));
+        // Controllers and system messages are not notes
+        assert_eq!(MidiEvent::from_bytes(Duration::ZERO, &[0xB0, 64, 127]).unwrap().note(), None);
+        assert_eq!(MidiEvent::from_bytes(Duration::ZERO, &[0xF8]).unwrap().channel, 0);
+        assert_eq!(MidiEvent::from_bytes(Duration::ZERO, &[60, 100]), None);
+    }
+
+    #[test]
+    fn test_scripted_source() {
+        let mut source = ScriptedSource::new(vec![MidiEvent::new(Duration::ZERO, 0xB0, &[64, 127])]);
+        source.note(Duration::from_millis(10), Duration::from_millis(20), 0, 64, 90);
+        source.note(Duration::ZERO, Duration::from_millis(20), 0, 60, 100);
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&received);
+        source.listen(Box::new(move |event| sink.lock().unwrap().push(event))).unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 5);
+        assert!(received.windows(2).all(|w| w[0].timestamp <= w[1].timestamp));
+        assert_eq!(received.last().unwrap().note(), Some((64, 0)));
+    }
+
+    #[test]
+    fn test_realtime_pacing() {
+        let mut source = ScriptedSource::new(vec![MidiEvent::note_on(Duration::from_millis(30), 0, 60, 100)]).realtime();
+        let start = Instant::now();
+        source.listen(Box::new(|_| {})).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(30));
+    }
+
+    #[test]
+    fn test_sequence_from_source() {
+        let mut source = ScriptedSource::default();
+        for index in [60, 64, 67] { source.push(MidiEvent::note_on(Duration::ZERO, 0, index, 100)); }
+        source.push(MidiEvent::new(Duration::ZERO, 0xB0, &[7, 100]));
+        source.push(MidiEvent::note_off(Duration::ZERO, 0, 64));
+
+        let sequence = Arc::new(Mutex::new(Sequence::new()));
+        let writer = Arc::clone(&sequence);
+        Events::listen(&mut source, move |index, velocity| writer.lock().unwrap().process_input(index, velocity)).unwrap();
+
+        let sequence = sequence.lock().unwrap();
+        let mut held: Vec<u8> = sequence.sequences.iter().flat_map(|s| s.tones.iter().map(|t| t.index)).collect();
+        held.sort();
+        assert_eq!(held, vec![60, 67]);
+    }
+
+    #[test]
+    fn test_file_source() {
+        let mut track = vec![0x00, 0xFF, 0x51, 0x03, 0x0F, 0x42, 0x40, 0x00, 0x90, 60, 100, 0x60, 0x80, 60, 0];
+        track.extend([0x00, 0xFF, 0x2F, 0x00]);
+        let mut bytes = b"MThd\0\0\0\x06\0\0\0\x01\0\x60MTrk".to_vec();
+        bytes.extend((track.len() as u32).to_be_bytes());
+        bytes.extend(track);
+
+        let source = FileSource::new(StandardMidiFile::from_bytes(&bytes).unwrap());
+        let events = source.events();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].note(), Some((60, 0)));
+        assert_eq!(events[1].timestamp, Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,277 @@
+//
+// Copyright 2024 Richard I. Christopher, NeoTec Digital. All Rights Reserved.
+//
+//  Offline rendering of a Timeline to sample buffers and WAV files, so audio
+//  can be produced and checked without an output device.
+//
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+use byteorder::{LittleEndian, WriteBytesExt};
+use rustysynth::Synthesizer;
+use crate::types::{Pitch, Tuning};
+use super::{Timeline, Waveform};
+
+/// Channel used by the [Synthesizer] for played tones.
+pub const PLAYED_CHANNEL: i32 = 0;
+/// Channel used by the [Synthesizer] for speculative tones.
+pub const HARMONY_CHANNEL: i32 = 1;
+
+/// Sample encodings for WAV output.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SampleFormat {
+    Int16,
+    Int24,
+    Float32,
+}
+
+impl SampleFormat {
+    pub fn bits(&self) -> u16 {
+        match self {
+            SampleFormat::Int16 => 16,
+            SampleFormat::Int24 => 24,
+            SampleFormat::Float32 => 32,
+        }
+    }
+}
+
+/// Interleaved floating point samples.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AudioBuffer {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub samples: Vec<f32>,
+}
+
+impl AudioBuffer {
+    pub fn new(sample_rate: u32, channels: u16, frames: usize) -> AudioBuffer {
+        AudioBuffer { sample_rate, channels, samples: vec![0.0; frames * channels as usize] }
+    }
+
+    pub fn frames(&self) -> usize { self.samples.len() / self.channels as usize }
+    pub fn duration(&self) -> Duration { Duration::from_secs_f64(self.frames() as f64 / self.sample_rate as f64) }
+    pub fn peak(&self) -> f32 { self.samples.iter().fold(0.0, |peak, s| peak.max(s.abs())) }
+
+    /// Samples of a single channel.
+    pub fn channel(&self, channel: u16) -> impl Iterator<Item = f32> + '_ {
+        self.samples.iter().skip(channel as usize).step_by(self.channels as usize).copied()
+    }
+
+    /// Write a RIFF/WAVE file - integer formats are clipped to [-1.0, 1.0].
+    pub fn write_wav<W: Write>(&self, writer: &mut W, format: SampleFormat) -> io::Result<()> {
+        let bytes_per_sample = format.bits() as u32 / 8;
+        let block_align = self.channels as u32 * bytes_per_sample;
+        let data_length = self.samples.len() as u32 * bytes_per_sample;
+        let float = format == SampleFormat::Float32;
+
+        // Non-PCM formats carry an extension size in fmt and need a fact chunk
+        let fmt_length: u32 = if float { 18 } else { 16 };
+        let fact_length: u32 = if float { 12 } else { 0 };
+
+        writer.write_all(b"RIFF")?;
+        writer.write_u32::<LittleEndian>(4 + (8 + fmt_length) + fact_length + (8 + data_length))?;
+        writer.write_all(b"WAVE")?;
+
+        writer.write_all(b"fmt ")?;
+        writer.write_u32::<LittleEndian>(fmt_length)?;
+        writer.write_u16::<LittleEndian>(if float { 3 } else { 1 })?;
+        writer.write_u16::<LittleEndian>(self.channels)?;
+        writer.write_u32::<LittleEndian>(self.sample_rate)?;
+        writer.write_u32::<LittleEndian>(self.sample_rate * block_align)?;
+        writer.write_u16::<LittleEndian>(block_align as u16)?;
+        writer.write_u16::<LittleEndian>(format.bits())?;
+        if float {
+            writer.write_u16::<LittleEndian>(0)?;
+            writer.write_all(b"fact")?;
+            writer.write_u32::<LittleEndian>(4)?;
+            writer.write_u32::<LittleEndian>(self.frames() as u32)?;
+        }
+
+        writer.write_all(b"data")?;
+        writer.write_u32::<LittleEndian>(data_length)?;
+        for sample in &self.samples {
+            let clipped = sample.clamp(-1.0, 1.0);
+            match format {
+                SampleFormat::Int16 => writer.write_i16::<LittleEndian>(quantize(clipped, 32_767.0, -32_768.0) as i16)?,
+                SampleFormat::Int24 => writer.write_i24::<LittleEndian>(quantize(clipped, 8_388_607.0, -8_388_608.0))?,
+                SampleFormat::Float32 => writer.write_f32::<LittleEndian>(*sample)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn save_wav<P: AsRef<Path>>(&self, path: P, format: SampleFormat) -> Result<(), Box<dyn Error>> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_wav(&mut writer, format)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+// Scale a clipped sample to the integer range, keeping -1.0 and 1.0 at the extremes
+fn quantize(sample: f32, max: f32, min: f32) -> i32 {
+    let scaled = if sample < 0.0 { sample * -min } else { sample * max };
+    scaled.round().clamp(min, max) as i32
+}
+
+/// Renders a [Timeline] offline, faster than realtime and without an audio device.
+#[derive(Copy, Clone, Debug)]
+pub struct Renderer {
+    pub sample_rate: u32,
+    pub tuning: Tuning,
+    /// Gain applied to every tone on top of its velocity.
+    pub gain: f32,
+    /// Fade in and out applied to every waveform tone to avoid clicks.
+    pub fade: Duration,
+    /// Extra time rendered after the last tone ends, to let synthesizer releases ring out.
+    pub tail: Duration,
+}
+
+impl Renderer {
+    pub fn new(sample_rate: u32) -> Renderer {
+        Renderer {
+            sample_rate,
+            tuning: Tuning::A4_440Hz,
+            gain: 1.0,
+            fade: Duration::from_millis(5),
+            tail: Duration::ZERO,
+        }
+    }
+
+    fn frame(&self, time: Duration) -> usize { (time.as_secs_f64() * self.sample_rate as f64).round() as usize }
+
+    fn frames(&self, timeline: &Timeline) -> usize { self.frame(timeline.duration() + self.tail) }
+
+    /// Mono render of every tone with its own copy of the `waveform` oscillator.
+    pub fn render_waveform(&self, timeline: &Timeline, waveform: &Waveform) -> AudioBuffer {
+        let mut buffer = AudioBuffer::new(self.sample_rate, 1, self.frames(timeline));
+        let fade = self.frame(self.fade).max(1);
+
+        for timed in timeline.tones.iter().filter(|t| t.tonic.index < 144) {
+            let start = self.frame(timed.start);
+            let length = self.frame(timed.end()).min(buffer.frames()) - start.min(buffer.frames());
+            let amplitude = self.gain * timed.tonic.velocity.min(127) as f32 / 127.0;
+
+            let mut oscillator = waveform.clone();
+            oscillator.sample_rate = self.sample_rate;
+            oscillator.reset_phase();
+            oscillator.set_frequency(Pitch::from_index(timed.tonic.index).frequency(self.tuning));
+
+            for (i, sample) in oscillator.take(length).enumerate() {
+                let envelope = (i.min(length - 1 - i) as f32 / fade as f32).min(1.0);
+                buffer.samples[start + i] += sample * amplitude * envelope;
+            }
+        }
+
+        buffer
+    }
+
+    /// Stereo render through a SoundFont [Synthesizer] - played tones on [PLAYED_CHANNEL] and speculative tones on [HARMONY_CHANNEL].
+    pub fn render_synthesizer(&self, timeline: &Timeline, synthesizer: &mut Synthesizer) -> Result<AudioBuffer, &'static str> {
+        if synthesizer.get_sample_rate() != self.sample_rate as i32 { return Err("Synthesizer sample rate does not match the renderer"); }
+
+        // (frame, on, channel, key, velocity) - note-offs sort ahead of note-ons on the same frame so repeated notes retrigger
+        let mut events: Vec<(usize, bool, i32, i32, i32)> = Vec::new();
+        for timed in timeline.tones.iter().filter(|t| t.tonic.index < 128) {
+            let channel = if timed.is_played() { PLAYED_CHANNEL } else { HARMONY_CHANNEL };
+            let velocity = (self.gain * timed.tonic.velocity as f32).round().clamp(1.0, 127.0) as i32;
+            events.push((self.frame(timed.start), true, channel, timed.tonic.index as i32, velocity));
+            events.push((self.frame(timed.end()), false, channel, timed.tonic.index as i32, 0));
+        }
+        events.sort_by_key(|(frame, on, ..)| (*frame, *on));
+
+        let frames = self.frames(timeline);
+        let mut left = vec![0.0; frames];
+        let mut right = vec![0.0; frames];
+        let mut position = 0;
+
+        synthesizer.reset();
+        for (frame, on, channel, key, velocity) in events {
+            let frame = frame.min(frames);
+            if frame > position {
+                synthesizer.render(&mut left[position..frame], &mut right[position..frame]);
+                position = frame;
+            }
+            if on { synthesizer.note_on(channel, key, velocity); } else { synthesizer.note_off(channel, key); }
+        }
+        synthesizer.render(&mut left[position..], &mut right[position..]);
+
+        let mut buffer = AudioBuffer::new(self.sample_rate, 2, 0);
+        buffer.samples = left.into_iter().zip(right).flat_map(|(l, r)| [l, r]).collect();
+        Ok(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::{Tonic, WaveformType};
+
+    fn sine() -> Waveform { Waveform::with_type(8000, WaveformType::Sine) }
+
+    #[test]
+    fn test_render_waveform() {
+        let mut timeline = Timeline::new();
+        timeline.push(Duration::from_millis(100), Duration::from_millis(500), Tonic::new(69, 127, 0));
+
+        let renderer = Renderer::new(8000);
+        let buffer = renderer.render_waveform(&timeline, &sine());
+        assert_eq!(buffer.frames(), 4800);
+        assert_eq!(buffer.duration(), Duration::from_millis(600));
+
+        // Silent until the tone starts
+        assert!(buffer.samples[..800].iter().all(|s| *s == 0.0));
+        assert!((buffer.peak() - 1.0).abs() < 0.01, "{}", buffer.peak());
+
+        // 220 cycles of A4 in half a second
+        let crossings = buffer.samples[800..].windows(2).filter(|w| w[0] < 0.0 && w[1] >= 0.0).count();
+        assert!((219..=221).contains(&crossings), "{}", crossings);
+
+        // Rendering is deterministic
+        assert_eq!(renderer.render_waveform(&timeline, &sine()), buffer);
+    }
+
+    #[test]
+    fn test_velocity_and_mixing() {
+        let mut timeline = Timeline::new();
+        timeline.push(Duration::ZERO, Duration::from_millis(100), Tonic::new(69, 64, 0));
+        let quiet = Renderer::new(8000).render_waveform(&timeline, &sine());
+        assert!((quiet.peak() - 64.0 / 127.0).abs() < 0.01);
+
+        timeline.push(Duration::ZERO, Duration::from_millis(100), Tonic::new(69, 64, 1));
+        let doubled = Renderer::new(8000).render_waveform(&timeline, &sine());
+        assert!(doubled.samples.iter().zip(&quiet.samples).all(|(d, q)| (d - 2.0 * q).abs() < 1e-6));
+    }
+
+    #[test]
+    fn test_write_wav() {
+        let mut buffer = AudioBuffer::new(48000, 2, 2);
+        buffer.samples = vec![1.0, -1.0, 0.5, 2.0];
+
+        let mut bytes = Vec::new();
+        buffer.write_wav(&mut bytes, SampleFormat::Int16).unwrap();
+        assert_eq!(bytes.len(), 44 + 8);
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(bytes[4..8].try_into().unwrap()), 36 + 8);
+        assert_eq!(u16::from_le_bytes([bytes[20], bytes[21]]), 1);
+        assert_eq!(u32::from_le_bytes(bytes[28..32].try_into().unwrap()), 48000 * 4);
+        assert_eq!(&bytes[44..], &[0xFF, 0x7F, 0x00, 0x80, 0x00, 0x40, 0xFF, 0x7F]);
+
+        let mut bytes = Vec::new();
+        buffer.write_wav(&mut bytes, SampleFormat::Int24).unwrap();
+        assert_eq!(u16::from_le_bytes([bytes[32], bytes[33]]), 6);
+        assert_eq!(&bytes[44..50], &[0xFF, 0xFF, 0x7F, 0x00, 0x00, 0x80]);
+
+        let mut bytes = Vec::new();
+        buffer.write_wav(&mut bytes, SampleFormat::Float32).unwrap();
+        assert_eq!(u16::from_le_bytes([bytes[20], bytes[21]]), 3);
+        assert_eq!(&bytes[38..42], b"fact");
+        assert_eq!(u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize, bytes.len() - 8);
+        // Float samples are written unclipped
+        assert_eq!(f32::from_le_bytes(bytes[bytes.len() - 4..].try_into().unwrap()), 2.0);
+    }
+}
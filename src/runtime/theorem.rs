@@ -3,11 +3,15 @@ mod key;
 mod sequence;
 mod subsequence;
 mod chord;
+mod roman;
 mod tonic;
+mod timeline;
 
 pub use self::pitchgroupkernel::PitchGroupKernel;
 pub use self::key::Key;
 pub use self::sequence::Sequence;
 pub use self::subsequence::Subsequence;
-pub use self::chord::Chord;
-pub use self::tonic::Tonic;
\ No newline at end of file
+pub use self::chord::{Chord, ChordQuality};
+pub use self::roman::{RomanNumeral, HarmonicFunction};
+pub use self::tonic::Tonic;
+pub use self::timeline::{Timeline, TimedTonic};
\ No newline at end of file
@@ -1,17 +1,645 @@
+//
+// Copyright 2024 Richard I. Christopher, NeoTec Digital. All Rights Reserved.
+//
 
 // Chord is a struct that represents a chord e.g. 'best attempt' to find a 'root' in music theory. It is a collection of notes that are played simultaneously.
-use crate::types::{Note, Interval};
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+use super::Tonic;
+use crate::types::{Interval, MajorQuality, Note, PerfectQuality, PitchClass};
 
+// Shorthands for the chord tones we name
+const M2: Interval = Interval::Second(MajorQuality::Major);
+const M3: Interval = Interval::Third(MajorQuality::Major);
+const MIN3: Interval = Interval::Third(MajorQuality::Minor);
+const P4: Interval = Interval::Fourth(PerfectQuality::Perfect);
+const D5: Interval = Interval::Fifth(PerfectQuality::Diminished);
+const P5: Interval = Interval::Fifth(PerfectQuality::Perfect);
+const A5: Interval = Interval::Fifth(PerfectQuality::Augmented);
+const M6: Interval = Interval::Sixth(MajorQuality::Major);
+const D7: Interval = Interval::Seventh(MajorQuality::Diminished);
+const MIN7: Interval = Interval::Seventh(MajorQuality::Minor);
+const M7: Interval = Interval::Seventh(MajorQuality::Major);
 
-#[derive(Clone, Debug)]
-pub struct Chord { 
-    // This isn't much of a chord, but it's an interface for a "scale" 
-    // to act as a cursor for all possible scales and N number of potential chords based on inversions.
-    // We can use this to reduce interval sets and determine how we want to filter the scales (proprietary - all rights reserved - Ancillary, 2024)
+/// The triad or seventh chord at the core of a [Chord], before extensions.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ChordQuality {
+    Major,
+    Minor,
+    Diminished,
+    Augmented,
+    MajorFlat5,
+    Suspended2,
+    Suspended4,
+    Power,
+    Dominant7,
+    Dominant7Flat5,
+    Major7,
+    Major7Flat5,
+    Minor7,
+    MinorMajor7,
+    HalfDiminished7,
+    Diminished7,
+    DiminishedMajor7,
+    Augmented7,
+    AugmentedMajor7,
+    Dominant7Suspended4,
+    Dominant7Suspended2,
+}
+
+impl ChordQuality {
+    /// Intervals above the root that make up this quality.
+    pub fn intervals(&self) -> &'static [Interval] {
+        match self {
+            ChordQuality::Major => &[M3, P5],
+            ChordQuality::Minor => &[MIN3, P5],
+            ChordQuality::Diminished => &[MIN3, D5],
+            ChordQuality::Augmented => &[M3, A5],
+            ChordQuality::MajorFlat5 => &[M3, D5],
+            ChordQuality::Suspended2 => &[M2, P5],
+            ChordQuality::Suspended4 => &[P4, P5],
+            ChordQuality::Power => &[P5],
+            ChordQuality::Dominant7 => &[M3, P5, MIN7],
+            ChordQuality::Dominant7Flat5 => &[M3, D5, MIN7],
+            ChordQuality::Major7 => &[M3, P5, M7],
+            ChordQuality::Major7Flat5 => &[M3, D5, M7],
+            ChordQuality::Minor7 => &[MIN3, P5, MIN7],
+            ChordQuality::MinorMajor7 => &[MIN3, P5, M7],
+            ChordQuality::HalfDiminished7 => &[MIN3, D5, MIN7],
+            ChordQuality::Diminished7 => &[MIN3, D5, D7],
+            ChordQuality::DiminishedMajor7 => &[MIN3, D5, M7],
+            ChordQuality::Augmented7 => &[M3, A5, MIN7],
+            ChordQuality::AugmentedMajor7 => &[M3, A5, M7],
+            ChordQuality::Dominant7Suspended4 => &[P4, P5, MIN7],
+            ChordQuality::Dominant7Suspended2 => &[M2, P5, MIN7],
+        }
+    }
+
+    /// Whether this quality has a seventh, so a major sixth above the root is a thirteenth rather than an added sixth.
+    pub fn has_seventh(&self) -> bool { self.intervals().len() == 3 }
+
+    // Qualities from the third (or suspension), fifth and seventh found above a root
+    fn from_parts(third: Option<Interval>, fifth: Option<Interval>, seventh: Option<Interval>) -> Option<ChordQuality> {
+        use ChordQuality::*;
+        Some(match (third, fifth, seventh) {
+            (Some(M3), Some(A5), None) => Augmented,
+            (Some(M3), Some(A5), Some(MIN7)) => Augmented7,
+            (Some(M3), Some(A5), Some(M7)) => AugmentedMajor7,
+            (Some(M3), Some(D5), None) => MajorFlat5,
+            (Some(M3), Some(D5), Some(MIN7)) => Dominant7Flat5,
+            (Some(M3), Some(D5), Some(M7)) => Major7Flat5,
+            (Some(M3), _, None) => Major,
+            (Some(M3), _, Some(MIN7)) => Dominant7,
+            (Some(M3), _, Some(M7)) => Major7,
+            (Some(MIN3), Some(D5), None) => Diminished,
+            (Some(MIN3), Some(D5), Some(MIN7)) => HalfDiminished7,
+            (Some(MIN3), Some(D5), Some(D7)) => Diminished7,
+            (Some(MIN3), Some(D5), Some(M7)) => DiminishedMajor7,
+            // Suspensions and power chords keep a perfect fifth
+            (_, Some(D5), _) => return None,
+            (Some(MIN3), _, None) => Minor,
+            (Some(MIN3), _, Some(MIN7)) => Minor7,
+            (Some(MIN3), _, Some(M7)) => MinorMajor7,
+            (Some(P4), _, None) => Suspended4,
+            (Some(P4), _, Some(MIN7)) => Dominant7Suspended4,
+            (Some(M2), _, None) => Suspended2,
+            (Some(M2), _, Some(MIN7)) => Dominant7Suspended2,
+            (None, Some(P5), None) => Power,
+            (None, _, Some(MIN7)) => Dominant7,
+            (None, _, Some(M7)) => Major7,
+            _ => return None,
+        })
+    }
+}
+
+/// A recognized [Chord] - a root and [ChordQuality], any extensions (6, 9, 11, 13 and their alterations),
+/// the bass note it is voiced over, and the tones that were missing from or extra to what was played.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Chord {
     root: Note,
-    intervals: Vec<(Note, Interval)> // We could just refactor this to just be intervals or even just have a function that returns the intervals (which we already have elsewhere)
-    // we need to add a vector where we add a gradient based the maximum derivation and the minimum derivation 
-        // ++ while !> /2
-    // and bounds of 14ths, and splitting sequences based on scale and interval limits
-        // .. we could plug machine learning here in the future to determine the best chord for a given set of notes (proprietary Nexus.)
-}
\ No newline at end of file
+    bass: Note,
+    quality: ChordQuality,
+    extensions: Vec<Interval>,
+    missing: Vec<Interval>,
+    extra: Vec<Note>,
+    // This is an interface for a "scale" to act as a cursor for all possible scales and N number of potential chords based on inversions.
+    // We can use this to reduce interval sets and determine how we want to filter the scales (proprietary - all rights reserved - Ancillary, 2024)
+    intervals: Vec<(Note, Interval)>,
+}
+
+impl Chord {
+    /// A root position [Chord] with no extensions.
+    pub fn new(root: Note, quality: ChordQuality) -> Chord {
+        Chord::from_parts(root, root, quality, Vec::new(), Vec::new(), Vec::new())
+    }
+
+    /// Add an extension or alteration above the root, e.g. `Interval::Ninth(MajorQuality::Minor)` for a b9.
+    pub fn with_extension(self, extension: Interval) -> Chord {
+        let mut extensions = self.extensions;
+        if !extensions.contains(&extension) { extensions.push(extension); }
+        Chord::from_parts(self.root, self.bass, self.quality, extensions, self.missing, self.extra)
+    }
+
+    /// Voice this [Chord] over another bass note - an inversion when the bass is a chord tone, a slash chord otherwise.
+    pub fn over(self, bass: Note) -> Chord {
+        Chord::from_parts(self.root, bass, self.quality, self.extensions, self.missing, self.extra)
+    }
+
+    fn from_parts(root: Note, bass: Note, quality: ChordQuality, mut extensions: Vec<Interval>, missing: Vec<Interval>, extra: Vec<Note>) -> Chord {
+        extensions.sort_by_key(|i| (Chord::order(i), i.steps().value()));
+        let intervals = std::iter::once(Interval::First(PerfectQuality::Perfect))
+            .chain(quality.intervals().iter().copied())
+            .chain(extensions.iter().copied())
+            .filter_map(|interval| (root + interval).map(|note| (note, interval)))
+            .collect();
+        Chord { root, bass, quality, extensions, missing, extra, intervals }
+    }
+
+    pub fn root(&self) -> Note { self.root }
+    pub fn bass(&self) -> Note { self.bass }
+    pub fn quality(&self) -> ChordQuality { self.quality }
+    pub fn extensions(&self) -> &[Interval] { &self.extensions }
+    /// Chord tones that were not played, e.g. the fifth of a voicing that omits it.
+    pub fn missing(&self) -> &[Interval] { &self.missing }
+    /// Played notes that the chord does not explain.
+    pub fn extra(&self) -> &[Note] { &self.extra }
+    /// Every chord tone spelled from the root, with its interval above the root.
+    pub fn intervals(&self) -> &[(Note, Interval)] { &self.intervals }
+    /// Every chord tone spelled from the root.
+    pub fn notes(&self) -> Vec<Note> { self.intervals.iter().map(|(note, _)| *note).collect() }
+
+    /// 0 for root position, 1 with the third (or suspension) in the bass, 2 with the fifth and 3 with the seventh (or added sixth).
+    /// None when the bass is not one of these, as in a slash chord.
+    pub fn inversion(&self) -> Option<u8> {
+        let bass = self.bass.pitch_class();
+        if bass == self.root.pitch_class() { return Some(0); }
+        let position = |interval: &Interval| (self.root.pitch_class() + *interval) == bass;
+        let core = self.quality.intervals();
+        if let Some(i) = core.iter().position(position) {
+            // A power chord only has a fifth
+            return Some(if self.quality == ChordQuality::Power { 2 } else { i as u8 + 1 });
+        }
+        if !self.quality.has_seventh() && self.extensions.contains(&M6) && position(&M6) { return Some(3); }
+        None
+    }
+
+    /// Whether the bass is something other than the root.
+    pub fn is_slash(&self) -> bool { self.bass.pitch_class() != self.root.pitch_class() }
+
+    /// The best [Chord] for a set of notes ordered from lowest to highest - the first note is the bass.
+    pub fn identify(notes: &[Note]) -> Option<Chord> {
+        Chord::candidates(notes).into_iter().next()
+    }
+
+    /// Every reading of a set of notes, ordered from lowest to highest, ranked from the most to least likely.
+    pub fn candidates(notes: &[Note]) -> Vec<Chord> {
+        let mut ranked: Vec<(u16, Chord)> = Vec::new();
+        let Some(bass) = notes.first().copied() else { return Vec::new(); };
+        let mut seen = HashSet::new();
+        for root in notes.iter().filter(|n| seen.insert(n.pitch_class())) {
+            if let Some(candidate) = Chord::analyze(*root, bass, notes) {
+                ranked.push(candidate);
+            }
+        }
+        ranked.sort_by_key(|(score, chord)| (*score, chord.is_slash(), chord.extensions.len()));
+        ranked.into_iter().map(|(_, chord)| chord).collect()
+    }
+
+    /// The best [Chord] for the played [Tonics](Tonic), using the lowest as the bass.
+    pub fn from_tonics<'a>(tonics: impl IntoIterator<Item = &'a Tonic>) -> Option<Chord> {
+        let mut tonics: Vec<&Tonic> = tonics.into_iter().collect();
+        tonics.sort_by_key(|t| t.index);
+        let notes: Vec<Note> = tonics.iter().filter_map(|t| t.note()).collect();
+        Chord::identify(&notes)
+    }
+
+    // Read the notes as a chord on `root`, scoring how much has to be explained away - lower is better
+    fn analyze(root: Note, bass: Note, notes: &[Note]) -> Option<(u16, Chord)> {
+        let root_class = root.pitch_class();
+        // Each played note spelled as an interval above the root, filed under the semitones it spans
+        let step = |note: &Note| Interval::distance(root, *note).map(|interval| interval.semitones().rem_euclid(12) as usize);
+        let mut offsets: [Option<Interval>; 12] = [None; 12];
+        for note in notes {
+            if let Some(interval) = Interval::distance(root, *note) {
+                offsets[interval.semitones().rem_euclid(12) as usize].get_or_insert(interval);
+            }
+        }
+        offsets[0] = None;
+        if offsets.iter().all(|o| o.is_none()) { return None; }
+
+        // A tritone written as a fifth (Gb over C) flattens the fifth, written as a fourth (F#) it is a #11
+        let flat_fifth = matches!(offsets[6], Some(Interval::Fifth(_)));
+        let mut take = |step: usize| offsets[step].take().is_some();
+        let third = if take(4) { Some(M3) } else if take(3) { Some(MIN3) } else { None };
+        let suspension = match third {
+            Some(_) => None,
+            None => if take(5) { Some(P4) } else if take(2) { Some(M2) } else { None },
+        };
+        let fifth = if take(7) { Some(P5) }
+            else if third == Some(MIN3) && take(6) { Some(D5) }
+            else if third == Some(M3) && take(8) { Some(A5) }
+            else if third == Some(M3) && flat_fifth && take(6) { Some(D5) }
+            else { None };
+        let seventh = if take(10) { Some(MIN7) }
+            else if take(11) { Some(M7) }
+            else if third == Some(MIN3) && fifth == Some(D5) && take(9) { Some(D7) }
+            else { None };
+        let quality = ChordQuality::from_parts(third.or(suspension), fifth, seventh)?;
+
+        let mut score: u16 = 0;
+        let mut extensions = Vec::new();
+        let alterations: [(usize, Interval, u16); 7] = [
+            (1, Interval::Ninth(MajorQuality::Minor), 2),
+            (2, Interval::Ninth(MajorQuality::Major), 1),
+            (3, Interval::Ninth(MajorQuality::Augmented), 2),
+            (5, Interval::Eleventh(PerfectQuality::Perfect), 1),
+            (6, Interval::Eleventh(PerfectQuality::Augmented), 2),
+            (8, Interval::Thirteenth(MajorQuality::Minor), 2),
+            (9, if quality.has_seventh() { Interval::Thirteenth(MajorQuality::Major) } else { M6 }, 1),
+        ];
+        for (step, interval, cost) in alterations {
+            if offsets[step].take().is_some() {
+                extensions.push(interval);
+                score += cost;
+            }
+        }
+
+        let extra: Vec<Note> = notes.iter().copied()
+            .filter(|n| step(n).is_none_or(|step| offsets[step].is_some()))
+            .fold(Vec::new(), |mut extra, n| { if !extra.contains(&n) { extra.push(n); } extra });
+        score += 4 * extra.len() as u16;
+
+        let played: HashSet<PitchClass> = notes.iter().map(|n| n.pitch_class()).collect();
+        let missing: Vec<Interval> = quality.intervals().iter().copied()
+            .filter(|i| !played.contains(&(root_class + *i)))
+            .collect();
+        score += missing.iter().map(|i| if matches!(i, Interval::Fifth(_)) { 1 } else { 3 }).sum::<u16>();
+        if matches!(quality, ChordQuality::Suspended2 | ChordQuality::Suspended4 | ChordQuality::Dominant7Suspended4 | ChordQuality::Dominant7Suspended2 | ChordQuality::Power) { score += 1; }
+
+        let chord = Chord::from_parts(root, bass, quality, extensions, missing, extra);
+        if chord.is_slash() { score += if chord.inversion().is_some() { 1 } else { 2 }; }
+        Some((score, chord))
+    }
+
+    // Extensions are kept in the order they stack above the root
+    fn order(interval: &Interval) -> u8 {
+        match interval {
+            Interval::Sixth(_) => 6,
+            Interval::Ninth(_) => 9,
+            Interval::Eleventh(_) => 11,
+            Interval::Thirteenth(_) => 13,
+            _ => 0,
+        }
+    }
+}
+
+const NINE: Interval = Interval::Ninth(MajorQuality::Major);
+const ELEVEN: Interval = Interval::Eleventh(PerfectQuality::Perfect);
+const THIRTEEN: Interval = Interval::Thirteenth(MajorQuality::Major);
+
+// Alterations in the order they are written, e.g. "7b9#11"
+const ALTERATIONS: [(&str, Interval); 4] = [
+    ("b9", Interval::Ninth(MajorQuality::Minor)),
+    ("#9", Interval::Ninth(MajorQuality::Augmented)),
+    ("#11", Interval::Eleventh(PerfectQuality::Augmented)),
+    ("b13", Interval::Thirteenth(MajorQuality::Minor)),
+];
+
+// Natural extensions that may follow "add"
+const ADDITIONS: [(&str, Interval); 7] = [
+    ("13", THIRTEEN), ("11", ELEVEN), ("9", NINE), ("6", M6), ("2", NINE), ("4", ELEVEN), ("", NINE),
+];
+
+/// Lead-sheet chord symbol, e.g. "Cmaj7", "F#m7b5", "Bb13#11", "G7sus4", "D/F#" or "C6/9".
+/// Played tones that the chord does not explain are not written.
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use ChordQuality::*;
+        let has = |interval: Interval| self.extensions.contains(&interval);
+        let mut naturals: Vec<Interval> = [M6, NINE, ELEVEN, THIRTEEN].into_iter().filter(|i| has(*i)).collect();
+        let mut take = |interval: Interval| naturals.iter().position(|i| *i == interval).map(|p| naturals.remove(p)).is_some();
+
+        // Sevenths are named by their highest natural extension - 11 and 13 both imply the 9
+        let mut number = String::from("7");
+        if self.quality.has_seventh() {
+            if has(NINE) && has(THIRTEEN) { take(NINE); take(THIRTEEN); number = String::from("13"); }
+            else if has(NINE) && has(ELEVEN) { take(NINE); take(ELEVEN); number = String::from("11"); }
+            else if take(NINE) { number = String::from("9"); }
+        } else if matches!(self.quality, Major | Minor) && take(M6) {
+            number = if take(NINE) { String::from("6/9") } else { String::from("6") };
+        } else {
+            number.clear();
+        }
+
+        write!(f, "{}", self.root)?;
+        match self.quality {
+            Major => write!(f, "{}", number)?,
+            Minor => write!(f, "m{}", number)?,
+            Diminished => write!(f, "dim")?,
+            Augmented => write!(f, "aug")?,
+            // "Cb5" would read as a Cb power chord
+            MajorFlat5 => write!(f, "(b5)")?,
+            Suspended2 => write!(f, "sus2")?,
+            Suspended4 => write!(f, "sus4")?,
+            Power => write!(f, "5")?,
+            Dominant7 => write!(f, "{}", number)?,
+            Dominant7Flat5 => write!(f, "{}b5", number)?,
+            Major7 => write!(f, "maj{}", number)?,
+            Major7Flat5 => write!(f, "maj{}b5", number)?,
+            Minor7 => write!(f, "m{}", number)?,
+            MinorMajor7 => write!(f, "mmaj{}", number)?,
+            HalfDiminished7 => write!(f, "m{}b5", number)?,
+            Diminished7 => write!(f, "dim{}", number)?,
+            DiminishedMajor7 => write!(f, "mmaj{}b5", number)?,
+            Augmented7 => write!(f, "{}#5", number)?,
+            AugmentedMajor7 => write!(f, "maj{}#5", number)?,
+            Dominant7Suspended4 => write!(f, "{}sus4", number)?,
+            Dominant7Suspended2 => write!(f, "{}sus2", number)?,
+        }
+        for (symbol, interval) in ALTERATIONS.iter() {
+            if has(*interval) { write!(f, "{}", symbol)?; }
+        }
+        for interval in naturals {
+            let symbol = ADDITIONS.iter().find(|(_, i)| *i == interval).map(|(s, _)| *s).unwrap_or_default();
+            write!(f, "add{}", symbol)?;
+        }
+        for interval in self.missing.iter() {
+            match interval {
+                Interval::Fifth(_) => write!(f, "(no5)")?,
+                _ => write!(f, "(no3)")?,
+            }
+        }
+        if self.is_slash() { write!(f, "/{}", self.bass)?; }
+        Ok(())
+    }
+}
+
+impl FromStr for Chord {
+    type Err = &'static str;
+
+    fn from_str(symbol: &str) -> Result<Chord, Self::Err> {
+        let symbol = symbol.trim().replace('Δ', "maj").replace('°', "dim").replace("ø7", "m7b5").replace('ø', "m7b5");
+        let (root, mut rest) = Note::parse_prefix(&symbol)?;
+
+        // Triad and seventh from the quality prefix and number
+        let (mut third, mut fifth, mut seventh) = (Some(M3), Some(P5), None);
+        let mut major_seventh = false;
+        for (prefix, minor, major) in [("mmaj", true, true), ("mMaj", true, true), ("m(maj", true, true), ("mM", true, true),
+                                       ("maj", false, true), ("Maj", false, true), ("min", true, false), ("m", true, false),
+                                       ("-", true, false), ("M", false, true)] {
+            if let Some(r) = rest.strip_prefix(prefix) {
+                if minor { third = Some(MIN3); }
+                major_seventh = major;
+                rest = r;
+                break;
+            }
+        }
+        if let Some(r) = rest.strip_prefix("dim").or_else(|| rest.strip_prefix('o')) { third = Some(MIN3); fifth = Some(D5); rest = r; }
+        else if let Some(r) = rest.strip_prefix("aug").or_else(|| rest.strip_prefix('+')) { fifth = Some(A5); rest = r; }
+
+        let mut extensions = Vec::new();
+        if let Some(r) = rest.strip_prefix("6/9") { extensions.extend([M6, NINE]); rest = r; }
+        else if let Some(r) = rest.strip_prefix('6') { extensions.push(M6); rest = r; }
+        else if let Some(r) = rest.strip_prefix('5').filter(|_| third == Some(M3) && fifth == Some(P5)) { third = None; rest = r; }
+        else {
+            for (number, implied) in [("13", &[NINE, THIRTEEN][..]), ("11", &[NINE, ELEVEN][..]), ("9", &[NINE][..]), ("7", &[][..])] {
+                if let Some(r) = rest.strip_prefix(number) {
+                    seventh = Some(if major_seventh { M7 } else if fifth == Some(D5) { D7 } else { MIN7 });
+                    extensions.extend_from_slice(implied);
+                    rest = r;
+                    break;
+                }
+            }
+        }
+
+        // Suspensions, alterations, additions and omissions in any order, then the bass
+        let mut bass = root;
+        let mut omit = (false, false);
+        loop {
+            rest = rest.trim_start_matches(['(', ')', ',', ' ']);
+            if rest.is_empty() { break; }
+            if let Some(r) = rest.strip_prefix('/') {
+                let (note, r) = Note::parse_prefix(r)?;
+                if !r.is_empty() { return Err("Unexpected text after the bass note"); }
+                bass = note;
+                break;
+            }
+            rest = if let Some(r) = rest.strip_prefix("sus2") { third = Some(M2); r }
+                else if let Some(r) = rest.strip_prefix("sus4").or_else(|| rest.strip_prefix("sus")) { third = Some(P4); r }
+                else if let Some(r) = rest.strip_prefix("no3") { omit.0 = true; r }
+                else if let Some(r) = rest.strip_prefix("no5") { omit.1 = true; r }
+                else if let Some(r) = rest.strip_prefix("b5") { fifth = Some(D5); r }
+                else if let Some(r) = rest.strip_prefix("#5") { if third != Some(M3) { return Err("Only major chords can sharpen the fifth"); } fifth = Some(A5); r }
+                else if let Some((symbol, interval)) = ALTERATIONS.iter().find(|(symbol, _)| rest.starts_with(symbol)) { extensions.push(*interval); &rest[symbol.len()..] }
+                else if let Some(r) = rest.strip_prefix("add") {
+                    let (symbol, interval) = ADDITIONS.iter().find(|(symbol, _)| r.starts_with(symbol)).unwrap_or(&ADDITIONS[6]);
+                    extensions.push(*interval);
+                    &r[symbol.len()..]
+                }
+                else { return Err("Unrecognized chord symbol"); };
+        }
+
+        let quality = ChordQuality::from_parts(third, fifth, seventh).ok_or("Unsupported chord quality")?;
+        let missing = quality.intervals().iter().copied()
+            .filter(|i| if matches!(i, Interval::Fifth(_)) { omit.1 } else { omit.0 && matches!(i, Interval::Third(_)) })
+            .collect();
+        extensions.dedup();
+        Ok(Chord::from_parts(root, bass, quality, extensions, missing, Vec::new()))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Accidental::*, Note::*};
+
+    fn identify(notes: &[Note]) -> Chord { Chord::identify(notes).unwrap() }
+
+    #[test]
+    fn test_triads() {
+        let chord = identify(&[C(Natural), E(Natural), G(Natural)]);
+        assert_eq!((chord.root(), chord.quality(), chord.inversion()), (C(Natural), ChordQuality::Major, Some(0)));
+        assert_eq!(identify(&[A(Natural), C(Natural), E(Natural)]).quality(), ChordQuality::Minor);
+        assert_eq!(identify(&[B(Natural), D(Natural), F(Natural)]).quality(), ChordQuality::Diminished);
+        assert_eq!(identify(&[C(Natural), E(Natural), G(Sharp)]).quality(), ChordQuality::Augmented);
+        assert_eq!(identify(&[C(Natural), F(Natural), G(Natural)]).quality(), ChordQuality::Suspended4);
+        assert_eq!(identify(&[C(Natural), D(Natural), G(Natural)]).quality(), ChordQuality::Suspended2);
+        assert_eq!(identify(&[E(Natural), B(Natural)]).quality(), ChordQuality::Power);
+    }
+
+    #[test]
+    fn test_sevenths() {
+        let chord = identify(&[G(Natural), B(Natural), D(Natural), F(Natural)]);
+        assert_eq!((chord.root(), chord.quality()), (G(Natural), ChordQuality::Dominant7));
+        assert_eq!(identify(&[C(Natural), E(Natural), G(Natural), B(Natural)]).quality(), ChordQuality::Major7);
+        assert_eq!(identify(&[D(Natural), F(Natural), A(Natural), C(Natural)]).quality(), ChordQuality::Minor7);
+        assert_eq!(identify(&[B(Natural), D(Natural), F(Natural), A(Natural)]).quality(), ChordQuality::HalfDiminished7);
+        assert_eq!(identify(&[C(Natural), E(Flat), G(Natural), B(Natural)]).quality(), ChordQuality::MinorMajor7);
+        assert_eq!(identify(&[G(Natural), C(Natural), D(Natural), F(Natural)]).quality(), ChordQuality::Dominant7Suspended4);
+
+        let chord = identify(&[C(Natural), E(Flat), G(Flat), A(Natural)]);
+        assert_eq!((chord.root(), chord.quality()), (C(Natural), ChordQuality::Diminished7));
+        assert_eq!(chord.notes(), vec![C(Natural), E(Flat), G(Flat), B(DoubleFlat)]);
+    }
+
+    #[test]
+    fn test_extensions_and_alterations() {
+        let chord = identify(&[C(Natural), E(Natural), G(Natural), B(Natural), D(Natural)]);
+        assert_eq!(chord.quality(), ChordQuality::Major7);
+        assert_eq!(chord.extensions(), &[Interval::Ninth(MajorQuality::Major)]);
+
+        let chord = identify(&[C(Natural), E(Natural), G(Natural), B(Flat), D(Flat)]);
+        assert_eq!((chord.root(), chord.quality()), (C(Natural), ChordQuality::Dominant7));
+        assert_eq!(chord.extensions(), &[Interval::Ninth(MajorQuality::Minor)]);
+        assert_eq!(chord.notes(), vec![C(Natural), E(Natural), G(Natural), B(Flat), D(Flat)]);
+
+        let chord = identify(&[C(Natural), E(Natural), B(Flat), D(Sharp)]);
+        assert_eq!(chord.quality(), ChordQuality::Dominant7);
+        assert_eq!(chord.extensions(), &[Interval::Ninth(MajorQuality::Augmented)]);
+        assert_eq!(chord.missing(), &[P5]);
+
+        let chord = identify(&[G(Natural), B(Natural), D(Natural), F(Natural), A(Natural), C(Sharp), E(Natural)]);
+        assert_eq!((chord.root(), chord.quality()), (G(Natural), ChordQuality::Dominant7));
+        assert_eq!(chord.extensions(), &[
+            Interval::Ninth(MajorQuality::Major),
+            Interval::Eleventh(PerfectQuality::Augmented),
+            Interval::Thirteenth(MajorQuality::Major),
+        ]);
+
+        let chord = identify(&[C(Natural), E(Natural), G(Natural), A(Natural)]);
+        assert_eq!((chord.root(), chord.quality(), chord.extensions()), (C(Natural), ChordQuality::Major, &[M6][..]));
+        let chord = identify(&[C(Natural), E(Natural), G(Natural), D(Natural)]);
+        assert_eq!(chord.extensions(), &[Interval::Ninth(MajorQuality::Major)]);
+    }
+
+    #[test]
+    fn test_flat_fifths() {
+        let chord = identify(&[C(Natural), E(Natural), G(Flat), B(Flat)]);
+        assert_eq!((chord.root(), chord.quality()), (C(Natural), ChordQuality::Dominant7Flat5));
+        assert!(chord.missing().is_empty() && chord.extensions().is_empty());
+        assert_eq!(chord.notes(), vec![C(Natural), E(Natural), G(Flat), B(Flat)]);
+        assert_eq!(chord.to_string(), "C7b5");
+
+        // Spelled as a fourth, the tritone over C is still the #11 over a missing fifth
+        let candidates = Chord::candidates(&[C(Natural), E(Natural), F(Sharp), B(Flat)]);
+        let chord = candidates.iter().find(|c| c.root() == C(Natural)).unwrap();
+        assert_eq!(chord.quality(), ChordQuality::Dominant7);
+        assert_eq!(chord.extensions(), &[Interval::Eleventh(PerfectQuality::Augmented)]);
+
+        assert_eq!(identify(&[C(Natural), E(Natural), G(Flat), B(Natural)]).quality(), ChordQuality::Major7Flat5);
+        assert_eq!(identify(&[C(Natural), E(Natural), G(Flat)]).to_string(), "C(b5)");
+        assert_eq!(identify(&[C(Natural), E(Flat), G(Flat), B(Natural)]).quality(), ChordQuality::DiminishedMajor7);
+    }
+
+    #[test]
+    fn test_inversions_and_slash_chords() {
+        let chord = identify(&[E(Natural), G(Natural), C(Natural)]);
+        assert_eq!((chord.root(), chord.bass(), chord.inversion()), (C(Natural), E(Natural), Some(1)));
+        assert_eq!(identify(&[G(Natural), C(Natural), E(Natural)]).inversion(), Some(2));
+        assert_eq!(identify(&[F(Natural), G(Natural), B(Natural), D(Natural)]).inversion(), Some(3));
+        assert_eq!(identify(&[A(Natural), C(Natural), E(Natural), G(Natural)]).root(), A(Natural));
+
+        let chord = Chord::new(C(Natural), ChordQuality::Major).over(D(Natural));
+        assert!(chord.is_slash());
+        assert_eq!(chord.inversion(), None);
+    }
+
+    #[test]
+    fn test_from_tonics() {
+        let tonics = [Tonic::new(64, 100, 0), Tonic::new(55, 90, 0), Tonic::new(60, 80, 0)];
+        let chord = Chord::from_tonics(tonics.iter()).unwrap();
+        assert_eq!((chord.root(), chord.bass(), chord.inversion()), (C(Natural), G(Natural), Some(2)));
+
+        let mut subsequence = super::super::Subsequence::new();
+        for tonic in tonics.iter() { subsequence.play_note(tonic.index, tonic.velocity); }
+        assert_eq!(subsequence.chords.into_iter().collect::<Vec<_>>(), vec![chord]);
+    }
+
+    #[test]
+    fn test_extra_tones() {
+        let chord = identify(&[C(Natural), E(Natural), G(Natural), B(Natural), B(Flat)]);
+        assert_eq!(chord.quality(), ChordQuality::Dominant7);
+        assert_eq!(chord.extra(), &[B(Natural)]);
+        assert!(Chord::identify(&[C(Natural)]).is_none());
+        assert!(Chord::identify(&[]).is_none());
+    }
+
+    #[test]
+    fn test_symbols_round_trip() {
+        let symbols = ["Cmaj7", "F#m7b5", "Bb13#11", "G7sus4", "D/F#", "C6/9", "Am", "Ebdim7", "Bdim", "Caug", "C7#5#9",
+                       "Dm9", "Fmaj13", "Gm11", "Cmmaj7", "C5", "Csus2", "Dsus4", "Cadd9", "Cm6", "E7b9b13", "C7#9(no5)",
+                       "C13add11", "G7add13", "Abmaj7#5", "C9sus4", "Cm7/Bb", "C/E"];
+        for symbol in symbols {
+            let chord: Chord = symbol.parse().unwrap_or_else(|e| panic!("{}: {}", symbol, e));
+            assert_eq!(chord.to_string(), symbol);
+        }
+    }
+
+    #[test]
+    fn test_symbols_expand() {
+        let chord: Chord = "F#m7b5".parse().unwrap();
+        assert_eq!(chord.quality(), ChordQuality::HalfDiminished7);
+        assert_eq!(chord.notes(), vec![F(Sharp), A(Natural), C(Natural), E(Natural)]);
+
+        let chord: Chord = "Bb13#11".parse().unwrap();
+        assert_eq!(chord.notes(), vec![B(Flat), D(Natural), F(Natural), A(Flat), C(Natural), E(Natural), G(Natural)]);
+
+        let chord: Chord = "C6/9".parse().unwrap();
+        assert_eq!(chord.notes(), vec![C(Natural), E(Natural), G(Natural), A(Natural), D(Natural)]);
+
+        let chord: Chord = "D/F#".parse().unwrap();
+        assert_eq!((chord.root(), chord.bass(), chord.inversion()), (D(Natural), F(Sharp), Some(1)));
+    }
+
+    #[test]
+    fn test_flat_five_symbols() {
+        let chord: Chord = "C7b5".parse().unwrap();
+        assert_eq!(chord.quality(), ChordQuality::Dominant7Flat5);
+        assert_eq!(chord.notes(), vec![C(Natural), E(Natural), G(Flat), B(Flat)]);
+        assert_eq!("Cmaj7b5".parse::<Chord>().unwrap().quality(), ChordQuality::Major7Flat5);
+        assert_eq!("C(b5)".parse::<Chord>().unwrap().quality(), ChordQuality::MajorFlat5);
+        assert_eq!("Cmb5".parse::<Chord>().unwrap().quality(), ChordQuality::Diminished);
+        for symbol in ["C7b5", "C9b5", "Cmaj7b5", "C(b5)", "Cmmaj7b5", "G7b5b9", "Db7b5/G"] {
+            assert_eq!(symbol.parse::<Chord>().unwrap().to_string(), symbol);
+        }
+        assert!("Csus4b5".parse::<Chord>().is_err());
+    }
+
+    #[test]
+    fn test_symbol_aliases() {
+        let parse = |symbol: &str| symbol.parse::<Chord>().unwrap().to_string();
+        assert_eq!(parse("C-7"), "Cm7");
+        assert_eq!(parse("CΔ7"), "Cmaj7");
+        assert_eq!(parse("CM7"), "Cmaj7");
+        assert_eq!(parse("Cmin"), "Cm");
+        assert_eq!(parse("Co7"), "Cdim7");
+        assert_eq!(parse("Cø7"), "Cm7b5");
+        assert_eq!(parse("C+"), "Caug");
+        assert_eq!(parse("Csus"), "Csus4");
+        assert_eq!(parse("C7(b9, #11)"), "C7b9#11");
+        assert_eq!(parse("Cm(maj7)"), "Cmmaj7");
+        assert_eq!(parse("B♭7"), "Bb7");
+        assert!("H7".parse::<Chord>().is_err());
+        assert!("Cfoo".parse::<Chord>().is_err());
+    }
+
+    #[test]
+    fn test_seventh_sus2() {
+        let chord: Chord = "C7sus2".parse().unwrap();
+        assert_eq!(chord.quality(), ChordQuality::Dominant7Suspended2);
+        assert_eq!(chord.notes(), [C(Natural), D(Natural), G(Natural), B(Flat)]);
+        assert_eq!(chord.to_string(), "C7sus2");
+        assert_eq!("C9sus2".parse::<Chord>().map(|c| c.to_string()), Ok("C9sus2".to_string()));
+        assert_eq!(identify(&[C(Natural), D(Natural), G(Natural), B(Flat)]).quality(), ChordQuality::Dominant7Suspended2);
+    }
+
+    #[test]
+    fn test_recognized_symbols() {
+        let name = |notes: &[Note]| identify(notes).to_string();
+        assert_eq!(name(&[E(Natural), G(Natural), C(Natural)]), "C/E");
+        assert_eq!(name(&[C(Natural), E(Natural), B(Flat), D(Sharp)]), "C7#9(no5)");
+        assert_eq!(name(&[C(Natural), E(Natural), G(Natural), A(Natural), D(Natural)]), "C6/9");
+    }
+}
@@ -1,6 +1,6 @@
 
 use std::collections::{self, HashSet};
-use crate::types::{PitchGroup, PitchClass, Note, Form, Matrix};
+use crate::types::{Accidental, PitchGroup, PitchClassSet, Note, Form, Matrix};
 use super::{PitchGroupKernel, Tonic};
 use std::fmt::{self, Display, Formatter};
 
@@ -10,7 +10,7 @@ use std::fmt::{self, Display, Formatter};
 #[derive(Clone, Debug)]
 pub struct Key {
     pub pitchgroup: PitchGroup,         // This is the pitchgroup that this slice belongs to
-    pub notes: Vec<Note>,         // This is the collection of notes found from the matrix
+    pub notes: [Note; 7],         // This is the collection of notes found from the matrix, from the tonic upwards
     accidental: Form,                   // Fast Sharp, Flat, or Natural note (Cn would be the only Natural Slice)
     pub probability: u8,                // This is the probability of the pitchgroup slice being played -> we could systematically map these with a sequence e.g. Matrix type
         /*
@@ -22,13 +22,21 @@ pub struct Key {
 // Used to determine all of the pitchgroups associated with the played notes
 impl Key {
     pub fn new(pitchgroup: &PitchGroup, voicings: HashSet<Tonic>) -> Key {
+        Key::with_voicings(pitchgroup, voicings.len())
+    }
+
+    // Same as new, for when we only know how many tones are voiced - nothing to collect or hand over
+    pub fn with_voicings(pitchgroup: &PitchGroup, voicings: usize) -> Key {
         // TODO: Rayon Parallelization 
 
-        // We get all the pitch classes belonging to this pitchgroup
-        let pitch_classes: HashSet<PitchClass> = pitchgroup.pitch_classes().iter().map(|pc| pc.clone()).collect::<HashSet<PitchClass>>();
+        // We get all the pitch classes belonging to this pitchgroup, the major tonic first so root() finds it
+        let pitch_classes = PitchClassSet::from(*pitchgroup);
 
         // We let the Matrix tell us whether we should be sharp or flat
-        let notes: Vec<Note> = pitch_classes.iter().map(|pc| Matrix::natural(pc, pitchgroup).unwrap()).collect::<Vec<Note>>(); 
+        let mut notes = [Note::C(Accidental::Natural); 7];
+        for (note, pc) in notes.iter_mut().zip(pitchgroup.ionian().notes()) {
+            *note = Matrix::natural(&pc, pitchgroup).unwrap();
+        }
         
         // We need to determine if this is a sharp, flat, or natural note
         let is_sharp = notes.iter().any(|n| n.sharp());
@@ -40,10 +48,14 @@ impl Key {
             pitchgroup: pitchgroup.clone(), 
             notes,
             accidental,
-            probability: ((voicings.len()  as f64 / pitch_classes.len() as f64) * 100.0) as u8
+            probability: Key::probability(voicings, pitch_classes.len())
         }
     }
 
+    pub(crate) fn probability(voicings: usize, pitch_classes: usize) -> u8 {
+        ((voicings as f64 / pitch_classes as f64) * 100.0) as u8
+    }
+
     pub fn len(&self) -> usize { self.notes.len() }
     pub fn root(&self) -> Note { self.notes[0].clone() }
     pub fn tonic(&self, note: Note) -> bool { self.root() == note }
@@ -62,4 +74,23 @@ impl Display for Key {
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use crate::types::{Accidental::*, Note::*, PitchGroup};
+    use super::Key;
+
+    #[test]
+    fn test_root_is_tonic() {
+        for pg in PitchGroup::all() {
+            let key = Key::new(&pg, HashSet::new());
+            assert_eq!(key.root(), pg.major_key(), "{:?}", pg);
+            assert!(key.tonic(pg.major_key()));
+        }
+        let a = Key::with_voicings(&PitchGroup::An, 3);
+        assert_eq!(a.notes, [A(Natural), B(Natural), C(Sharp), D(Natural), E(Natural), F(Sharp), G(Sharp)]);
+        assert_eq!(a.probability, 42);
+    }
+}
@@ -1,7 +1,8 @@
 // Written and Created by Richard I. Christopher, Big Stick Studio, 2024
 
 use std::collections::{self, HashSet};
-use crate::types::{Tone, PitchClass, Note, PitchGroup};
+use std::sync::OnceLock;
+use crate::types::{Tone, PitchClassSet, Note, PitchGroup};
 use super::{Key, Tonic};
 use std::fmt::{self, Display, Formatter};
 
@@ -10,29 +11,92 @@ pub struct PitchGroupKernel {
     index: usize,
     keys: Vec<Key>,
     dissidents: Vec<Key>,     // These are the negative pitchgroups that are not being played - we may not need this
+    pitch_classes: PitchClassSet,   // The pitch classes being played
+    counts: [u16; 12],              // How many of the tones sound each pitch class, so we know when one drops out
+    voicings: usize,                // How many tones we have been given
 }
 
-// It's expensive, but we just create a new pitchgroup every time, for now
+// Which pitchgroups share at least one pitch class with each of the 4096 pitch class sets, one bit per pitchgroup index
+fn harmonious_groups() -> &'static [u16; 4096] {
+    static HARMONIOUS: OnceLock<[u16; 4096]> = OnceLock::new();
+    HARMONIOUS.get_or_init(|| {
+        let mut table = [0; 4096];
+        for (mask, groups) in table.iter_mut().enumerate() {
+            let (harmonious, _) = PitchGroup::split_set(PitchClassSet::from_mask(mask as u16));
+            *groups = harmonious.iter().fold(0, |groups, pg| groups | 1 << pg.to_index());
+        }
+        table
+    })
+}
+
+// The key of every pitchgroup, built once - only the probability changes with the tones
+fn group_keys() -> &'static [Key; 12] {
+    static KEYS: OnceLock<[Key; 12]> = OnceLock::new();
+    KEYS.get_or_init(|| PitchGroup::all().map(|pg| Key::with_voicings(&pg, 0)))
+}
+
+// Rebuilding is still there, but add_tone and remove_tone keep the kernel up to date without it
 impl PitchGroupKernel {
     pub fn new(tones: HashSet<Tonic>) -> PitchGroupKernel { // Where do tones come from?
-        if tones.is_empty() { return PitchGroupKernel { index: 0, keys: Vec::new(), dissidents: Vec::new() }; }
+        let mut kernel = PitchGroupKernel { 
+                index: 0, 
+                keys: Vec::with_capacity(12), 
+                dissidents: Vec::with_capacity(12), 
+                pitch_classes: PitchClassSet::new(), 
+                counts: [0; 12], 
+                voicings: 0 
+            };
 
         // We can eventually make this our consumer with a lock/mutex
         // We start by getting the tones being played in pitchclass form
-        let pitch_classes: Vec<PitchClass> = tones.iter().map(|t| t.pitch_class().unwrap()).collect::<Vec<PitchClass>>(); 
+        for tone in tones.iter() { kernel.voice(tone); }
+
         // and then we get the pitchgroups that contain the notes, and the ones that don't
-        let (harmonious, dissidence) = PitchGroup::split_classes(pitch_classes.clone());
-
-        // Then we want to build a 'Kernel' of Keys that will be used to determine the favorability of the notes not being played
-        PitchGroupKernel { 
-                index: 0,
-                keys: harmonious.iter()
-                                .map(|pg| Key::new(pg, tones.clone())) 
-                                .collect::<Vec<Key>>(),
-                dissidents: dissidence.iter()
-                                .map(|pg| Key::new(pg, tones.clone())) 
-                                .collect::<Vec<Key>>()
-            }
+        kernel.refresh();
+        kernel
+    }
+
+    // A tone started sounding - the caller makes sure it was not already counted
+    pub fn add_tone(&mut self, tone: &Tonic) {
+        self.voice(tone);
+        self.refresh();
+    }
+
+    // A tone stopped sounding - the caller makes sure it was counted
+    pub fn remove_tone(&mut self, tone: &Tonic) {
+        self.voicings = self.voicings.saturating_sub(1);
+        if let Some(pc) = tone.pitch_class() {
+            let count = &mut self.counts[pc.to_index() as usize];
+            *count = count.saturating_sub(1);
+            if *count == 0 { self.pitch_classes.remove(&pc); }
+        }
+        self.refresh();
+    }
+
+    pub fn pitch_classes(&self) -> PitchClassSet { self.pitch_classes }
+
+    fn voice(&mut self, tone: &Tonic) {
+        self.voicings += 1;
+        if let Some(pc) = tone.pitch_class() {
+            self.counts[pc.to_index() as usize] += 1;
+            self.pitch_classes.insert(pc);
+        }
+    }
+
+    // Then we want to build a 'Kernel' of Keys that will be used to determine the favorability of the notes not being played
+    // This is a table lookup and twelve key copies no matter how many tones are sounding
+    fn refresh(&mut self) {
+        self.index = 0;
+        self.keys.clear();
+        self.dissidents.clear();
+        if self.pitch_classes.is_empty() { return; }
+
+        let harmonious = harmonious_groups()[self.pitch_classes.mask() as usize];
+        for (i, key) in group_keys().iter().enumerate() {
+            let mut key = key.clone();
+            key.probability = Key::probability(self.voicings, key.len());
+            if harmonious & 1 << i != 0 { self.keys.push(key); } else { self.dissidents.push(key); }
+        }
     }
 
     // This gives the highest probability keys
@@ -102,4 +166,88 @@ impl Display for PitchGroupKernel {
         write!(f, "{:?}", self.keys)
     }
 }
-//impl FromIterator
\ No newline at end of file
+//impl FromIterator
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use crate::types::{PitchClass, PitchClassSet, PitchGroup};
+    use super::{Key, PitchGroupKernel, Tonic};
+
+    // Sorted, since the original split came out in HashSet order
+    fn summary(keys: &[Key]) -> Vec<(PitchGroup, u8)> {
+        let mut summary: Vec<(PitchGroup, u8)> = keys.iter().map(|k| (k.pitchgroup, k.probability)).collect();
+        summary.sort();
+        summary
+    }
+
+    // PitchGroup::split_classes as it was before PitchClassSet, kept here to check the kernel against
+    fn original_split(pitch_classes: Vec<PitchClass>) -> (Vec<PitchGroup>, Vec<PitchGroup>) {
+        if pitch_classes.is_empty() { return (Vec::new(), Vec::new()); }
+
+        let all: HashSet<PitchGroup> = PitchGroup::all().iter().copied().collect();
+        let pitch_class_groups: HashSet<PitchGroup> = pitch_classes.iter().map(|pc| pc.groups()).flatten().collect();
+
+        let harmonic_groups: Vec<PitchGroup> = all.intersection(&pitch_class_groups).copied().collect();
+        let dissonant_groups: Vec<PitchGroup> = all.difference(&pitch_class_groups).copied().collect();
+
+        (harmonic_groups, dissonant_groups)
+    }
+
+    // What the kernel was before it could be updated - split the groups and work out every probability from scratch
+    fn rebuilt(tones: &HashSet<Tonic>) -> (Vec<(PitchGroup, u8)>, Vec<(PitchGroup, u8)>) {
+        let pitch_classes: Vec<PitchClass> = tones.iter().map(|t| t.pitch_class().unwrap()).collect();
+        let (harmonious, dissidence) = original_split(pitch_classes);
+        let probability = |pg: &PitchGroup| {
+            let (voicings, pitch_classes) = (tones.len(), pg.pitch_classes().len());
+            (*pg, ((voicings as f64 / pitch_classes as f64) * 100.0) as u8)
+        };
+        let mut keys: Vec<(PitchGroup, u8)> = harmonious.iter().map(probability).collect();
+        let mut dissidents: Vec<(PitchGroup, u8)> = dissidence.iter().map(probability).collect();
+        keys.sort();
+        dissidents.sort();
+        (keys, dissidents)
+    }
+
+    fn state(kernel: &PitchGroupKernel) -> (Vec<(PitchGroup, u8)>, Vec<(PitchGroup, u8)>) { (summary(&kernel.keys), summary(&kernel.dissidents)) }
+
+    #[test]
+    fn test_incremental_matches_rebuild() {
+        for mask in 0..4096u16 {
+            // One tone per pitch class, spread over two octaves, plus a doubled octave on the lowest
+            let mut tones: Vec<Tonic> = PitchClassSet::from_mask(mask).iter()
+                .enumerate()
+                .map(|(i, pc)| Tonic::new(48 + pc.to_index() + 12 * (i % 2) as u8, 100, 0))
+                .collect();
+            if let Some(low) = tones.first().map(|t| t.index) { tones.push(Tonic::new(low + 24, 90, 0)); }
+
+            let mut kernel = PitchGroupKernel::new(HashSet::new());
+            let mut played = HashSet::new();
+            for tone in tones.iter() {
+                played.insert(tone.clone());
+                kernel.add_tone(tone);
+                assert_eq!(state(&kernel), rebuilt(&played));
+            }
+            assert_eq!(state(&kernel), state(&PitchGroupKernel::new(played.clone())));
+            assert_eq!(kernel.pitch_classes(), PitchClassSet::from_mask(mask));
+
+            for tone in tones.iter().rev() {
+                played.remove(tone);
+                kernel.remove_tone(tone);
+                assert_eq!(state(&kernel), rebuilt(&played));
+            }
+            assert!(kernel.keys.is_empty() && kernel.dissidents.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_release_keeps_doubled_pitch_class() {
+        let c4 = Tonic::new(60, 100, 0);
+        let c5 = Tonic::new(72, 100, 0);
+        let fs4 = Tonic::new(66, 100, 0);
+        let mut kernel = PitchGroupKernel::new([c4.clone(), c5.clone(), fs4.clone()].into_iter().collect());
+        kernel.remove_tone(&c5);
+        assert_eq!(kernel.pitch_classes(), PitchClassSet::from(&[PitchClass::Cn, PitchClass::Fs][..]));
+        assert_eq!(state(&kernel), rebuilt(&[c4, fs4].into_iter().collect()));
+    }
+}
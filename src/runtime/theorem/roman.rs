@@ -0,0 +1,327 @@
+//
+// Copyright 2024 Richard I. Christopher, NeoTec Digital. All Rights Reserved.
+//
+
+// Roman numeral and functional analysis of a recognized Chord against the PitchMode it is heard in - the
+// "Tonic Analysis and Chord / Inversions Chart" from the README
+use std::fmt;
+use super::{Chord, ChordQuality};
+use crate::types::{Accidental, Degree, MajorQuality, Matrix, PerfectQuality, PitchClass, PitchClassSet, PitchGroup, PitchMode, Steps};
+
+const NUMERALS: [&str; 7] = ["I", "II", "III", "IV", "V", "VI", "VII"];
+
+/// What a chord does in its key.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum HarmonicFunction {
+    Tonic,
+    Subdominant,
+    Dominant,
+}
+
+impl fmt::Display for HarmonicFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HarmonicFunction::Tonic => write!(f, "Tonic"),
+            HarmonicFunction::Subdominant => write!(f, "Subdominant"),
+            HarmonicFunction::Dominant => write!(f, "Dominant"),
+        }
+    }
+}
+
+/// A [Chord] named by the scale step of its root in a [PitchMode], e.g. I, ii, V7/V, bVII or viio7.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RomanNumeral {
+    step: u8,
+    accidental: Accidental,
+    quality: ChordQuality,
+    inversion: Option<u8>,
+    secondary: Option<Box<RomanNumeral>>,
+    function: HarmonicFunction,
+}
+
+impl RomanNumeral {
+    /// Analyze a [Chord] in a [PitchMode], e.g. `pg.ionian()` or `pg.aeolian()`. Chords outside the mode are read as
+    /// secondary dominants (and leading tone chords) of a diatonic step when they can be, and as borrowed chords otherwise.
+    pub fn analyze(chord: &Chord, mode: &PitchMode) -> RomanNumeral {
+        let tonic = mode.tonic();
+        let root = chord.root().pitch_class();
+        let inversion = chord.inversion();
+        let diatonic = chord.intervals().iter()
+            .take(chord.quality().intervals().len() + 1)
+            .all(|(note, _)| mode.notes().contains(&note.pitch_class()));
+
+        if !diatonic {
+            if let Some(secondary) = RomanNumeral::tonicization(chord, mode) {
+                return secondary;
+            }
+        }
+
+        let (step, accidental) = RomanNumeral::step(root, chord.root().accidental(), mode);
+        let distance = Steps::distance(&tonic, &root).value();
+        let function = match step {
+            // Borrowed from the parallel minor, bVI leads away from the tonic
+            5 if accidental == Accidental::Flat => HarmonicFunction::Subdominant,
+            0 | 2 | 5 => HarmonicFunction::Tonic,
+            1 | 3 => HarmonicFunction::Subdominant,
+            4 => HarmonicFunction::Dominant,
+            _ if distance == 11 => HarmonicFunction::Dominant,
+            _ => HarmonicFunction::Subdominant,
+        };
+        RomanNumeral { step, accidental, quality: chord.quality(), inversion, secondary: None, function }
+    }
+
+    // The dominant or leading tone chord of a major or minor triad on any step but the tonic
+    fn tonicization(chord: &Chord, mode: &PitchMode) -> Option<RomanNumeral> {
+        let root = chord.root().pitch_class();
+        let offset = match chord.quality() {
+            ChordQuality::Major | ChordQuality::Dominant7 | ChordQuality::Dominant7Flat5 => 7,
+            ChordQuality::Diminished | ChordQuality::Diminished7 | ChordQuality::HalfDiminished7 => 11,
+            _ => return None,
+        };
+        (1..7u8).find_map(|step| {
+            let target = mode.notes()[step as usize];
+            let triad = RomanNumeral::triad(step, mode);
+            if Steps::distance(&target, &root).value() != offset || !matches!(triad, ChordQuality::Major | ChordQuality::Minor) {
+                return None;
+            }
+            let target = RomanNumeral {
+                step,
+                accidental: Accidental::Natural,
+                quality: triad,
+                inversion: Some(0),
+                secondary: None,
+                function: HarmonicFunction::Tonic,
+            };
+            Some(RomanNumeral {
+                step: if offset == 7 { 4 } else { 6 },
+                accidental: Accidental::Natural,
+                quality: chord.quality(),
+                inversion: chord.inversion(),
+                secondary: Some(Box::new(target)),
+                function: HarmonicFunction::Dominant,
+            })
+        })
+    }
+
+    // Scale step of a root, raising or lowering the nearest step for roots outside the mode
+    // Steps are the Matrix degrees in a pitchgroup holding the tonic and as much of the mode as it can
+    fn step(root: PitchClass, spelling: Accidental, mode: &PitchMode) -> (u8, Accidental) {
+        let notes = mode.notes();
+        let members = PitchClassSet::from(&notes[..]);
+        let group = PitchGroup::all().into_iter()
+            .filter(|pg| PitchClassSet::from(*pg).contains(&mode.tonic()))
+            .max_by_key(|pg| (PitchClassSet::from(*pg) & members).len())
+            .unwrap_or(PitchGroup::Cn);
+        let tonic = Matrix::degree(&mode.tonic(), &group).map_or(0, |d| d.to_index());
+        let position = |pc: PitchClass| Matrix::degree(&pc, &group).map(|d| (d.to_index() + 7 - tonic) % 7);
+
+        // The root read as its own degree, or as the degree below or above it raised or lowered
+        let steps = [position(root), position(root - Steps::from(1)), position(root + Steps::from(1))];
+        let mut steps = steps.into_iter().flatten();
+        if let Some(step) = steps.clone().find(|step| notes[*step as usize] == root) {
+            return (step, Accidental::Natural);
+        }
+        let distance = Steps::distance(&mode.tonic(), &root).value();
+        let below = steps.clone().find(|step| notes[*step as usize] + Steps::from(1) == root);
+        let above = steps.find(|step| notes[*step as usize] - Steps::from(1) == root);
+        match (below, above) {
+            // A raised leading tone is still the seventh step
+            (Some(6), _) if distance == 11 => (6, Accidental::Natural),
+            (Some(step), _) if spelling.sharp() || (!spelling.flat() && distance == 6) || above.is_none() => (step, Accidental::Sharp),
+            (_, Some(step)) => (step, Accidental::Flat),
+            _ => (0, Accidental::Natural),
+        }
+    }
+
+    // Quality of the triad built on a step of the mode
+    fn triad(step: u8, mode: &PitchMode) -> ChordQuality {
+        let notes = mode.notes();
+        let root = notes[step as usize];
+        let third = Steps::distance(&root, &notes[(step as usize + 2) % 7]).value();
+        let fifth = Steps::distance(&root, &notes[(step as usize + 4) % 7]).value();
+        match (third, fifth) {
+            (4, 8) => ChordQuality::Augmented,
+            (4, _) => ChordQuality::Major,
+            (3, 6) => ChordQuality::Diminished,
+            _ => ChordQuality::Minor,
+        }
+    }
+
+    /// Scale step of the root, 1 to 7.
+    pub fn step_number(&self) -> u8 { self.step + 1 }
+
+    /// Sharp or flat when the root is outside the mode, as in bVII.
+    pub fn accidental(&self) -> Accidental { self.accidental }
+
+    pub fn quality(&self) -> ChordQuality { self.quality }
+
+    pub fn inversion(&self) -> Option<u8> { self.inversion }
+
+    /// The step this chord tonicizes, as the IV in V7/IV.
+    pub fn secondary(&self) -> Option<&RomanNumeral> { self.secondary.as_deref() }
+
+    pub fn function(&self) -> HarmonicFunction { self.function }
+
+    /// [Degree] of the root above the tonic, with the quality of that interval.
+    pub fn degree(&self) -> Degree {
+        let shift = match self.accidental { Accidental::Flat => -1, Accidental::Sharp => 1, _ => 0 };
+        let perfect = match shift { -1 => PerfectQuality::Diminished, 1 => PerfectQuality::Augmented, _ => PerfectQuality::Perfect };
+        let major = match shift { -1 => MajorQuality::Minor, 1 => MajorQuality::Augmented, _ => MajorQuality::Major };
+        match self.step {
+            0 => Degree::Tonic(perfect),
+            1 => Degree::Supertonic(major),
+            2 => Degree::Mediant(major),
+            3 => Degree::Subdominant(perfect),
+            4 => Degree::Dominant(perfect),
+            5 => Degree::Submediant(major),
+            _ => Degree::Subtonic(major),
+        }
+    }
+}
+
+impl Chord {
+    /// [RomanNumeral] of this chord in a [PitchMode].
+    pub fn roman(&self, mode: &PitchMode) -> RomanNumeral { RomanNumeral::analyze(self, mode) }
+}
+
+impl fmt::Display for RomanNumeral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use ChordQuality::*;
+        let numeral = NUMERALS[self.step as usize];
+        let minor = matches!(self.quality, Minor | Diminished | Minor7 | MinorMajor7 | HalfDiminished7 | Diminished7 | DiminishedMajor7);
+        let suffix = match self.quality {
+            Diminished => "o",
+            Augmented => "+",
+            MajorFlat5 => "b5",
+            Suspended2 => "sus2",
+            Suspended4 => "sus4",
+            Power => "5",
+            Dominant7 | Minor7 => "7",
+            Dominant7Flat5 => "7b5",
+            Major7 | MinorMajor7 => "maj7",
+            Major7Flat5 => "maj7b5",
+            HalfDiminished7 => "ø7",
+            Diminished7 => "o7",
+            DiminishedMajor7 => "omaj7",
+            Augmented7 => "+7",
+            AugmentedMajor7 => "+maj7",
+            Dominant7Suspended4 => "7sus4",
+            Dominant7Suspended2 => "7sus2",
+            Major | Minor => "",
+        };
+        // Inversions are written as figured bass
+        let seventh = self.quality.has_seventh();
+        let suffix = match (self.inversion, seventh) {
+            (Some(1), false) => format!("{}6", suffix),
+            (Some(2), false) => format!("{}64", suffix),
+            (Some(1), true) => suffix.replace('7', "65"),
+            (Some(2), true) => suffix.replace('7', "43"),
+            (Some(3), true) => suffix.replace('7', "42"),
+            _ => suffix.to_string(),
+        };
+        if self.accidental != Accidental::Natural { write!(f, "{}", self.accidental)?; }
+        if minor { write!(f, "{}", numeral.to_lowercase())?; } else { write!(f, "{}", numeral)?; }
+        write!(f, "{}", suffix)?;
+        if let Some(target) = &self.secondary { write!(f, "/{}", target)?; }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::PitchGroup;
+
+    fn roman(symbol: &str, mode: &PitchMode) -> (String, HarmonicFunction) {
+        let numeral = symbol.parse::<Chord>().unwrap().roman(mode);
+        (numeral.to_string(), numeral.function())
+    }
+
+    #[test]
+    fn test_diatonic() {
+        let major = PitchGroup::Cn.ionian();
+        assert_eq!(roman("C", &major), ("I".to_string(), HarmonicFunction::Tonic));
+        assert_eq!(roman("Dm", &major), ("ii".to_string(), HarmonicFunction::Subdominant));
+        assert_eq!(roman("Em", &major), ("iii".to_string(), HarmonicFunction::Tonic));
+        assert_eq!(roman("F", &major), ("IV".to_string(), HarmonicFunction::Subdominant));
+        assert_eq!(roman("G7", &major), ("V7".to_string(), HarmonicFunction::Dominant));
+        assert_eq!(roman("Am7", &major), ("vi7".to_string(), HarmonicFunction::Tonic));
+        assert_eq!(roman("Bdim", &major), ("viio".to_string(), HarmonicFunction::Dominant));
+        assert_eq!(roman("Bm7b5", &major), ("viiø7".to_string(), HarmonicFunction::Dominant));
+        assert_eq!(roman("Cmaj7", &major).0, "Imaj7");
+    }
+
+    #[test]
+    fn test_inversions() {
+        let major = PitchGroup::Cn.ionian();
+        assert_eq!(roman("C/E", &major).0, "I6");
+        assert_eq!(roman("C/G", &major).0, "I64");
+        assert_eq!(roman("G7/B", &major).0, "V65");
+        assert_eq!(roman("G7/D", &major).0, "V43");
+        assert_eq!(roman("G7/F", &major).0, "V42");
+    }
+
+    #[test]
+    fn test_secondary_dominants() {
+        let major = PitchGroup::Cn.ionian();
+        assert_eq!(roman("D7", &major), ("V7/V".to_string(), HarmonicFunction::Dominant));
+        assert_eq!(roman("D", &major).0, "V/V");
+        assert_eq!(roman("C7", &major).0, "V7/IV");
+        assert_eq!(roman("E7", &major).0, "V7/vi");
+        assert_eq!(roman("A7/C#", &major).0, "V65/ii");
+        assert_eq!(roman("F#dim7", &major).0, "viio7/V");
+        assert_eq!(roman("C#m7b5", &major).0, "viiø7/ii");
+
+        let g = PitchGroup::Gn.ionian();
+        assert_eq!(roman("A7", &g).0, "V7/V");
+    }
+
+    #[test]
+    fn test_borrowed() {
+        let major = PitchGroup::Cn.ionian();
+        assert_eq!(roman("Bb", &major), ("bVII".to_string(), HarmonicFunction::Subdominant));
+        assert_eq!(roman("Ab", &major), ("bVI".to_string(), HarmonicFunction::Subdominant));
+        assert_eq!(roman("Eb", &major).0, "bIII");
+        assert_eq!(roman("Db", &major), ("bII".to_string(), HarmonicFunction::Subdominant));
+        assert_eq!(roman("Fm", &major).0, "iv");
+        assert_eq!(roman("F#", &major).0, "#IV");
+
+        let f = PitchGroup::Fn.ionian();
+        assert_eq!(roman("B", &f).0, "#IV");
+        assert_eq!(roman("Bdim", &f).0, "viio/V");
+    }
+
+    #[test]
+    fn test_minor() {
+        let minor = PitchGroup::Cn.aeolian();
+        assert_eq!(roman("Am", &minor), ("i".to_string(), HarmonicFunction::Tonic));
+        assert_eq!(roman("Dm", &minor).0, "iv");
+        assert_eq!(roman("E7", &minor), ("V7".to_string(), HarmonicFunction::Dominant));
+        assert_eq!(roman("G#dim7", &minor), ("viio7".to_string(), HarmonicFunction::Dominant));
+        assert_eq!(roman("F", &minor), ("VI".to_string(), HarmonicFunction::Tonic));
+        assert_eq!(roman("G", &minor), ("VII".to_string(), HarmonicFunction::Subdominant));
+        assert_eq!(roman("C", &minor).0, "III");
+
+        // The leading tone is part of harmonic minor itself
+        let harmonic = PitchGroup::Cn.harmonic_minor();
+        assert_eq!(roman("E7", &harmonic), ("V7".to_string(), HarmonicFunction::Dominant));
+        assert_eq!(roman("G#dim7", &harmonic), ("viio7".to_string(), HarmonicFunction::Dominant));
+        assert_eq!(roman("Dm", &harmonic).0, "iv");
+
+        // Modes outside the diatonic family still count their steps from their own tonic
+        let phrygian_dominant = PitchGroup::Cn.phrygian_dominant();
+        assert_eq!(roman("F", &phrygian_dominant).0, "II");
+        assert_eq!(roman("G#dim", &phrygian_dominant).0, "iiio");
+        assert_eq!(roman("G", &phrygian_dominant).0, "bIII");
+    }
+
+    #[test]
+    fn test_degree() {
+        let major = PitchGroup::Cn.ionian();
+        let chord: Chord = "Bb".parse().unwrap();
+        assert_eq!(chord.roman(&major).degree(), Degree::Subtonic(MajorQuality::Minor));
+        let chord: Chord = "G".parse().unwrap();
+        assert_eq!(chord.roman(&major).degree(), Degree::Dominant(PerfectQuality::Perfect));
+        assert_eq!(chord.roman(&major).step_number(), 5);
+    }
+}
@@ -4,7 +4,7 @@
 
 use cgmath::num_traits::clamp;
 
-use crate::types::{sequences, Interval, Note, Pitch, PitchClass, PitchGroup, Scale, Tone};
+use crate::types::{sequences, Interval, KeySignature, Note, Pitch, PitchClass, PitchGroup, Scale, Tone};
 use super::{PitchGroupKernel, Subsequence, Tonic};
 
 
@@ -46,8 +46,7 @@ impl Sequence {
                         {
                             if sub.tones.iter().any(|t| t.index == index)
                                 {
-                                    sub.tones.retain(|t| t.index != index);
-                                    sub.sync();
+                                    sub.release_note(index);
                                 }
                         }
                     return;
@@ -69,6 +68,12 @@ impl Sequence {
             self.sequences.last_mut().unwrap().play_note(index, velocity);
         }
 
+    // Pitch::note only knows the pitchclass, so a sequence in Eb minor would show sharps until it is re-spelled in its key
+    pub fn respell(&mut self, key: KeySignature)
+        {
+            for sub in self.sequences.iter_mut() { sub.respell(key); }
+        }
+
     pub fn print_state(&self)
         {
             // clear the screen
@@ -100,4 +105,24 @@ mod tests {
         assert_eq!(sequence.lower_bound(), -1);
     }
 
+    #[test]
+    fn test_respell() {
+        let mut sequence = Sequence::new();
+        // Eb minor, then a G# far enough away to start its own subsequence
+        for index in [63, 66, 70, 116] { sequence.process_input(index, 100); }
+        assert_eq!(sequence.sequences.len(), 2);
+        // Only the played tones, lowest first
+        let played = |sequence: &Sequence| -> Vec<String> {
+            let mut tones: Vec<&Tonic> = sequence.sequences.iter().flat_map(|s| s.tones.iter()).collect();
+            tones.sort_by_key(|t| t.index);
+            tones.iter().filter_map(|t| t.note()).map(|n| n.to_string()).collect()
+        };
+
+        sequence.respell(KeySignature::new(-6).unwrap());
+        assert_eq!(played(&sequence), ["Eb", "Gb", "Bb", "Ab"]);
+
+        sequence.respell(KeySignature::new(6).unwrap());
+        assert_eq!(played(&sequence), ["D#", "F#", "A#", "G#"]);
+    }
+
 }
\ No newline at end of file
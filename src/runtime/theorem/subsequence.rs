@@ -13,14 +13,19 @@
 
 use std::collections::HashSet;
 use super::{Chord, PitchGroupKernel, Tonic};
-use crate::types::{Tone, Interval, Scale, Note};
+use crate::types::{CompoundInterval, KeySignature, Tone, Interval, Matrix, Pitch, PitchClass, PitchClassSet, PitchGroup, Scale, Note, Speller};
+
+// How many of the closest fitting scales we hold onto
+const SCALE_LIMIT: usize = 12;
 
 pub struct Subsequence {
     pub tones: HashSet<Tonic>,          // These are initially the tones being played, and we add the tones from the pitchgroupkernel across the entire bounds
     pub speculative: HashSet<Tonic>,    // These are the tones that are being inferred from the pitchgroupkernel and analysis of the tones - This needs to be moved up to the entire sequence level
     pub chords: HashSet<Chord>,             // We need to split this further into n_inversions and n_shapes
+    chords_for: Option<Vec<Note>>,      // The notes the chords were last named from, so we only name them when they change
     pub scales: HashSet<Scale>,             // We can use these to determine "gravity"
     pub kernel: PitchGroupKernel, 
+    scales_for: Option<PitchClassSet>,  // The pitch classes the scales were last searched for, so we only search when they change
     pub upper_bound: u8,                // This is the upper bound of the dynamic range for a set of keys + 7 but we may need to make this part of a filter (proprietary NEXUS)
     pub lower_bound: u8,                // This is the lower bound of the dynamic range for a set of keys - 7
 }
@@ -31,18 +36,15 @@ impl Subsequence {
             tones: HashSet::new(), 
             speculative: HashSet::new(),
             chords: HashSet::new(), 
+            chords_for: None,
             scales: HashSet::new(), 
             kernel: PitchGroupKernel::new(HashSet::new()),
+            scales_for: None,
             upper_bound: 144,       // These need to be swapped for a filter type
             lower_bound: 0
         } 
     }
 
-    // We need logic for the following:
-    // add note
-    // remove note
-    // update kernel
-
     pub fn upper_bound(&self) -> u8 { self.tones.iter().map(|t| t.index).max().unwrap_or(144)}
     pub fn lower_bound(&self) -> u8 { self.tones.iter().map(|t| t.index).min().unwrap_or(0)}
 
@@ -51,7 +53,7 @@ impl Subsequence {
     pub fn calculate_bounds(&mut self) 
         {
             self.upper_bound = (self.upper_bound() + 7).clamp(0, 144); // 144 is the max index
-            self.lower_bound = (self.lower_bound() - 7).clamp(0, 144); // 0 is the min index
+            self.lower_bound = self.lower_bound().saturating_sub(7).clamp(0, 144); // 0 is the min index
         }
 
     pub fn within_bounds(&self, index: u8) -> bool 
@@ -65,20 +67,26 @@ impl Subsequence {
     // This only works for notes that we actually play.. this does not work for speculative notes (which we calculate from the kernel)
     pub fn play_note(&mut self, index: u8, velocity: u8)
         {
-            // We need to check if the note is within the bounds of the sequence
-            if self.tones.len() == 0 
-                { 
-                    self.tones.insert(Tonic::new(index, velocity, 0));
-                    self.calculate_bounds();
-                }
+            let tonic = Tonic::new(index, velocity, 0);
 
-            // This is the actual 'play' logic
+            // This is the actual 'play' logic - the kernel only hears about tones it has not counted yet
+            if self.tones.insert(tonic.clone()) { self.kernel.add_tone(&tonic); }
             self.calculate_bounds();
-            self.tones.insert(Tonic::new(index, velocity, 0));
 
             // This is the speculative - theoretical logic
             self.sync();
-            // We need to update the kernel, but unfortunately we don't have a good update method to preserve caching or space-time complexity
+        }
+
+    // Stops every tone played at this index, taking them back out of the kernel
+    pub fn release_note(&mut self, index: u8)
+        {
+            let released: Vec<Tonic> = self.tones.iter().filter(|t| t.index == index).cloned().collect();
+            for tonic in released.iter()
+                {
+                    self.tones.remove(tonic);
+                    self.kernel.remove_tone(tonic);
+                }
+            self.sync();
         }
 
     fn cloned(&self) -> Subsequence
@@ -87,8 +95,10 @@ impl Subsequence {
                 tones: self.tones.clone(), 
                 speculative: self.speculative.clone(),
                 chords: self.chords.clone(), 
+                chords_for: self.chords_for.clone(),
                 scales: self.scales.clone(), 
                 kernel: self.kernel.clone(),
+                scales_for: self.scales_for,
                 upper_bound: self.upper_bound,
                 lower_bound: self.lower_bound
             }
@@ -96,10 +106,27 @@ impl Subsequence {
 
     pub fn sync(&mut self)
         {
-            // We could probably improve this by updating the kernel with the new tones
-            self.kernel = PitchGroupKernel::new(self.tones.clone());
+            // The kernel is kept up to date by play_note and release_note
             let speculative = self.kernel.normalize(self.tones.clone());
 
+            // Name what is being played - the lowest tone is the bass
+            self.name_chords();
+
+            // Scales holding everything being played, with the fewest tones left over
+            // The catalog search is only run again when the pitch classes being played change
+            let pitch_classes = self.kernel.pitch_classes();
+            if self.scales_for != Some(pitch_classes)
+                {
+                    let notes: Vec<Note> = self.tones.iter().filter_map(|t| t.note()).collect();
+                    self.scales = Scale::search_notes(&notes)
+                        .into_iter()
+                        .filter(|m| m.complete())
+                        .take(SCALE_LIMIT)
+                        .map(|m| m.scale)
+                        .collect();
+                    self.scales_for = Some(pitch_classes);
+                }
+
             // We go ahead and exit if this sync was a 'reset'
             if self.tones.len() == 0
                 { 
@@ -111,12 +138,12 @@ impl Subsequence {
 
             // We need to iterate from a lower bounds to an upper bounds and add the speculative tones
             self.calculate_bounds();
-            let mut prev_vel = self.tones.iter().map(|t| t.velocity).sum::<u8>() / self.tones.len() as u8;
+            let mut prev_vel = (self.tones.iter().map(|t| t.velocity as u16).sum::<u16>() / self.tones.len() as u16) as u8;
 
             for i in self.lower_bound..self.upper_bound
                 {
                     // If we find a speculative tone that is in the bounds
-                    if let mut t_tonic = speculative.iter().find(|t| t.index == i % 12).unwrap().clone()
+                    if let Some(mut t_tonic) = speculative.iter().find(|t| t.index == i % 12).cloned()
                         {
                             // We want to add something to calculate nearby velocity
                             
@@ -124,14 +151,14 @@ impl Subsequence {
                             if !self.tones.iter().any(|t| t.index == i)
                                 { 
                                     t_tonic.index = i;
-                                    t_tonic.velocity = (prev_vel + t_tonic.velocity) / 2;
+                                    t_tonic.velocity = ((prev_vel as u16 + t_tonic.velocity as u16) / 2) as u8;
                                     self.speculative.insert(t_tonic.clone()); 
                                     continue;
                                 }
 
-                            if let p_tonic = self.tones.iter().find(|t| t.index == i).unwrap()
+                            if let Some(p_tonic) = self.tones.iter().find(|t| t.index == i)
                                 { 
-                                    prev_vel = (prev_vel + p_tonic.velocity) / 2;
+                                    prev_vel = ((prev_vel as u16 + p_tonic.velocity as u16) / 2) as u8;
                                     self.speculative.insert(p_tonic.clone()); 
                                 }
                         }
@@ -142,4 +169,146 @@ impl Subsequence {
         {
             
         }
-}
\ No newline at end of file
+
+    // Re-spells the played and speculative tones in the key, reading chromatic tones against the chord being played
+    // so the G# of an E7 in A minor follows its root rather than coming out as Ab
+    pub fn respell(&mut self, key: KeySignature)
+        {
+            let mut played: Vec<u8> = self.tones.iter().map(|t| t.index).collect();
+            played.sort_unstable();
+            let notes: Vec<Note> = played.iter().map(|i| key.spell(&PitchClass::from_index(i % 12))).collect();
+
+            let mut speller = Speller::new(key);
+            if let Some(chord) = Chord::identify(&notes) { speller.set_chord(&chord.notes()); }
+            let respell = |t: &Tonic| t.respelled(speller.note(&PitchClass::from_index(t.index % 12), None));
+
+            self.tones = self.tones.iter().map(respell).collect();
+            // Tones can merge once they share a spelling, so the kernel is counted again
+            self.kernel = PitchGroupKernel::new(self.tones.clone());
+            // and the scales are searched again with the new spellings on the next sync
+            self.scales_for = None;
+            self.speculative = self.speculative.iter().map(respell).collect();
+            self.name_chords();
+        }
+
+    // Chord analysis is only run again when the notes being played, lowest first, change - a restruck
+    // tone or a velocity change leaves the chords as they were
+    fn name_chords(&mut self)
+        {
+            let mut tones: Vec<&Tonic> = self.tones.iter().collect();
+            tones.sort_by_key(|t| t.index);
+            tones.dedup_by_key(|t| (t.index, t.note()));
+            let notes: Vec<Note> = tones.iter().filter_map(|t| t.note()).collect();
+            if self.chords_for.as_ref() != Some(&notes)
+                {
+                    self.chords = Chord::identify(&notes).into_iter().collect();
+                    self.chords_for = Some(notes);
+                }
+        }
+
+    // Names every tone within the bounds as a compound interval above the lowest tone, spelled in the pitchgroup,
+    // so wide voicings and extended chords read as 9ths, 11ths and 13ths - tones outside the group are skipped
+    pub fn ladder(&self, pg: &PitchGroup) -> Vec<(u8, CompoundInterval)>
+        {
+            let root = match Matrix::spell(&Pitch::from_index(self.lower_bound()), pg) {
+                Some(root) => root,
+                None => return Vec::new(),
+            };
+            let mut indices: Vec<u8> = self.tones.iter().map(|t| t.index).filter(|i| self.within_bounds(*i)).collect();
+            indices.sort_unstable();
+            indices.dedup();
+            indices.into_iter()
+                .filter_map(|index| Matrix::ladder(&Pitch::from_index(index), &root, pg).map(|interval| (index, interval)))
+                .collect()
+        }
+}
+#[cfg(test)]
+mod tests {
+    use super::Subsequence;
+    use crate::types::{KeySignature, Pitch, PitchGroup};
+
+    #[test]
+    fn test_scales_follow_pitch_classes() {
+        let mut sub = Subsequence::new();
+        sub.play_note(60, 100);
+        sub.play_note(64, 100);
+        sub.play_note(67, 100);
+        let triad = sub.scales.clone();
+        assert!(!triad.is_empty());
+        assert!(triad.iter().all(|s| s.notes().iter().any(|n| n.pitch_class().to_index() == 4)));
+
+        // Doubling the root an octave up leaves the pitch classes, and the scales, alone
+        sub.play_note(72, 90);
+        assert_eq!(sub.scales_for, Some(sub.kernel.pitch_classes()));
+        assert_eq!(sub.scales, triad);
+
+        // A new pitch class searches again
+        sub.play_note(70, 90);
+        assert_eq!(sub.kernel.pitch_classes().len(), 4);
+        assert_eq!(sub.scales_for, Some(sub.kernel.pitch_classes()));
+        assert_ne!(sub.scales, triad);
+
+        sub.release_note(70);
+        sub.release_note(72);
+        assert_eq!(sub.scales, triad);
+        assert_eq!(sub.scales_for.map(|pcs| pcs.len()), Some(3));
+    }
+
+    #[test]
+    fn test_chords_follow_notes() {
+        let mut sub = Subsequence::new();
+        for index in [60, 64, 67] { sub.play_note(index, 100); }
+        assert_eq!(sub.chords.iter().map(|c| c.to_string()).collect::<Vec<_>>(), ["C"]);
+
+        // Striking the E again, softer, plays the same notes, so the chord is not named again
+        sub.chords.clear();
+        sub.play_note(64, 40);
+        assert!(sub.chords.is_empty());
+
+        sub.play_note(70, 100);
+        assert_eq!(sub.chords.iter().map(|c| c.to_string()).collect::<Vec<_>>(), ["C7"]);
+        sub.release_note(70);
+        assert_eq!(sub.chords.iter().map(|c| c.to_string()).collect::<Vec<_>>(), ["C"]);
+    }
+
+    #[test]
+    fn test_respell() {
+        let mut sub = Subsequence::new();
+        for index in [63, 66, 70] { sub.play_note(index, 100); }
+        let played = |sub: &Subsequence| -> Vec<String> {
+            let mut tones: Vec<_> = sub.tones.iter().collect();
+            tones.sort_by_key(|t| t.index);
+            tones.iter().filter_map(|t| t.note()).map(|n| n.to_string()).collect()
+        };
+
+        // Bb minor spells the triad in flats, and the chord follows the new spelling
+        sub.respell(KeySignature::new(-5).unwrap());
+        assert_eq!(played(&sub), ["Eb", "Gb", "Bb"]);
+        assert_eq!(sub.chords.iter().map(|c| c.to_string()).collect::<Vec<_>>(), ["Ebm"]);
+        assert_eq!(sub.scales_for, None);
+
+        // The next sync searches the scales again with the new spellings
+        sub.play_note(73, 100);
+        assert_eq!(sub.scales_for, Some(sub.kernel.pitch_classes()));
+        assert_eq!(played(&sub), ["Eb", "Gb", "Bb", "Db"]);
+    }
+
+    #[test]
+    fn test_ladder() {
+        let index = |pitch: &str| pitch.parse::<Pitch>().unwrap().to_index();
+        let mut sub = Subsequence::new();
+        for pitch in ["C3", "G3", "E4", "Bb4", "D5", "F#5", "A5"] { sub.play_note(index(pitch), 100); }
+
+        let ladder = |sub: &Subsequence, pg: PitchGroup| -> Vec<(u8, String)> {
+            sub.ladder(&pg).into_iter().map(|(index, interval)| (index, interval.to_string())).collect()
+        };
+        // A C13#11 voicing read up from the C in F, where the F#5 is outside the group
+        assert_eq!(ladder(&sub, PitchGroup::Fn), [
+            (index("C3"), "P1".to_string()), (index("G3"), "P5".to_string()), (index("E4"), "M10".to_string()),
+            (index("Bb4"), "m14".to_string()), (index("D5"), "M16".to_string()), (index("A5"), "M20".to_string()),
+        ]);
+        // In G the Bb is skipped instead, and the F# is an augmented eleventh an octave further up
+        assert_eq!(ladder(&sub, PitchGroup::Gn).iter().map(|(_, i)| i.as_str()).collect::<Vec<_>>(), ["P1", "P5", "M10", "M16", "A18", "M20"]);
+        assert!(ladder(&Subsequence::new(), PitchGroup::Cn).is_empty());
+    }
+}
@@ -0,0 +1,162 @@
+//
+// Copyright 2024 Richard I. Christopher, NeoTec Digital. All Rights Reserved.
+//
+
+use std::time::Duration;
+use super::{Chord, Sequence, Tonic};
+use crate::types::{KeySignature, Note, PitchClass, RowForm, Speller, ToneRow};
+
+/// A [Tonic] held from `start` for `duration`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimedTonic {
+    pub start: Duration,
+    pub duration: Duration,
+    pub tonic: Tonic,
+}
+
+impl TimedTonic {
+    pub fn end(&self) -> Duration { self.start + self.duration }
+    pub fn is_played(&self) -> bool { self.tonic.harmony == 0 }
+}
+
+/// Tones laid out in time - both the tones we played and the speculative tones inferred by the kernel.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Timeline {
+    pub tones: Vec<TimedTonic>,
+}
+
+impl Timeline {
+    pub fn new() -> Timeline { Timeline { tones: Vec::new() } }
+
+    pub fn push(&mut self, start: Duration, duration: Duration, tonic: Tonic) {
+        self.tones.push(TimedTonic { start, duration, tonic });
+    }
+
+    /// Snapshot the played and speculative tones of a [Sequence] for the given span of time
+    pub fn capture(&mut self, sequence: &Sequence, start: Duration, duration: Duration) {
+        let played: Vec<Tonic> = sequence.sequences.iter().flat_map(|s| s.tones.iter().cloned()).collect();
+
+        // The speculative set carries a copy of each played tone, which we already have
+        let mut speculative: Vec<Tonic> = sequence.sequences.iter()
+                                                  .flat_map(|s| s.speculative.iter().cloned())
+                                                  .filter(|t| t.harmony != 0 && !played.iter().any(|p| p.index == t.index))
+                                                  .collect();
+        speculative.sort_by_key(|t| t.index);
+        speculative.dedup_by_key(|t| t.index);
+
+        for tonic in played.into_iter().chain(speculative) {
+            self.push(start, duration, tonic);
+        }
+    }
+
+    /// Time at which the last tone ends
+    pub fn duration(&self) -> Duration { self.tones.iter().map(TimedTonic::end).max().unwrap_or(Duration::ZERO) }
+
+    pub fn played(&self) -> impl Iterator<Item = &TimedTonic> { self.tones.iter().filter(|t| t.is_played()) }
+    pub fn speculative(&self) -> impl Iterator<Item = &TimedTonic> { self.tones.iter().filter(|t| !t.is_played()) }
+
+    /// Re-spell every tone in a [KeySignature], reading chromatic tones against the chord sounding at the
+    /// time and, for a line of single tones, the direction it moves in
+    pub fn respell(&mut self, key: KeySignature) {
+        let mut onsets: Vec<Duration> = self.played().map(|t| t.start).collect();
+        onsets.sort();
+        onsets.dedup();
+
+        let mut speller = Speller::new(key);
+        let mut previous: Option<Vec<u8>> = None;
+        for start in onsets {
+            let mut sounding: Vec<u8> = self.played().filter(|t| t.start <= start && start < t.end()).map(|t| t.tonic.index).collect();
+            sounding.sort_unstable();
+            let notes: Vec<Note> = sounding.iter().map(|i| key.spell(&PitchClass::from_index(i % 12))).collect();
+            match Chord::identify(&notes) {
+                Some(chord) if notes.len() > 2 => speller.set_chord(&chord.notes()),
+                _ => speller.clear_chord(),
+            }
+
+            let starting: Vec<u8> = self.played().filter(|t| t.start == start).map(|t| t.tonic.index).collect();
+            let direction = match (&previous, starting.as_slice()) {
+                (Some(last), [index]) if last.len() == 1 => Some(index.cmp(&last[0])),
+                _ => None,
+            };
+            for t in self.tones.iter_mut().filter(|t| t.start == start) {
+                let direction = if t.is_played() { direction } else { None };
+                t.tonic = t.tonic.respelled(speller.note(&PitchClass::from_index(t.tonic.index % 12), direction));
+            }
+            previous = Some(starting);
+        }
+    }
+
+    /// Forms of a [ToneRow] heard in the played tones, in the order they started, with the time each form begins
+    pub fn row_forms(&self, row: &ToneRow) -> Vec<(Duration, RowForm)> {
+        // Tones without a pitch class are left out of both, so the row's indices land on the right tone
+        let mut played: Vec<(&TimedTonic, PitchClass)> = self.played().filter_map(|t| Some((t, t.tonic.pitch_class()?))).collect();
+        played.sort_by_key(|(t, _)| (t.start, t.tonic.index));
+        let classes: Vec<PitchClass> = played.iter().map(|(_, pc)| *pc).collect();
+        row.find(&classes).into_iter().map(|(i, form)| (played[i].0.start, form)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture() {
+        let mut sequence = Sequence::new();
+        sequence.process_input(60, 100);
+        sequence.process_input(64, 100);
+        sequence.process_input(67, 100);
+
+        let mut timeline = Timeline::new();
+        timeline.capture(&sequence, Duration::from_secs(1), Duration::from_millis(500));
+
+        let mut played: Vec<u8> = timeline.played().map(|t| t.tonic.index).collect();
+        played.sort();
+        assert_eq!(played, vec![60, 64, 67]);
+        assert!(timeline.speculative().all(|t| ![60, 64, 67].contains(&t.tonic.index)));
+        assert_eq!(timeline.duration(), Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn test_row_forms() {
+        use crate::types::{PitchClass::*, RowTransform};
+        let row = ToneRow::new(&[En, Fn, Gn, Cs, Fs, Ds, Gs, Dn, Bn, Cn, An, As]).unwrap();
+        let inversion = row.form(RowForm::new(RowTransform::Inversion, 2));
+
+        let mut timeline = Timeline::new();
+        for (i, pc) in inversion.iter().enumerate() {
+            let start = Duration::from_millis(250 * (i as u64 + 1));
+            timeline.push(start, Duration::from_millis(250), Tonic::new(60 + pc.to_index(), 100, 0));
+        }
+        // Speculative tones are not part of the row
+        timeline.push(Duration::from_millis(500), Duration::from_millis(250), Tonic::new(61, 100, 1));
+        // and neither is a played tone with no pitch, even when it starts first
+        timeline.push(Duration::ZERO, Duration::from_millis(250), Tonic { note: None, tone: None, index: 0, velocity: 100, harmony: 0 });
+
+        assert_eq!(timeline.row_forms(&row), vec![(Duration::from_millis(250), RowForm::new(RowTransform::Inversion, 2))]);
+    }
+
+    #[test]
+    fn test_respell() {
+        let beat = |n: u64| Duration::from_millis(250 * n);
+        let mut timeline = Timeline::new();
+        // Eb harmonic minor climbing through its leading tone
+        for (i, index) in [63u8, 65, 66, 68, 70, 71, 74, 75].iter().enumerate() {
+            timeline.push(beat(i as u64), beat(1), Tonic::new(*index, 100, 0));
+        }
+        timeline.respell(KeySignature::new(-6).unwrap());
+        let notes: Vec<String> = timeline.played().filter_map(|t| t.tonic.note()).map(|n| n.to_string()).collect();
+        assert_eq!(notes.join(" "), "Eb F Gb Ab Bb Cb D Eb");
+
+        // Stepping down from A onto G# over a held E7 follows the chord rather than reading Ab
+        let mut timeline = Timeline::new();
+        for index in [52u8, 56, 59, 62] {
+            timeline.push(beat(0), beat(3), Tonic::new(index, 100, 0));
+        }
+        timeline.push(beat(1), beat(1), Tonic::new(69, 100, 0));
+        timeline.push(beat(2), beat(1), Tonic::new(68, 100, 0));
+        timeline.respell(KeySignature::new(0).unwrap());
+        let late = timeline.played().find(|t| t.start == beat(2)).unwrap();
+        assert_eq!(late.tonic.tone.unwrap().to_string(), "G#4");
+    }
+}
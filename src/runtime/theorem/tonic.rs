@@ -2,7 +2,7 @@
 use std::collections::HashSet;
 
 use super::{chord::Chord, Subsequence};
-use crate::types::{Interval, Note, Matrix, Octave, PitchClass, Scale, Tone};
+use crate::types::{Interval, Note, Matrix, Octave, Pitch, PitchClass, Scale, Tone};
 
 #[derive(Debug, Eq, Hash, PartialEq, Clone)]
 pub struct Tonic {
@@ -33,6 +33,14 @@ impl Tonic {
     pub fn pitch_class(&self) -> Option<PitchClass> { Some(self.tone?.pitch_class()) }
     pub fn note(&self) -> Option<Note> { if self.note.is_some() { self.note } else if self.tone.is_some() { Some(self.tone?.note()) } else { None } }
     pub fn velocity(&self) -> u8 { self.velocity }
+
+    // Same tonic written as another note - the tone's octave follows the letter, so B#3 and C4 are the same index
+    pub fn respelled(&self, note: Note) -> Tonic {
+        match Tone::spelled(&Pitch::from_index(self.index), note) {
+            Some(tone) => Tonic { note: Some(note), tone: Some(tone), ..self.clone() },
+            None => self.clone(),
+        }
+    }
 }
 
 impl IntoIterator for Tonic {
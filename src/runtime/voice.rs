@@ -0,0 +1,372 @@
+//
+// Copyright 2024 Richard I. Christopher, NeoTec Digital. All Rights Reserved.
+//
+//  Note lifecycle for Waveform playback - ADSR envelopes, voices and a polyphonic
+//  voice allocator that plays as a single rodio Source.
+//
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use rodio::Source;
+use crate::types::{Pitch, Tuning};
+use super::{Tonic, Waveform, WaveformType};
+
+/// Samples rendered by a [VoiceSource] each time it locks the voices.
+pub const BLOCK_SIZE: usize = 64;
+
+/// Attack, Decay, Sustain and Release of an [Envelope].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Adsr {
+    pub attack: Duration,
+    pub decay: Duration,
+    /// Level held after the decay, 0.0 - 1.0
+    pub sustain: f32,
+    pub release: Duration,
+}
+
+impl Default for Adsr {
+    fn default() -> Adsr {
+        Adsr {
+            attack: Duration::from_millis(5),
+            decay: Duration::from_millis(100),
+            sustain: 0.7,
+            release: Duration::from_millis(200),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Stage {
+    Idle,
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+/// Linear ADSR envelope, advanced one sample at a time.
+#[derive(Clone, Debug)]
+pub struct Envelope {
+    pub adsr: Adsr,
+    sample_rate: u32,
+    stage: Stage,
+    level: f32,
+    release_step: f32,
+}
+
+impl Envelope {
+    pub fn new(sample_rate: u32, adsr: Adsr) -> Envelope {
+        Envelope { adsr, sample_rate, stage: Stage::Idle, level: 0.0, release_step: 0.0 }
+    }
+
+    pub fn stage(&self) -> Stage { self.stage }
+    pub fn level(&self) -> f32 { self.level }
+    pub fn is_active(&self) -> bool { self.stage != Stage::Idle }
+
+    // Change in level per sample to cover `range` in `time`
+    fn step(&self, range: f32, time: Duration) -> f32 {
+        let samples = time.as_secs_f32() * self.sample_rate as f32;
+        if samples < 1.0 { range } else { range / samples }
+    }
+
+    /// Start the attack from the current level, so retriggering a sounding voice doesn't click
+    pub fn note_on(&mut self) { self.stage = Stage::Attack; }
+
+    pub fn note_off(&mut self) {
+        if self.stage == Stage::Idle { return; }
+        self.stage = Stage::Release;
+        self.release_step = self.step(self.level, self.adsr.release);
+    }
+
+    pub fn next_level(&mut self) -> f32 {
+        match self.stage {
+            Stage::Idle => self.level = 0.0,
+            Stage::Attack => {
+                self.level += self.step(1.0, self.adsr.attack);
+                if self.level >= 1.0 { self.level = 1.0; self.stage = Stage::Decay; }
+            }
+            Stage::Decay => {
+                self.level -= self.step(1.0 - self.adsr.sustain, self.adsr.decay);
+                if self.level <= self.adsr.sustain { self.level = self.adsr.sustain; self.stage = Stage::Sustain; }
+            }
+            Stage::Sustain => self.level = self.adsr.sustain,
+            Stage::Release => {
+                self.level -= self.release_step;
+                if self.level <= 0.0 { self.level = 0.0; self.stage = Stage::Idle; }
+            }
+        }
+        self.level
+    }
+}
+
+/// A [Waveform] shaped by an [Envelope] and scaled by velocity.
+#[derive(Clone)]
+pub struct Voice {
+    pub waveform: Waveform,
+    pub envelope: Envelope,
+    index: u8,
+    velocity: f32,
+    age: u64,
+}
+
+impl Voice {
+    pub fn new(waveform: Waveform, adsr: Adsr) -> Voice {
+        let envelope = Envelope::new(waveform.sample_rate, adsr);
+        Voice { waveform, envelope, index: 0, velocity: 0.0, age: 0 }
+    }
+
+    /// Index (0-143) of the note this voice is playing, or last played
+    pub fn index(&self) -> u8 { self.index }
+    pub fn is_active(&self) -> bool { self.envelope.is_active() }
+    pub fn is_released(&self) -> bool { matches!(self.envelope.stage(), Stage::Release | Stage::Idle) }
+
+    pub fn note_on(&mut self, index: u8, velocity: u8, tuning: Tuning) {
+        if !self.is_active() { self.waveform.reset_phase(); }
+        self.index = index;
+        self.velocity = velocity.min(127) as f32 / 127.0;
+        self.waveform.set_frequency(Pitch::from_index(index.min(143)).frequency(tuning));
+        self.envelope.note_on();
+    }
+
+    pub fn note_off(&mut self) { self.envelope.note_off(); }
+
+    pub fn next_sample(&mut self) -> f32 {
+        if !self.is_active() { return 0.0; }
+        let level = self.envelope.next_level();
+        self.waveform.next_sample() * level * self.velocity
+    }
+}
+
+// Voices shared between whoever plays notes and the audio thread
+struct Voices {
+    voices: Vec<Voice>,
+    tuning: Tuning,
+    gain: f32,
+    clock: u64,
+}
+
+impl Voices {
+    fn note_on(&mut self, index: u8, velocity: u8) {
+        self.clock += 1;
+        let tuning = self.tuning;
+
+        // Retrigger a voice already on this note, otherwise take a free voice, the quietest released voice, or the oldest
+        let slot = self.voices.iter().position(|v| v.is_active() && v.index == index)
+            .or_else(|| self.voices.iter().position(|v| !v.is_active()))
+            .or_else(|| self.voices.iter().enumerate()
+                                  .filter(|(_, v)| v.is_released())
+                                  .min_by(|(_, a), (_, b)| a.envelope.level().total_cmp(&b.envelope.level()))
+                                  .map(|(i, _)| i))
+            .or_else(|| self.voices.iter().enumerate().min_by_key(|(_, v)| v.age).map(|(i, _)| i));
+
+        if let Some(slot) = slot {
+            let voice = &mut self.voices[slot];
+            voice.age = self.clock;
+            voice.note_on(index, velocity, tuning);
+        }
+    }
+
+    fn note_off(&mut self, index: u8) {
+        for voice in self.voices.iter_mut().filter(|v| v.index == index && !v.is_released()) {
+            voice.note_off();
+        }
+    }
+
+    fn next_sample(&mut self) -> f32 {
+        self.voices.iter_mut().map(Voice::next_sample).sum::<f32>() * self.gain
+    }
+}
+
+/// Polyphonic voice allocator keyed by the 0-143 index used by [Tonic]. Clones share the same voices.
+#[derive(Clone)]
+pub struct VoiceManager {
+    pub sample_rate: u32,
+    voices: Arc<Mutex<Voices>>,
+}
+
+impl VoiceManager {
+    pub fn new(sample_rate: u32, polyphony: usize, waveform_type: WaveformType) -> VoiceManager {
+        VoiceManager::with_waveform(Waveform::with_type(sample_rate, waveform_type), polyphony, Adsr::default())
+    }
+
+    /// Voices are copies of `waveform`, all shaped by `adsr`
+    pub fn with_waveform(waveform: Waveform, polyphony: usize, adsr: Adsr) -> VoiceManager {
+        let sample_rate = waveform.sample_rate;
+        let voices = Voices {
+            voices: vec![Voice::new(waveform, adsr); polyphony.max(1)],
+            tuning: Tuning::A4_440Hz,
+            gain: 1.0 / (polyphony.max(1) as f32).sqrt(),
+            clock: 0,
+        };
+        VoiceManager { sample_rate, voices: Arc::new(Mutex::new(voices)) }
+    }
+
+    pub fn set_tuning(&self, tuning: Tuning) { self.voices.lock().unwrap().tuning = tuning; }
+    pub fn set_gain(&self, gain: f32) { self.voices.lock().unwrap().gain = gain; }
+    pub fn set_waveform_type(&self, waveform_type: WaveformType) {
+        for voice in &mut self.voices.lock().unwrap().voices { voice.waveform.set_waveform_type(waveform_type); }
+    }
+
+    /// Follows the [Sequence::process_input](crate::runtime::Sequence::process_input) convention - a velocity of 0 releases the note
+    pub fn note_on(&self, index: u8, velocity: u8) {
+        let mut voices = self.voices.lock().unwrap();
+        if velocity == 0 { voices.note_off(index); } else { voices.note_on(index, velocity); }
+    }
+
+    pub fn note_off(&self, index: u8) { self.voices.lock().unwrap().note_off(index); }
+
+    pub fn all_notes_off(&self) {
+        for voice in &mut self.voices.lock().unwrap().voices { voice.note_off(); }
+    }
+
+    /// Release every voice not in `tones` and start the tones that aren't sounding yet
+    pub fn sync(&self, tones: &[Tonic]) {
+        let mut wanted: HashMap<u8, u8> = HashMap::new();
+        for tonic in tones.iter().filter(|t| t.index < 144 && t.velocity > 0) {
+            let velocity = wanted.entry(tonic.index).or_insert(0);
+            *velocity = (*velocity).max(tonic.velocity);
+        }
+
+        let mut voices = self.voices.lock().unwrap();
+        let sounding: Vec<u8> = voices.voices.iter().filter(|v| !v.is_released()).map(Voice::index).collect();
+        for index in sounding.iter().filter(|i| !wanted.contains_key(i)) {
+            voices.note_off(*index);
+        }
+
+        let mut starting: Vec<(u8, u8)> = wanted.into_iter().filter(|(i, _)| !sounding.contains(i)).collect();
+        starting.sort();
+        for (index, velocity) in starting {
+            voices.note_on(index, velocity);
+        }
+    }
+
+    /// Indices of the notes currently held (not yet released)
+    pub fn active(&self) -> Vec<u8> {
+        let mut active: Vec<u8> = self.voices.lock().unwrap().voices.iter().filter(|v| !v.is_released()).map(Voice::index).collect();
+        active.sort();
+        active
+    }
+
+    /// An endless rodio [Source] mixing every voice
+    pub fn source(&self) -> VoiceSource {
+        VoiceSource { manager: self.clone(), block: [0.0; BLOCK_SIZE], position: BLOCK_SIZE }
+    }
+}
+
+/// Mono mix of a [VoiceManager], rendered a block at a time.
+pub struct VoiceSource {
+    manager: VoiceManager,
+    block: [f32; BLOCK_SIZE],
+    position: usize,
+}
+
+impl Iterator for VoiceSource {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.position == BLOCK_SIZE {
+            let mut voices = self.manager.voices.lock().unwrap();
+            for sample in &mut self.block { *sample = voices.next_sample(); }
+            self.position = 0;
+        }
+        self.position += 1;
+        Some(self.block[self.position - 1])
+    }
+}
+
+impl Source for VoiceSource {
+    fn current_frame_len(&self) -> Option<usize> { Some(BLOCK_SIZE - self.position) }
+    fn channels(&self) -> u16 { 1 }
+    fn sample_rate(&self) -> u32 { self.manager.sample_rate }
+    fn total_duration(&self) -> Option<Duration> { None }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_envelope() {
+        let adsr = Adsr { attack: Duration::from_millis(10), decay: Duration::from_millis(10), sustain: 0.5, release: Duration::from_millis(20) };
+        let mut envelope = Envelope::new(1000, adsr);
+        assert_eq!(envelope.next_level(), 0.0);
+
+        envelope.note_on();
+        let attack: Vec<f32> = (0..10).map(|_| envelope.next_level()).collect();
+        assert!(attack.windows(2).all(|w| w[1] > w[0]));
+        assert_eq!(envelope.level(), 1.0);
+        assert_eq!(envelope.stage(), Stage::Decay);
+
+        for _ in 0..10 { envelope.next_level(); }
+        assert_eq!(envelope.stage(), Stage::Sustain);
+        for _ in 0..100 { assert_eq!(envelope.next_level(), 0.5); }
+
+        envelope.note_off();
+        for _ in 0..19 { envelope.next_level(); }
+        assert!(envelope.is_active());
+        envelope.next_level();
+        assert_eq!(envelope.stage(), Stage::Idle);
+        assert_eq!(envelope.level(), 0.0);
+    }
+
+    #[test]
+    fn test_release_during_attack() {
+        let adsr = Adsr { attack: Duration::from_millis(100), release: Duration::from_millis(10), ..Adsr::default() };
+        let mut envelope = Envelope::new(1000, adsr);
+        envelope.note_on();
+        for _ in 0..50 { envelope.next_level(); }
+        envelope.note_off();
+        let release: Vec<f32> = (0..10).map(|_| envelope.next_level()).collect();
+        assert!(release.windows(2).all(|w| w[1] < w[0]));
+        assert!(!envelope.is_active());
+    }
+
+    #[test]
+    fn test_voice_stealing() {
+        let manager = VoiceManager::new(1000, 2, WaveformType::Sine);
+        manager.note_on(60, 100);
+        manager.note_on(64, 100);
+        manager.note_on(67, 100);
+        assert_eq!(manager.active(), vec![64, 67]);
+
+        // Released voices are taken before held voices
+        manager.note_off(64);
+        manager.note_on(72, 100);
+        assert_eq!(manager.active(), vec![67, 72]);
+
+        // Restriking a note reuses its voice
+        manager.note_on(72, 50);
+        assert_eq!(manager.active(), vec![67, 72]);
+        manager.note_on(72, 0);
+        assert_eq!(manager.active(), vec![67]);
+    }
+
+    #[test]
+    fn test_sync() {
+        let manager = VoiceManager::new(1000, 8, WaveformType::Sine);
+        manager.sync(&[Tonic::new(60, 100, 0), Tonic::new(64, 80, 0), Tonic::new(64, 30, 1)]);
+        assert_eq!(manager.active(), vec![60, 64]);
+        manager.sync(&[Tonic::new(64, 80, 0), Tonic::new(67, 80, 0)]);
+        assert_eq!(manager.active(), vec![64, 67]);
+        manager.sync(&[]);
+        assert!(manager.active().is_empty());
+    }
+
+    #[test]
+    fn test_source() {
+        let manager = VoiceManager::new(8000, 4, WaveformType::Sine);
+        let mut source = manager.source();
+        assert!(source.by_ref().take(256).all(|s| s == 0.0));
+
+        manager.note_on(69, 127);
+        let sounding: Vec<f32> = source.by_ref().take(4000).collect();
+        assert!(sounding.iter().any(|s| s.abs() > 0.3));
+
+        // Once released the source falls back to silence but keeps going
+        manager.note_off(69);
+        let _ = source.by_ref().take(4000).count();
+        assert!(source.by_ref().take(256).all(|s| s == 0.0));
+        assert_eq!(source.total_duration(), None);
+    }
+}
@@ -2,13 +2,18 @@
 // Copyright 2024 Richard I. Christopher, NeoTec Digital. All Rights Reserved.
 //
 
-use std::path::Iter;
+use std::sync::{Arc, OnceLock};
 use std::time::Duration;
 use rodio::Source;
 
-const TWO_PI: f32 = 2.0 * std::f32::consts::PI;
+const TWO_PI: f64 = 2.0 * std::f64::consts::PI;
 
-#[derive(Clone)]
+/// Samples in each band-limited table.
+pub const TABLE_SIZE: usize = 2048;
+/// Number of mip levels - level `n` holds at most `2^(MIP_LEVELS - 1 - n)` harmonics.
+pub const MIP_LEVELS: usize = 10;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum WaveformType {
     Sine,
     Square,
@@ -16,115 +21,289 @@ pub enum WaveformType {
     Triangle,
 }
 
+impl WaveformType {
+    pub fn all() -> [WaveformType; 4] { [WaveformType::Sine, WaveformType::Square, WaveformType::Sawtooth, WaveformType::Triangle] }
+
+    // Amplitude of the nth harmonic of the Fourier series
+    fn harmonic(&self, n: usize) -> f64 {
+        let odd = n % 2 == 1;
+        match self {
+            WaveformType::Sine => if n == 1 { 1.0 } else { 0.0 },
+            WaveformType::Square => if odd { 1.0 / n as f64 } else { 0.0 },
+            WaveformType::Sawtooth => if odd { 1.0 / n as f64 } else { -1.0 / n as f64 },
+            WaveformType::Triangle => if !odd { 0.0 } else if n % 4 == 1 { 1.0 / (n * n) as f64 } else { -1.0 / (n * n) as f64 },
+        }
+    }
+
+    /// Shared band-limited tables for this waveform, built on first use
+    pub fn tables(&self) -> Arc<WavetableSet> {
+        static TABLES: [OnceLock<Arc<WavetableSet>>; 4] = [OnceLock::new(), OnceLock::new(), OnceLock::new(), OnceLock::new()];
+        let index = WaveformType::all().iter().position(|t| t == self).unwrap();
+        Arc::clone(TABLES[index].get_or_init(|| Arc::new(WavetableSet::band_limited(*self))))
+    }
+}
+
+/// A set of single cycle tables, each holding fewer harmonics than the last, so that
+/// higher frequencies can read from a table with no partials above Nyquist.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WavetableSet {
+    /// (harmonics, table) from the richest table to the plainest
+    levels: Vec<(usize, Vec<f32>)>,
+}
+
+impl WavetableSet {
+    /// Additive synthesis of one table per octave of harmonic content
+    pub fn band_limited(waveform_type: WaveformType) -> WavetableSet {
+        let levels = (0..MIP_LEVELS).map(|level| {
+            let harmonics = 1 << (MIP_LEVELS - 1 - level);
+            let mut table: Vec<f64> = vec![0.0; TABLE_SIZE];
+            for n in 1..=harmonics {
+                let amplitude = waveform_type.harmonic(n);
+                if amplitude == 0.0 { continue; }
+                for (i, sample) in table.iter_mut().enumerate() {
+                    *sample += amplitude * (TWO_PI * ((n * i) % TABLE_SIZE) as f64 / TABLE_SIZE as f64).sin();
+                }
+            }
+
+            // Normalize each level to a peak of 1.0 so loudness doesn't jump between levels
+            let peak = table.iter().fold(0.0f64, |peak, s| peak.max(s.abs()));
+            (harmonics, table.iter().map(|s| (s / peak) as f32).collect())
+        }).collect();
+
+        WavetableSet { levels }
+    }
+
+    /// A single user supplied table, read at every frequency without band limiting - an empty table
+    /// gives an empty set, which plays silence
+    pub fn custom(table: Vec<f32>) -> WavetableSet {
+        if table.is_empty() { return WavetableSet { levels: Vec::new() }; }
+        WavetableSet { levels: vec![(usize::MAX, table)] }
+    }
+
+    /// Richest table whose highest harmonic stays below Nyquist at this frequency
+    pub fn level(&self, frequency: f32, sample_rate: u32) -> usize {
+        let limit = (sample_rate as f64 / 2.0) / (frequency.abs().max(f32::MIN_POSITIVE) as f64);
+        self.levels.iter()
+                   .position(|(harmonics, _)| (*harmonics as f64) < limit)
+                   .unwrap_or(self.levels.len().saturating_sub(1))
+    }
+
+    pub fn table(&self, level: usize) -> &[f32] { &self.levels[level].1 }
+    pub fn harmonics(&self, level: usize) -> usize { self.levels[level].0 }
+    pub fn len(&self) -> usize { self.levels.len() }
+    pub fn is_empty(&self) -> bool { self.levels.is_empty() }
+}
+
 #[derive(Clone)]
 pub struct Waveform {
     pub sample_rate: u32,
-    waveform_type: WaveformType, // Does this become a Vector of Waveforms and what do we do about clone?
-    wave_table: Vec<f32>,
+    waveform_type: Option<WaveformType>,    // None for a custom wave table
+    tables: Arc<WavetableSet>,
+    level: usize,
     frequency: f32,
-    sample_index: f32,
-    increment: f32,
+    phase: f64,         // position in the cycle, 0.0 - 1.0
+    increment: f64,     // cycles per sample
 }
 
-// This class is a simple wavetable oscillator that generates a sine wave
+// This class is a wavetable oscillator reading from a band-limited table for the current frequency
 impl Waveform {
+    /// Oscillator reading from a single custom wave table
     pub fn new(sample_rate: u32, wave_table: Vec<f32>) -> Waveform {
-        Self { 
-            sample_rate, 
-            waveform_type: WaveformType::Sine, // Todo - Split this into an array of waveforms
-            wave_table, // Is this Global or Local or Both? - How do we handle this?
-            frequency: 0.0, 
-            sample_index: 0.0, 
-            increment: 0.0 
-        }
+        Waveform::from_tables(sample_rate, None, Arc::new(WavetableSet::custom(wave_table)))
     }
 
+    /// Band-limited oscillator of the given type
+    pub fn with_type(sample_rate: u32, waveform_type: WaveformType) -> Waveform {
+        Waveform::from_tables(sample_rate, Some(waveform_type), waveform_type.tables())
+    }
 
-    // returns the frequency of the waveform in Hz / length of the wave table
-    pub fn frequency(&mut self, frequency: f32) -> f32 {
-        self.frequency = frequency;
+    fn from_tables(sample_rate: u32, waveform_type: Option<WaveformType>, tables: Arc<WavetableSet>) -> Waveform {
+        Self {
+            sample_rate,
+            waveform_type,
+            tables,
+            level: 0,
+            frequency: 0.0,
+            phase: 0.0,
+            increment: 0.0,
+        }
+    }
+
+    pub fn waveform_type(&self) -> Option<WaveformType> { self.waveform_type }
 
-        frequency * self.wave_table.len() as f32
+    /// Switch to the band-limited tables of another waveform type, keeping phase and frequency
+    pub fn set_waveform_type(&mut self, waveform_type: WaveformType) {
+        self.waveform_type = Some(waveform_type);
+        self.tables = waveform_type.tables();
+        self.level = self.tables.level(self.frequency, self.sample_rate);
     }
 
-    // we set the frequency of the waveform and calculate the increment based on the sample rate
+    pub fn frequency(&self) -> f32 { self.frequency }
+    pub fn phase(&self) -> f64 { self.phase }
+
+    // we set the frequency of the waveform and calculate the increment based on the sample rate - the phase
+    // carries on from where it was so there is no discontinuity
     pub fn set_frequency(&mut self, frequency: f32) {
-        self.increment = self.frequency(frequency) / self.sample_rate as f32;
+        self.frequency = frequency;
+        self.increment = frequency as f64 / self.sample_rate as f64;
+        self.level = self.tables.level(frequency, self.sample_rate);
     }
 
+    pub fn reset_phase(&mut self) { self.phase = 0.0; }
+
     // we interpolate between the two samples in the wave table
-    fn interpolate(&mut self) -> f32 {
-        let index = self.sample_index as usize;                                         // truncate the index to an integer                 e.g 1.5 -> 1
-        let next_index = (index + 1) % self.wave_table.len();                           // get the next index                               e.g 1.5 -> 2 % 1024 = 2
-        let fractional_index_value = self.sample_index - index as f32;                  // get the fractional part of the index weight      e.g 1.5 - 1 = 0.5
+    fn interpolate(&self) -> f32 {
+        if self.tables.is_empty() { return 0.0; }
+        let table = self.tables.table(self.level);
+        let position = self.phase * table.len() as f64;
+        let index = position as usize % table.len();                                    // truncate the index to an integer                 e.g 1.5 -> 1
+        let next_index = (index + 1) % table.len();                                     // get the next index                               e.g 1.5 -> 2 % 1024 = 2
+        let fractional_index_value = position.fract() as f32;                           // get the fractional part of the index weight      e.g 1.5 - 1 = 0.5
         let truncated_index_value = 1.0 - fractional_index_value;                       // get the truncated index weight                   e.g 1 - 0.5 = 0.5
 
-        let sample = self.wave_table[index] * truncated_index_value + self.wave_table[next_index] * fractional_index_value;
-        sample
+        table[index] * truncated_index_value + table[next_index] * fractional_index_value
+    }
+
+    // we move on to the next sample and return it
+    pub fn waveform(&mut self) -> f32 { 
+        self.next_sample();
+        self.interpolate()
     }
 
     // we calculate the next sample in the waveform
     pub fn next_sample(&mut self) -> f32 {
         let sample = self.interpolate();
-        self.sample_index = (self.sample_index + self.frequency) % self.wave_table.len() as f32;
+        self.phase = (self.phase + self.increment).rem_euclid(1.0);
         sample
     }
+}
+
+impl Iterator for Waveform {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        Some(self.next_sample())
+    }
+}
 
+impl Source for Waveform {
+    fn current_frame_len(&self) -> Option<usize> { Some(1) }
+    fn channels(&self) -> u16 { 1 } // TODO - This should be a vector of channels
+    fn sample_rate(&self) -> u32 { self.sample_rate }
+    fn total_duration(&self) -> Option<Duration> { None }
+}
 
-    // Used to calculate the frequency of the waveform
-    fn sin_frequency(&mut self, frequency: f32) -> f32 { self.sample_index * frequency * TWO_PI / self.sample_rate as f32 }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
+    // Magnitude of every bin up to Nyquist
+    fn spectrum(samples: &[f32]) -> Vec<f64> {
+        let n = samples.len();
+        (0..=n / 2).map(|k| {
+            let (re, im) = samples.iter().enumerate().fold((0.0, 0.0), |(re, im), (i, s)| {
+                let angle = TWO_PI * ((k * i) % n) as f64 / n as f64;
+                (re + *s as f64 * angle.cos(), im - *s as f64 * angle.sin())
+            });
+            (re * re + im * im).sqrt() * 2.0 / n as f64
+        }).collect()
+    }
 
-    fn generate(&mut self, sample_rate: u32, incriment: i32, gain: f32) -> f32 {
-        let mut out = 0.0; 
-        let mut i = 1;
+    #[test]
+    fn test_no_aliasing() {
+        // 4800 samples at 48kHz gives 10Hz bins, so every harmonic lands on a bin
+        let (sample_rate, length) = (48_000, 4800);
 
-        // used to generate our waveforms based on frequency, multiple and sample rate
-        let multiple = |f: f32, m: f32, s: u32| -> bool { f * m > s as f32 / 2.0 };
+        for waveform_type in [WaveformType::Square, WaveformType::Sawtooth, WaveformType::Triangle] {
+            for frequency in [110.0, 1130.0, 3010.0, 7770.0] {
+                let mut oscillator = Waveform::with_type(sample_rate, waveform_type);
+                oscillator.set_frequency(frequency);
+                let samples: Vec<f32> = oscillator.take(length).collect();
+                let bins = spectrum(&samples);
 
-        // used to calculate the gain factor for the waveform
-        let gain_factor = |i: f32, g: f32| -> f32 { 1.0 / (i).powf(g) };
+                let fundamental = (frequency / 10.0) as usize;
+                let reference = bins[fundamental];
+                assert!(reference > 0.5, "{:?} {} {}", waveform_type, frequency, reference);
 
-        while !multiple(self.frequency, i as f32, sample_rate) {
-            out += gain_factor(i as f32, gain) * self.sin_frequency(self.frequency * i as f32);
-            i += incriment;
+                // Anything that isn't a harmonic of the fundamental is folded back from above Nyquist
+                for (k, magnitude) in bins.iter().enumerate().skip(1) {
+                    if k % fundamental != 0 {
+                        assert!(magnitude / reference < 1e-3, "{:?} at {}Hz has {}Hz at {}", waveform_type, frequency, k * 10, magnitude / reference);
+                    }
+                }
+            }
         }
+    }
 
-        out
+    #[test]
+    fn test_empty_table() {
+        assert!(WavetableSet::custom(Vec::new()).is_empty());
+        let mut oscillator = Waveform::new(48_000, Vec::new());
+        oscillator.set_frequency(440.0);
+        assert!(oscillator.by_ref().take(64).all(|sample| sample == 0.0));
+        assert_eq!(oscillator.waveform(), 0.0);
     }
 
-    fn sine(&mut self) -> f32 { self.sin_frequency(self.frequency) }
-    fn square(&mut self, sample_rate: u32) -> f32 { self.generate(sample_rate, 2, 1.0) }
-    fn sawtooth(&mut self, sample_rate: u32) -> f32 { self.generate(sample_rate, 1, 1.0) }
-    fn triangle(&mut self, sample_rate: u32) -> f32 { self.generate(sample_rate, 2, 2.0) }
+    #[test]
+    fn test_harmonic_content() {
+        let (sample_rate, length) = (48_000, 4800);
+        let mut oscillator = Waveform::with_type(sample_rate, WaveformType::Square);
+        oscillator.set_frequency(1000.0);
+        let bins = spectrum(&oscillator.take(length).collect::<Vec<f32>>());
 
-    pub fn waveform(&mut self) -> f32 { 
-        self.next_sample();
+        // Odd harmonics fall off as 1/n and even harmonics are missing
+        assert!((bins[300] / bins[100] - 1.0 / 3.0).abs() < 0.01);
+        assert!((bins[500] / bins[100] - 1.0 / 5.0).abs() < 0.01);
+        assert!(bins[200] / bins[100] < 1e-3);
 
-        match self.waveform_type {
-            WaveformType::Sine => self.sine(),
-            WaveformType::Square => self.square(self.sample_rate),
-            WaveformType::Sawtooth => self.sawtooth(self.sample_rate),
-            WaveformType::Triangle => self.triangle(self.sample_rate),
+        let tables = WaveformType::Sawtooth.tables();
+        assert_eq!(tables.len(), MIP_LEVELS);
+        assert!(tables.harmonics(tables.level(1000.0, sample_rate)) * 1000 < 24_000);
+        assert!(tables.harmonics(tables.level(1000.0, sample_rate)) * 2 * 1000 >= 24_000);
+    }
+
+    #[test]
+    fn test_sine() {
+        let mut oscillator = Waveform::with_type(44_100, WaveformType::Sine);
+        oscillator.set_frequency(441.0);
+        for (i, sample) in oscillator.take(200).enumerate() {
+            let expected = (TWO_PI * 441.0 * i as f64 / 44_100.0).sin() as f32;
+            assert!((sample - expected).abs() < 1e-4, "{} {} {}", i, sample, expected);
         }
     }
-}
 
+    #[test]
+    fn test_phase_continuity() {
+        let sample_rate = 48_000;
+        let mut oscillator = Waveform::with_type(sample_rate, WaveformType::Sine);
+        oscillator.set_frequency(200.0);
 
-impl Iterator for Waveform {
-    type Item = f32;
+        let mut samples: Vec<f32> = Vec::new();
+        for frequency in [200.0, 2000.0, 350.0, 13.0] {
+            oscillator.set_frequency(frequency);
+            let phase = oscillator.phase();
+            samples.extend(oscillator.by_ref().take(1234));
+            assert!(oscillator.phase() != phase);
+        }
 
-    fn next(&mut self) -> Option<f32> {
-        Some(self.next_sample())
-        // test waveform function here
+        // The largest step of a unit sine is 2π f / sample_rate, at the highest frequency used
+        let limit = TWO_PI * 2000.0 / sample_rate as f64 * 1.01;
+        assert!(samples.windows(2).all(|w| ((w[1] - w[0]).abs() as f64) < limit));
     }
-}
 
-impl Source for Waveform {
-    fn current_frame_len(&self) -> Option<usize> { Some(1) }
-    fn channels(&self) -> u16 { 1 } // TODO - This should be a vector of channels
-    fn sample_rate(&self) -> u32 { self.sample_rate }
-    fn total_duration(&self) -> Option<Duration> { Some(Duration::from_millis(10)) }
-}
+    #[test]
+    fn test_selectable_type() {
+        let mut oscillator = Waveform::with_type(48_000, WaveformType::Sine);
+        oscillator.set_frequency(100.0);
+        oscillator.set_waveform_type(WaveformType::Square);
+        assert_eq!(oscillator.waveform_type(), Some(WaveformType::Square));
+
+        let samples: Vec<f32> = oscillator.take(480).collect();
+        let high = samples.iter().filter(|s| **s > 0.8).count();
+        let low = samples.iter().filter(|s| **s < -0.8).count();
+        assert!(high > 200 && low > 200, "{} {}", high, low);
 
-unsafe impl Sync for Waveform {}
\ No newline at end of file
+        let custom = Waveform::new(48_000, vec![0.0, 1.0, 0.0, -1.0]);
+        assert_eq!(custom.waveform_type(), None);
+    }
+}
@@ -18,21 +18,32 @@
 //! Core Types:
 //! * [Pitch](audiotheorem::types::Pitch) - Discrete [PitchClass](audiotheorem::types::PitchClass) at a given [Octave](audiotheorem::types::Octave) pinned to a tuned frequency.
 //! * [PitchClass](audiotheorem::types::PitchClass) - [Notes](audiotheorem::types::Note) reduced to the 12 discrete Semitones.
+//! * [PitchClassSet](audiotheorem::types::PitchClassSet) - Unordered set of [PitchClasses](audiotheorem::types::PitchClass) held as a bitmask.
 //! * [PitchGroup](audiotheorem::types::PitchGroup) - `Musical Key`s reduced to the 12 discrete [PitchClass](audiotheorem::types::PitchClass) Combinations.
 //! * [Octave](audiotheorem::types::Octave) - Pitch range for a given set of [Pitches](audiotheorem::types::Pitch).
 //! * [Cents](audiotheorem::types::Cents) - Logarithmic distance between two Pitches.
 //! * [Steps](audiotheorem::types::Steps) - Distance between two Pitches measured in Semitonal Steps.
+//! * [Resolution](audiotheorem::types::Resolution) - Division of the 144 Pitches into 144, 720, 1440 or 14400 bands.
+//! * [TuningSystem](audiotheorem::types::TuningSystem) - [Temperament](audiotheorem::types::Temperament) on a root pinned to a reference frequency.
 //!
 //! Music Theory Types:
 //! * [Tone](audiotheorem::types::Tone) - A [Note](audiotheorem::types::Note) at a given [Octave](audiotheorem::types::Octave).
 //! * [Note](audiotheorem::types::Note) - Various Names given to [PitchClasses](audiotheorem::types::PitchClass) in Musical Theory.
 //! * [Key]() - Unimplemented
+//! * [KeySignature](audiotheorem::types::KeySignature) - Sharps or flats of a major key and its relative minor, including theoretical keys.
+//!   * [Speller](audiotheorem::types::Speller) - Spells [Pitches](audiotheorem::types::Pitch) from the key, the local chord and the melodic direction.
 //! * [Octave](audiotheorem::types::Octave) - Range for a given set of [Tones](audiotheorem::types::Tone).
 //! * [Interval](audiotheorem::types::Interval) - Distance Between Two [Pitches](audiotheorem::types::Pitch)
+//!   * [CompoundInterval](audiotheorem::types::CompoundInterval) - [Interval](audiotheorem::types::Interval) of any size, ascending or descending, such as between two [Tones](audiotheorem::types::Tone).
 //!   * [PerfectQuality](audiotheorem::types::PerfectQuality) - Quality of First, Fourth, Fifth, and Seventh [Intervals](audiotheorem::types::Interval).
 //!   * [MajorQuality](audiotheorem::types::MajorQuality) - Quality of the Second, Third, and Sixth [Intervals](audiotheorem::types::Interval).
 //! * [Degree](audiotheorem::types::Degree)  - [Note](audiotheorem::types::Note) Positions in a [Scale](audiotheorem::types::Scale)
 //! * [Scale](audiotheorem::types::Scale) - In music theory, a scale is any set of musical notes ordered by fundamental frequency or pitch. A scale ordered by increasing pitch is an ascending scale, and a scale ordered by decreasing pitch is a descending scale.
+//!   * [ScaleSequence](audiotheorem::types::ScaleSequence) - Any of the interval sequences a [Scale](audiotheorem::types::Scale) is built from.
+//!   * [ScaleShape](audiotheorem::types::ScaleShape) - Any of the 2048 scales as a mask of semitones above the root, with its modes, complement and symmetry.
+//!   * [ScaleMatch](audiotheorem::types::ScaleMatch) - A [Scale](audiotheorem::types::Scale) found by searching for [Notes](audiotheorem::types::Note) or [PitchClasses](audiotheorem::types::PitchClass).
+//! * [SetClass](audiotheorem::types::SetClass) - Pitch-class set theory: prime forms, interval vectors and Forte numbers of collections of [PitchClasses](audiotheorem::types::PitchClass).
+//! * [ToneRow](audiotheorem::types::ToneRow) - Twelve-tone rows, their 48 [forms](audiotheorem::types::RowForm), matrix and combinatoriality.
 //!
 
 mod cents;
@@ -40,26 +51,36 @@ mod circle;
 mod degree;
 mod form;
 mod interval;
+mod keysignature;
 mod matrix;
 mod mode;
 mod note;
 mod octave;
 mod pitch;
 mod pitchclass;
+mod pitchclassset;
 mod pitchgroup;
 mod pitchmode;
+mod resolution;
 mod scale;
+mod scala;
+mod scaleshape;
+mod setclass;
+mod spelling;
 mod steps;
+mod temperament;
 mod tone;
+mod tonerow;
 mod dynamic;
 
 pub use self::cents::Cents;
 pub use self::circle::CircleOfFifths;
 pub use self::degree::Degree;
 pub use self::form::Form;
-pub use self::interval::Interval;
+pub use self::interval::{Interval, CompoundInterval};
 pub use self::interval::MajorQuality;
 pub use self::interval::PerfectQuality;
+pub use self::keysignature::KeySignature;
 pub use self::matrix::Matrix;
 pub use self::mode::Mode;
 pub use self::note::Accidental;
@@ -67,10 +88,18 @@ pub use self::note::Note;
 pub use self::octave::Octave;
 pub use self::pitch::{Pitch,Tuning};
 pub use self::pitchclass::PitchClass;
+pub use self::pitchclassset::PitchClassSet;
 pub use self::pitchgroup::PitchGroup;
-pub use self::pitchmode::PitchMode;
+pub use self::pitchmode::{ModeFamily, PitchMode};
+pub use self::resolution::Resolution;
 pub use self::scale::sequences;
-pub use self::scale::Scale;
+pub use self::scale::{Scale, ScaleMatch, ScaleSequence};
+pub use self::scaleshape::ScaleShape;
+pub use self::scala::{ScalaScale, ScalaPitch, KeyboardMapping, ScalaTuning};
+pub use self::setclass::SetClass;
+pub use self::spelling::Speller;
 pub use self::steps::Steps;
+pub use self::temperament::{Temperament, TuningSystem};
 pub use self::tone::Tone;
+pub use self::tonerow::{ToneRow, RowForm, RowTransform};
 pub use self::dynamic::Dynamic;
\ No newline at end of file
@@ -15,33 +15,96 @@
 //
 
 use super::Steps;
-use std::fmt;
+use std::{fmt, ops};
 
 /// Representation of Logarithmic pitch distance based on an equal tempered semitone (100 cents) and
-/// octave (1200 cents).
-#[derive(Copy, Clone, PartialOrd, PartialEq)]
-pub struct Cents(u16);
+/// octave (1200 cents). Cents are signed and fractional, so they can describe any direction and any
+/// microtonal detune.
+#[derive(Copy, Clone, Default, PartialOrd, PartialEq)]
+pub struct Cents(f64);
 
 impl Cents {
-    /// [Steps](audiotheorem::types::Steps) represented by this [Cents](audiotheorem::types::Cents).
+    /// One equal tempered octave.
+    pub const OCTAVE: Cents = Cents(1200.0);
+
+    /// [Cents](audiotheorem::types::Cents) of a frequency ratio - 3/2 is 701.955 cents.
+    pub fn from_ratio(ratio: f64) -> Cents {
+        Cents(1200.0 * ratio.log2())
+    }
+    /// [Cents](audiotheorem::types::Cents) from one frequency up to another - negative if `to` is lower.
+    pub fn between(from: f64, to: f64) -> Cents {
+        Cents::from_ratio(to / from)
+    }
+    /// Frequency ratio represented by this [Cents](audiotheorem::types::Cents).
+    pub fn ratio(&self) -> f64 {
+        2f64.powf(self.0 / 1200.0)
+    }
+    /// Nearest whole [Steps](audiotheorem::types::Steps) represented by this [Cents](audiotheorem::types::Cents).
     pub fn steps(&self) -> Steps {
-        Steps::from((self.0 as f64 / 100.0).round() as u16)
+        Steps::from(self.semitones().round() as i32)
+    }
+    /// Fractional semitones represented by this [Cents](audiotheorem::types::Cents).
+    pub fn semitones(&self) -> f64 {
+        self.0 / 100.0
+    }
+    /// What is left over after the nearest [Steps](audiotheorem::types::Steps), between -50 and 50 cents.
+    pub fn remainder(&self) -> Cents {
+        *self - self.steps().cents()
     }
     /// Get numeric value of [Cents](audiotheorem::types::Cents).
-    pub fn cents(&self) -> u16 {
+    pub fn cents(&self) -> f64 {
         self.0
     }
+    /// Absolute size of this [Cents](audiotheorem::types::Cents), ignoring direction.
+    pub fn abs(&self) -> Cents {
+        Cents(self.0.abs())
+    }
 }
 
-impl From<u16> for Cents {
-    fn from(value: u16) -> Cents {
+impl From<f64> for Cents {
+    fn from(value: f64) -> Cents {
         Cents(value)
     }
 }
 
 impl From<Steps> for Cents {
     fn from(value: Steps) -> Cents {
-        Cents(value.value() * 100)
+        value.cents()
+    }
+}
+
+impl ops::Add for Cents {
+    type Output = Cents;
+    fn add(self, other: Cents) -> Cents {
+        Cents(self.0 + other.0)
+    }
+}
+
+impl ops::Sub for Cents {
+    type Output = Cents;
+    fn sub(self, other: Cents) -> Cents {
+        Cents(self.0 - other.0)
+    }
+}
+
+impl ops::Neg for Cents {
+    type Output = Cents;
+    fn neg(self) -> Cents {
+        Cents(-self.0)
+    }
+}
+
+impl ops::Add<Steps> for Cents {
+    type Output = Cents;
+    fn add(self, steps: Steps) -> Cents {
+        self + steps.cents()
+    }
+}
+
+impl ops::Sub<Steps> for Cents {
+    type Output = Cents;
+    fn sub(self, steps: Steps) -> Cents {
+        self - steps.cents()
     }
 }
 
@@ -56,3 +119,36 @@ impl fmt::Debug for Cents {
         write!(f, "Cents(cents: {})", self.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ratios() {
+        assert!((Cents::from_ratio(1.5).cents() - 701.955).abs() < 0.001);
+        assert!((Cents::from_ratio(2.0).cents() - 1200.0).abs() < 1e-9);
+        assert!((Cents::between(440.0, 220.0).cents() + 1200.0).abs() < 1e-9);
+        assert!((Cents::from(386.3137).ratio() - 1.25).abs() < 1e-6);
+        assert!((Cents::from(-1200.0).ratio() - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let fifth = Cents::from(701.955);
+        let fourth = Cents::OCTAVE - fifth;
+        assert!((fourth.cents() - 498.045).abs() < 1e-9);
+        assert_eq!(-fourth, Cents::from(-fourth.cents()));
+        assert_eq!(fifth + Steps::from(-7), Cents::from(701.955 - 700.0));
+        assert!((fourth + fifth - Cents::OCTAVE).cents().abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_steps_and_remainder() {
+        assert_eq!(Cents::from(701.955).steps(), Steps::from(7));
+        assert!((Cents::from(701.955).remainder().cents() - 1.955).abs() < 1e-9);
+        assert_eq!(Cents::from(-386.3).steps(), Steps::from(-4));
+        assert!((Cents::from(-386.3).remainder().cents() - 13.7).abs() < 1e-9);
+        assert_eq!(Cents::from(1250.0).semitones(), 12.5);
+    }
+}
@@ -20,7 +20,7 @@ use std::fmt;
 use super::{Interval, MajorQuality, PerfectQuality};
 
 /// Degrees are a representation of particular notes in a sequence.
-#[derive(Copy, Clone, PartialOrd, PartialEq, Debug)]
+#[derive(Copy, Clone, PartialOrd, PartialEq, Eq, Hash, Debug)]
 pub enum Degree {
     Tonic(PerfectQuality),
     Supertonic(MajorQuality),
@@ -31,6 +31,21 @@ pub enum Degree {
     Subtonic(MajorQuality),
 }
 
+impl Degree {
+    /// Position in the scale, 0 for the tonic to 6 for the subtonic.
+    pub(crate) fn to_index(self) -> u8 {
+        match self {
+            Degree::Tonic(_) => 0,
+            Degree::Supertonic(_) => 1,
+            Degree::Mediant(_) => 2,
+            Degree::Subdominant(_) => 3,
+            Degree::Dominant(_) => 4,
+            Degree::Submediant(_) => 5,
+            Degree::Subtonic(_) => 6,
+        }
+    }
+}
+
 impl fmt::Display for Degree {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if f.alternate() {
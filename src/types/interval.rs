@@ -15,12 +15,13 @@
 //
 
 use super::Degree;
-use crate::types::{Cents, Note, Steps};
+use crate::types::{Cents, Note, Steps, Tone};
 use std::fmt;
+use std::str::FromStr;
 
 /// [PerfectQuality](audiotheorem::types::PerfectQuality) is the
 /// [Interval](audiotheorem::types::Interval) Quality of the First, Fourth, and Fifth.
-#[derive(Copy, Clone, PartialOrd, PartialEq, Debug)]
+#[derive(Copy, Clone, PartialOrd, PartialEq, Eq, Hash, Debug)]
 pub enum PerfectQuality {
     TripleDiminished,
     DoubleDiminished,
@@ -33,7 +34,7 @@ pub enum PerfectQuality {
 
 /// [MajorQuality](audiotheorem::types::MajorQuality) is the [Interval](audiotheorem::types::Interval)
 /// Quality of the Second, Third, Sixth, and Seventh.
-#[derive(Copy, Clone, PartialOrd, PartialEq, Debug)]
+#[derive(Copy, Clone, PartialOrd, PartialEq, Eq, Hash, Debug)]
 pub enum MajorQuality {
     TripleDiminished,
     DoubleDiminished,
@@ -93,7 +94,7 @@ impl fmt::Display for MajorQuality {
                 MajorQuality::Minor => write!(f, "m"),
                 MajorQuality::Augmented => write!(f, "A"),
                 MajorQuality::DoubleAugmented => write!(f, "AA"),
-                MajorQuality::TripleAugmented => write!(f, "AA"),
+                MajorQuality::TripleAugmented => write!(f, "AAA"),
             }
         }
     }
@@ -101,7 +102,7 @@ impl fmt::Display for MajorQuality {
 
 /// [Interval](audiotheorem::types::Interval) is the distance between two
 /// [PitchClasses](audiotheorem::types::PitchClass).
-#[derive(Copy, Clone, PartialOrd, PartialEq, Debug)]
+#[derive(Copy, Clone, PartialOrd, PartialEq, Eq, Hash, Debug)]
 pub enum Interval {
     First(PerfectQuality),
     Second(MajorQuality),
@@ -119,6 +120,9 @@ pub enum Interval {
     Fourteenth(MajorQuality),
 }
 
+// Semitones from C to each natural letter, and the major or perfect size of each simple interval number
+pub(crate) const LETTER_STEPS: [i32; 7] = [0, 2, 4, 5, 7, 9, 11];
+
 impl Interval {
     /// [Degrees](audiotheorem::types::Degree) are matched to
     /// [Interval](audiotheorem::types::Interval).
@@ -160,7 +164,7 @@ impl Interval {
                 PerfectQuality::DoubleAugmented => 14,
                 PerfectQuality::TripleAugmented => 15,
             } + base as i16;
-            Steps::from((steps % 12) as u16)
+            Steps::from((steps % 12) as i32)
         }
         fn mtou(base: u16, q: MajorQuality) -> Steps {
             let steps = match q {
@@ -173,7 +177,7 @@ impl Interval {
                 MajorQuality::DoubleAugmented => 14,
                 MajorQuality::TripleAugmented => 15,
             } + base as i16;
-            Steps::from((steps % 12) as u16)
+            Steps::from((steps % 12) as i32)
         }
         match *self {
             Interval::First(q) => ptou(0, q),
@@ -192,10 +196,383 @@ impl Interval {
             Interval::Fourteenth(q) => mtou(23, q),
         }
     }
-    /// Interval Mappings for Notes
-    #[rustfmt::skip]
+    /// Simple ascending [Interval](audiotheorem::types::Interval) from one
+    /// [Note](audiotheorem::types::Note) up to the next, counted from the letter names and the
+    /// semitones between them, or None when the quality would be beyond triple augmented or diminished.
     pub fn distance(l: Note, r: Note) -> Option<Interval> {
-        use super::{Accidental::*, Interval::*, Note::*};
+        let number = (r.letter() - l.letter()).rem_euclid(7);
+        let semitones = LETTER_STEPS[r.letter() as usize] - LETTER_STEPS[l.letter() as usize] + 12 * ((l.letter() > r.letter()) as i32)
+            + r.accidental().offset()
+            - l.accidental().offset();
+        Interval::from_parts(number as u8 + 1, semitones - LETTER_STEPS[number as usize])
+    }
+
+    /// Interval for a number from 1 to 14 and how many semitones it is above the perfect or major
+    /// interval of that number.
+    pub fn from_parts(number: u8, deviation: i32) -> Option<Interval> {
+        use self::Interval::*;
+        let perfect = match deviation {
+            -3 => Some(PerfectQuality::TripleDiminished),
+            -2 => Some(PerfectQuality::DoubleDiminished),
+            -1 => Some(PerfectQuality::Diminished),
+            0 => Some(PerfectQuality::Perfect),
+            1 => Some(PerfectQuality::Augmented),
+            2 => Some(PerfectQuality::DoubleAugmented),
+            3 => Some(PerfectQuality::TripleAugmented),
+            _ => None,
+        };
+        let major = match deviation {
+            -4 => Some(MajorQuality::TripleDiminished),
+            -3 => Some(MajorQuality::DoubleDiminished),
+            -2 => Some(MajorQuality::Diminished),
+            -1 => Some(MajorQuality::Minor),
+            0 => Some(MajorQuality::Major),
+            1 => Some(MajorQuality::Augmented),
+            2 => Some(MajorQuality::DoubleAugmented),
+            3 => Some(MajorQuality::TripleAugmented),
+            _ => None,
+        };
+        match number {
+            1 => perfect.map(First),
+            2 => major.map(Second),
+            3 => major.map(Third),
+            4 => perfect.map(Fourth),
+            5 => perfect.map(Fifth),
+            6 => major.map(Sixth),
+            7 => major.map(Seventh),
+            8 => perfect.map(Octave),
+            9 => major.map(Ninth),
+            10 => major.map(Tenth),
+            11 => perfect.map(Eleventh),
+            12 => perfect.map(Twelfth),
+            13 => major.map(Thirteenth),
+            14 => major.map(Fourteenth),
+            _ => None,
+        }
+    }
+
+    /// Interval number, 1 for a [First](Interval::First) to 14 for a [Fourteenth](Interval::Fourteenth).
+    pub fn number(&self) -> u8 {
+        match *self {
+            Interval::First(_) => 1,
+            Interval::Second(_) => 2,
+            Interval::Third(_) => 3,
+            Interval::Fourth(_) => 4,
+            Interval::Fifth(_) => 5,
+            Interval::Sixth(_) => 6,
+            Interval::Seventh(_) => 7,
+            Interval::Octave(_) => 8,
+            Interval::Ninth(_) => 9,
+            Interval::Tenth(_) => 10,
+            Interval::Eleventh(_) => 11,
+            Interval::Twelfth(_) => 12,
+            Interval::Thirteenth(_) => 13,
+            Interval::Fourteenth(_) => 14,
+        }
+    }
+
+    /// Semitones above the perfect or major interval of the same number.
+    pub fn deviation(&self) -> i32 {
+        fn perfect(q: PerfectQuality) -> i32 {
+            match q {
+                PerfectQuality::TripleDiminished => -3,
+                PerfectQuality::DoubleDiminished => -2,
+                PerfectQuality::Diminished => -1,
+                PerfectQuality::Perfect => 0,
+                PerfectQuality::Augmented => 1,
+                PerfectQuality::DoubleAugmented => 2,
+                PerfectQuality::TripleAugmented => 3,
+            }
+        }
+        fn major(q: MajorQuality) -> i32 {
+            match q {
+                MajorQuality::TripleDiminished => -4,
+                MajorQuality::DoubleDiminished => -3,
+                MajorQuality::Diminished => -2,
+                MajorQuality::Minor => -1,
+                MajorQuality::Major => 0,
+                MajorQuality::Augmented => 1,
+                MajorQuality::DoubleAugmented => 2,
+                MajorQuality::TripleAugmented => 3,
+            }
+        }
+        match *self {
+            Interval::First(q) | Interval::Fourth(q) | Interval::Fifth(q) | Interval::Octave(q)
+            | Interval::Eleventh(q) | Interval::Twelfth(q) => perfect(q),
+            Interval::Second(q) | Interval::Third(q) | Interval::Sixth(q) | Interval::Seventh(q)
+            | Interval::Ninth(q) | Interval::Tenth(q) | Interval::Thirteenth(q) | Interval::Fourteenth(q) => major(q),
+        }
+    }
+
+    /// Full size in semitones, including the octave for compound intervals - a major ninth is 14.
+    pub fn semitones(&self) -> i32 {
+        let number = self.number() as usize - 1;
+        LETTER_STEPS[number % 7] + 12 * (number / 7) as i32 + self.deviation()
+    }
+
+    /// Interval that completes this one to an octave - M3 and m6, P4 and P5, P1 and P8.
+    /// Compound intervals invert their simple part, so a major ninth gives a minor seventh.
+    pub fn inversion(&self) -> Interval {
+        let number = match self.number() {
+            1 => 8,
+            8 => 1,
+            n => 9 - ((n - 1) % 7 + 1),
+        };
+        // Major turns to minor, one semitone below, while perfect stays perfect
+        let deviation = match number {
+            1 | 4 | 5 | 8 => -self.deviation(),
+            _ => -1 - self.deviation(),
+        };
+        Interval::from_parts(number, deviation).expect("qualities invert symmetrically")
+    }
+
+    /// Get [Cents](audiotheorem::types::Cents) measurement for this
+    /// [Interval](audiotheorem::types::Interval).
+    pub fn cents(&self) -> Cents {
+        self.steps().cents()
+    }
+}
+
+/// Parse interval shorthand, e.g. "P5", "m3", "A4", "d7", "M13" or "AA6".
+impl FromStr for Interval {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Interval, Self::Err> {
+        let s = s.trim();
+        let split = s.find(|c: char| c.is_ascii_digit()).ok_or("Missing interval number")?;
+        let (quality, number) = s.split_at(split);
+        let number: u8 = number.parse().map_err(|_| "Invalid interval number")?;
+        let perfect = match quality {
+            "ddd" => Some(PerfectQuality::TripleDiminished),
+            "dd" => Some(PerfectQuality::DoubleDiminished),
+            "d" => Some(PerfectQuality::Diminished),
+            "P" => Some(PerfectQuality::Perfect),
+            "A" => Some(PerfectQuality::Augmented),
+            "AA" => Some(PerfectQuality::DoubleAugmented),
+            "AAA" => Some(PerfectQuality::TripleAugmented),
+            _ => None,
+        };
+        let major = match quality {
+            "ddd" => Some(MajorQuality::TripleDiminished),
+            "dd" => Some(MajorQuality::DoubleDiminished),
+            "d" => Some(MajorQuality::Diminished),
+            "m" => Some(MajorQuality::Minor),
+            "M" => Some(MajorQuality::Major),
+            "A" => Some(MajorQuality::Augmented),
+            "AA" => Some(MajorQuality::DoubleAugmented),
+            "AAA" => Some(MajorQuality::TripleAugmented),
+            _ => None,
+        };
+        let perfect = || perfect.ok_or("Invalid quality for a perfect interval");
+        let major = || major.ok_or("Invalid quality for a major interval");
+        Ok(match number {
+            1 => Interval::First(perfect()?),
+            2 => Interval::Second(major()?),
+            3 => Interval::Third(major()?),
+            4 => Interval::Fourth(perfect()?),
+            5 => Interval::Fifth(perfect()?),
+            6 => Interval::Sixth(major()?),
+            7 => Interval::Seventh(major()?),
+            8 => Interval::Octave(perfect()?),
+            9 => Interval::Ninth(major()?),
+            10 => Interval::Tenth(major()?),
+            11 => Interval::Eleventh(perfect()?),
+            12 => Interval::Twelfth(perfect()?),
+            13 => Interval::Thirteenth(major()?),
+            14 => Interval::Fourteenth(major()?),
+            _ => return Err("Interval number out of range"),
+        })
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            match *self {
+                Interval::First(q) => write!(f, "{:#} First", q),
+                Interval::Second(q) => write!(f, "{:#} Second", q),
+                Interval::Third(q) => write!(f, "{:#} Third", q),
+                Interval::Fourth(q) => write!(f, "{:#} Fourth", q),
+                Interval::Fifth(q) => write!(f, "{:#} Fifth", q),
+                Interval::Sixth(q) => write!(f, "{:#} Sixth", q),
+                Interval::Seventh(q) => write!(f, "{:#} Seventh", q),
+                Interval::Octave(q) => write!(f, "{:#} Eighth", q),
+                Interval::Ninth(q) => write!(f, "{:#} Ninth", q),
+                Interval::Tenth(q) => write!(f, "{:#} Tenth", q),
+                Interval::Eleventh(q) => write!(f, "{:#} Eleventh", q),
+                Interval::Twelfth(q) => write!(f, "{:#} Twelfth", q),
+                Interval::Thirteenth(q) => write!(f, "{:#} Thirteenth", q),
+                Interval::Fourteenth(q) => write!(f, "{:#} Fourteenth", q),
+            }
+        } else {
+            match *self {
+                Interval::First(q) => write!(f, "{}1", q),
+                Interval::Second(q) => write!(f, "{}2", q),
+                Interval::Third(q) => write!(f, "{}3", q),
+                Interval::Fourth(q) => write!(f, "{}4", q),
+                Interval::Fifth(q) => write!(f, "{}5", q),
+                Interval::Sixth(q) => write!(f, "{}6", q),
+                Interval::Seventh(q) => write!(f, "{}7", q),
+                Interval::Octave(q) => write!(f, "{}8", q),
+                Interval::Ninth(q) => write!(f, "{}9", q),
+                Interval::Tenth(q) => write!(f, "{}10", q),
+                Interval::Eleventh(q) => write!(f, "{}11", q),
+                Interval::Twelfth(q) => write!(f, "{}12", q),
+                Interval::Thirteenth(q) => write!(f, "{}13", q),
+                Interval::Fourteenth(q) => write!(f, "{}14", q),
+            }
+        }
+    }
+}
+
+/// [CompoundInterval](audiotheorem::types::CompoundInterval) is an
+/// [Interval](audiotheorem::types::Interval) of any size, up or down - a simple interval from the
+/// First to the Seventh plus whole octaves.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct CompoundInterval {
+    /// First to Seventh
+    pub simple: Interval,
+    pub octaves: u8,
+    pub descending: bool,
+}
+
+impl CompoundInterval {
+    /// Interval for a number (1 for a unison, 8 for an octave, 15 for two octaves) and how many
+    /// semitones it is above the perfect or major interval of that number.
+    pub fn new(number: u32, deviation: i32, descending: bool) -> Option<CompoundInterval> {
+        if number == 0 {
+            return None;
+        }
+        let simple = Interval::from_parts(((number - 1) % 7 + 1) as u8, deviation)?;
+        let octaves = u8::try_from((number - 1) / 7).ok()?;
+        Some(CompoundInterval { simple, octaves, descending })
+    }
+
+    /// Interval from one [Tone](audiotheorem::types::Tone) to another, counted from the letter
+    /// names and octaves. A unison that goes down, such as C4 to Cb4, is a descending augmented unison.
+    pub fn between(from: Tone, to: Tone) -> Option<CompoundInterval> {
+        let letters = |t: Tone| 7 * t.octave().to_index() as i32 + t.note().letter();
+        let (mut number, mut steps) = (letters(to) - letters(from), to.height() - from.height());
+        let descending = number < 0 || (number == 0 && steps < 0);
+        if descending {
+            number = -number;
+            steps = -steps;
+        }
+        let size = LETTER_STEPS[(number % 7) as usize] + 12 * (number / 7);
+        CompoundInterval::new(number as u32 + 1, steps - size, descending)
+    }
+
+    /// Interval number, 9 for a ninth or 15 for a double octave.
+    pub fn number(&self) -> u32 { self.simple.number() as u32 + 7 * self.octaves as u32 }
+
+    /// Size in semitones, negative when descending.
+    pub fn semitones(&self) -> i32 {
+        let semitones = self.simple.semitones() + 12 * self.octaves as i32;
+        if self.descending { -semitones } else { semitones }
+    }
+
+    /// [Degree](audiotheorem::types::Degree) of the simple part and the octaves above it, so a
+    /// major ninth is the Supertonic one octave up.
+    pub fn degree(&self) -> (Degree, u8) { (self.simple.degree(), self.octaves) }
+
+    /// Get [Steps](audiotheorem::types::Steps) measurement, negative when descending.
+    pub fn steps(&self) -> Steps { Steps::from(self.semitones()) }
+
+    /// Get [Cents](audiotheorem::types::Cents) measurement, negative when descending.
+    pub fn cents(&self) -> Cents { self.steps().cents() }
+
+    /// The same [Interval](audiotheorem::types::Interval) without direction, if it is no wider than
+    /// a Fourteenth.
+    pub fn interval(&self) -> Option<Interval> {
+        Interval::from_parts(u8::try_from(self.number()).ok()?, self.simple.deviation())
+    }
+
+    /// Inversion of the simple part, keeping the direction - a major ninth gives a minor seventh.
+    pub fn inversion(&self) -> CompoundInterval {
+        CompoundInterval { descending: self.descending, ..CompoundInterval::from(self.simple.inversion()) }
+    }
+}
+
+impl From<Interval> for CompoundInterval {
+    fn from(interval: Interval) -> CompoundInterval {
+        CompoundInterval::new(interval.number() as u32, interval.deviation(), false).expect("every Interval is a CompoundInterval")
+    }
+}
+
+impl std::ops::Neg for CompoundInterval {
+    type Output = CompoundInterval;
+    fn neg(self) -> CompoundInterval { CompoundInterval { descending: !self.descending, ..self } }
+}
+
+/// Parse interval shorthand of any size, e.g. "M9", "P15", "AA23" or "-m3" going down.
+impl FromStr for CompoundInterval {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<CompoundInterval, Self::Err> {
+        let s = s.trim();
+        let (descending, s) = match s.strip_prefix('-') {
+            Some(s) => (true, s),
+            None => (false, s),
+        };
+        let split = s.find(|c: char| c.is_ascii_digit()).ok_or("Missing interval number")?;
+        let (quality, number) = s.split_at(split);
+        let number: u32 = number.parse().map_err(|_| "Invalid interval number")?;
+        if number == 0 {
+            return Err("Interval number out of range");
+        }
+        let simple: Interval = format!("{}{}", quality, (number - 1) % 7 + 1).parse()?;
+        CompoundInterval::new(number, simple.deviation(), descending).ok_or("Interval number out of range")
+    }
+}
+
+impl fmt::Display for CompoundInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let quality = self.simple.to_string();
+        let quality = quality.trim_end_matches(|c: char| c.is_ascii_digit());
+        write!(f, "{}{}{}", if self.descending { "-" } else { "" }, quality, self.number())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::interval::{MajorQuality::*, PerfectQuality::*};
+    use crate::types::{
+        Accidental, Accidental::*, CompoundInterval, Interval, Interval::*, MajorQuality, Note,
+        Note::*, PerfectQuality, PitchClass, Steps, Tone,
+    };
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("P5".parse::<Interval>(), Ok(Fifth(Perfect)));
+        assert_eq!("m3".parse::<Interval>(), Ok(Third(Minor)));
+        assert_eq!("A4".parse::<Interval>(), Ok(Fourth(PerfectQuality::Augmented)));
+        assert_eq!("d7".parse::<Interval>(), Ok(Seventh(MajorQuality::Diminished)));
+        assert_eq!("M13".parse::<Interval>(), Ok(Thirteenth(Major)));
+        assert_eq!("P8".parse::<Interval>(), Ok(Octave(Perfect)));
+        assert!("M5".parse::<Interval>().is_err());
+        assert!("P3".parse::<Interval>().is_err());
+        assert!("M15".parse::<Interval>().is_err());
+        assert!("5".parse::<Interval>().is_err());
+        assert!("P".parse::<Interval>().is_err());
+
+        use PerfectQuality as P;
+        use MajorQuality as M;
+        let perfect = [P::TripleDiminished, P::DoubleDiminished, P::Diminished, P::Perfect, P::Augmented, P::DoubleAugmented, P::TripleAugmented];
+        let major = [M::TripleDiminished, M::DoubleDiminished, M::Diminished, M::Minor, M::Major, M::Augmented, M::DoubleAugmented, M::TripleAugmented];
+        let mut intervals = Vec::new();
+        for q in perfect {
+            intervals.extend([First(q), Fourth(q), Fifth(q), Octave(q), Eleventh(q), Twelfth(q)]);
+        }
+        for q in major {
+            intervals.extend([Second(q), Third(q), Sixth(q), Seventh(q), Ninth(q), Tenth(q), Thirteenth(q), Fourteenth(q)]);
+        }
+        for interval in intervals {
+            assert_eq!(interval.to_string().parse::<Interval>(), Ok(interval));
+        }
+    }
+
+    // The hand-written table Interval::distance used to be, kept as the reference it must agree with
+    #[rustfmt::skip]
+    fn table(l: Note, r: Note) -> Option<Interval> {
+        use crate::types::{Accidental::*, Interval::*, Note::*};
         match (l, r) {
 
             // Note C(_)
@@ -1077,60 +1454,84 @@ impl Interval {
         }
     }
 
-    /// Get [Cents](audiotheorem::types::Cents) measurement for this
-    /// [Interval](audiotheorem::types::Interval).
-    pub fn cents(&self) -> Cents {
-        self.steps().cents()
+    #[test]
+    fn test_computed_distance() {
+        assert_eq!(Interval::distance(C(Natural), C(Flat)), Some(First(PerfectQuality::Diminished)));
+        assert_eq!(Interval::distance(B(Natural), C(Natural)), Some(Second(Minor)));
+        assert_eq!(Interval::distance(E(Sharp), F(Flat)), Some(Second(MajorQuality::DoubleDiminished)));
+        assert_eq!(Interval::distance(C(Flat), B(Sharp)), Some(Seventh(MajorQuality::DoubleAugmented)));
+        assert_eq!(Interval::distance(F(Flat), B(Sharp)), Some(Fourth(PerfectQuality::TripleAugmented)));
+        assert_eq!(Interval::distance(F(DoubleFlat), B(DoubleSharp)), None);
+        assert_eq!(Interval::distance(D(Sharp), C(Flat)), Some(Seventh(MajorQuality::DoubleDiminished)));
+        assert_eq!(Interval::distance(D(DoubleSharp), C(Flat)), Some(Seventh(MajorQuality::TripleDiminished)));
     }
-}
 
-impl fmt::Display for Interval {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if f.alternate() {
-            match *self {
-                Interval::First(q) => write!(f, "{:#} First", q),
-                Interval::Second(q) => write!(f, "{:#} Second", q),
-                Interval::Third(q) => write!(f, "{:#} Third", q),
-                Interval::Fourth(q) => write!(f, "{:#} Fourth", q),
-                Interval::Fifth(q) => write!(f, "{:#} Fifth", q),
-                Interval::Sixth(q) => write!(f, "{:#} Sixth", q),
-                Interval::Seventh(q) => write!(f, "{:#} Seventh", q),
-                Interval::Octave(q) => write!(f, "{:#} Eighth", q),
-                Interval::Ninth(q) => write!(f, "{:#} Ninth", q),
-                Interval::Tenth(q) => write!(f, "{:#} Tenth", q),
-                Interval::Eleventh(q) => write!(f, "{:#} Eleventh", q),
-                Interval::Twelfth(q) => write!(f, "{:#} Twelfth", q),
-                Interval::Thirteenth(q) => write!(f, "{:#} Thirteenth", q),
-                Interval::Fourteenth(q) => write!(f, "{:#} Fourteenth", q),
-            }
-        } else {
-            match *self {
-                Interval::First(q) => write!(f, "{}1", q),
-                Interval::Second(q) => write!(f, "{}2", q),
-                Interval::Third(q) => write!(f, "{}3", q),
-                Interval::Fourth(q) => write!(f, "{}4", q),
-                Interval::Fifth(q) => write!(f, "{}5", q),
-                Interval::Sixth(q) => write!(f, "{}6", q),
-                Interval::Seventh(q) => write!(f, "{}7", q),
-                Interval::Octave(q) => write!(f, "{}8", q),
-                Interval::Ninth(q) => write!(f, "{}9", q),
-                Interval::Tenth(q) => write!(f, "{}10", q),
-                Interval::Eleventh(q) => write!(f, "{}11", q),
-                Interval::Twelfth(q) => write!(f, "{}12", q),
-                Interval::Thirteenth(q) => write!(f, "{}13", q),
-                Interval::Fourteenth(q) => write!(f, "{}14", q),
-            }
+    #[test]
+    fn test_semitones_and_inversion() {
+        assert_eq!(Ninth(Major).semitones(), 14);
+        assert_eq!(Fourteenth(Minor).semitones(), 22);
+        assert_eq!(First(PerfectQuality::Diminished).semitones(), -1);
+        assert_eq!(Third(Major).inversion(), Sixth(Minor));
+        assert_eq!(Fourth(PerfectQuality::Augmented).inversion(), Fifth(PerfectQuality::Diminished));
+        assert_eq!(First(Perfect).inversion(), Octave(Perfect));
+        assert_eq!(Octave(Perfect).inversion(), First(Perfect));
+        assert_eq!(Ninth(Major).inversion(), Seventh(Minor));
+        assert_eq!(Seventh(MajorQuality::TripleAugmented).inversion(), Second(MajorQuality::TripleDiminished));
+        for interval in [Second(Minor), Fifth(PerfectQuality::DoubleAugmented), Sixth(MajorQuality::Diminished)] {
+            assert_eq!(interval.inversion().inversion(), interval);
+            assert_eq!(interval.semitones() + interval.inversion().semitones(), 12);
         }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::types::interval::{MajorQuality::*, PerfectQuality::*};
-    use crate::types::{
-        Accidental, Accidental::*, Interval, Interval::*, MajorQuality, Note,
-        Note::*, PerfectQuality, PitchClass, Steps,
-    };
+    #[test]
+    fn test_compound_intervals() {
+        let tone = |s: &str| s.parse::<Tone>().unwrap();
+        let between = |a: &str, b: &str| CompoundInterval::between(tone(a), tone(b)).map(|i| i.to_string());
+        assert_eq!(between("C4", "D5").as_deref(), Some("M9"));
+        assert_eq!(between("C4", "C6").as_deref(), Some("P15"));
+        assert_eq!(between("E4", "G2").as_deref(), Some("-M13"));
+        assert_eq!(between("C4", "Cb4").as_deref(), Some("-A1"));
+        assert_eq!(between("B3", "C4").as_deref(), Some("m2"));
+        assert_eq!(between("G4", "F#4").as_deref(), Some("-m2"));
+        assert_eq!(between("Cb4", "B#7").as_deref(), Some("AA28"));
+
+        let ninth = CompoundInterval::between(tone("C4"), tone("D5")).unwrap();
+        assert_eq!(ninth.semitones(), 14);
+        assert_eq!(ninth.interval(), Some(Ninth(Major)));
+        assert_eq!(ninth.inversion().to_string(), "m7");
+        assert_eq!((-ninth).steps(), Steps::from(-14));
+        assert_eq!(CompoundInterval::from(Tenth(Minor)).to_string(), "m10");
+        assert_eq!("P22".parse::<CompoundInterval>().unwrap().interval(), None);
+
+        for s in ["M9", "P15", "AA23", "-m3", "d8", "-P1"] {
+            assert_eq!(s.parse::<CompoundInterval>().unwrap().to_string(), s);
+        }
+        assert!("M15".parse::<CompoundInterval>().is_err());
+        assert!("P0".parse::<CompoundInterval>().is_err());
+    }
+
+    #[test]
+    fn test_table_equivalence() {
+        // Cbb up to Bb is an augmented seventh, the table had it as a sixth
+        let errata = [(C(DoubleFlat), B(Flat), Seventh(MajorQuality::Augmented))];
+        let mut covered = 0;
+        for l in Note::all() {
+            for r in Note::all() {
+                let computed = Interval::distance(l, r);
+                match table(l, r) {
+                    Some(expected) => match errata.iter().find(|(a, b, _)| (*a, *b) == (l, r)) {
+                        Some((_, _, fixed)) => assert_eq!(computed, Some(*fixed)),
+                        None => assert_eq!(computed, Some(expected), "Interval::distance({}, {})", l, r),
+                    },
+                    None => covered += computed.is_some() as usize,
+                }
+                if let Some(interval) = computed {
+                    assert_eq!(interval.steps(), Steps::distance(&l.pitch_class(), &r.pitch_class()), "{} {} {}", l, r, interval);
+                }
+            }
+        }
+        assert!(covered > 0);
+    }
 
     #[test]
     #[rustfmt::skip]
@@ -0,0 +1,210 @@
+//
+// Copyright 2024 Hans W. Uhlig, Richard I. Christopher. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+
+use super::{Accidental, Note, PitchClass, PitchGroup};
+use std::fmt;
+use std::str::FromStr;
+
+// Beyond fourteen a signature would need triple accidentals
+const LIMIT: i8 = 14;
+
+/// [KeySignature](audiotheorem::types::KeySignature) is the count of sharps or flats written at the
+/// start of a staff, shared by a major key and its relative minor.
+///
+/// Theoretical keys with more than seven accidentals, such as G# major with eight sharps (F##), are
+/// kept rather than folded onto their enharmonic, up to fourteen sharps or flats.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct KeySignature {
+    // Sharps are positive and flats negative, which is also where the major tonic sits on the line of fifths
+    accidentals: i8,
+}
+
+impl KeySignature {
+    /// Signature with a number of sharps (positive) or flats (negative).
+    pub fn new(accidentals: i8) -> Option<KeySignature> {
+        (-LIMIT..=LIMIT).contains(&accidentals).then_some(KeySignature { accidentals })
+    }
+    /// Signature of the major key on a [Note](audiotheorem::types::Note).
+    pub fn from_major(tonic: &Note) -> Option<KeySignature> {
+        i8::try_from(tonic.fifths()).ok().and_then(KeySignature::new)
+    }
+    /// Signature of the minor key on a [Note](audiotheorem::types::Note).
+    pub fn from_minor(tonic: &Note) -> Option<KeySignature> {
+        i8::try_from(tonic.fifths() - 3).ok().and_then(KeySignature::new)
+    }
+
+    /// Sharps as positive, flats as negative.
+    pub fn accidentals(&self) -> i8 { self.accidentals }
+    pub fn sharps(&self) -> u8 { self.accidentals.max(0) as u8 }
+    pub fn flats(&self) -> u8 { (-self.accidentals).max(0) as u8 }
+    /// True past seven sharps or flats, where double accidentals appear.
+    pub fn is_theoretical(&self) -> bool { self.accidentals.abs() > 7 }
+
+    /// Tonic of the major key.
+    pub fn major(&self) -> Note { self.note(0) }
+    /// Tonic of the relative minor key.
+    pub fn minor(&self) -> Note { self.note(3) }
+
+    /// The other spelling of the same [PitchGroup](audiotheorem::types::PitchGroup), e.g. five flats
+    /// for seven sharps, or nine flats for three sharps.
+    pub fn enharmonic(&self) -> Option<KeySignature> {
+        match self.accidentals {
+            0 => None,
+            n if n > 0 => KeySignature::new(n - 12),
+            n => KeySignature::new(n + 12),
+        }
+    }
+
+    /// [PitchGroup](audiotheorem::types::PitchGroup) holding the same pitch classes.
+    pub fn pitch_group(&self) -> PitchGroup {
+        let tonic = self.major().pitch_class();
+        PitchGroup::all().into_iter().find(|pg| pg.pitch_class() == tonic).unwrap()
+    }
+
+    /// The seven [Notes](audiotheorem::types::Note) of the major scale from its tonic.
+    pub fn notes(&self) -> [Note; 7] {
+        [0, 2, 4, -1, 1, 3, 5].map(|offset| self.note(offset))
+    }
+
+    /// The sharps or flats of the signature in the order they are written, along the line of fifths.
+    pub fn altered(&self) -> Vec<Note> {
+        let mut altered: Vec<Note> = self.notes().into_iter().filter(|n| n.accidental() != Accidental::Natural).collect();
+        altered.sort_by_key(|n| if self.accidentals < 0 { -n.fifths() } else { n.fifths() });
+        altered
+    }
+
+    /// True if the [Note](audiotheorem::types::Note) is one of the seven the signature spells.
+    pub fn contains(&self, note: &Note) -> bool {
+        let accidentals = self.accidentals as i32;
+        (accidentals - 1..=accidentals + 5).contains(&note.fifths())
+    }
+
+    /// Spelling of a [PitchClass](audiotheorem::types::PitchClass) in the key alone - the key's own note
+    /// when diatonic, otherwise the spelling closest to the key on the line of fifths, with double and
+    /// triple accidentals counted as four and eight fifths further away. Ties go to the fewer accidentals and then to the
+    /// sharp, so no signature gives G# rather than Ab and four sharps give C rather than B#.
+    pub fn spell(&self, pc: &PitchClass) -> Note {
+        let center = self.accidentals as i32 + 2;
+        let nearest = center + (7 * pc.to_index() as i32 - center + 6).rem_euclid(12) - 6;
+        [nearest - 12, nearest, nearest + 12].into_iter()
+            .filter_map(Note::from_fifths)
+            .min_by_key(|note| {
+                let offset = note.accidental().offset().abs();
+                let distance = (note.fifths() - center).abs() + 4 * (offset - 1).max(0);
+                (distance, offset, -note.fifths())
+            })
+            .unwrap_or_else(|| pc.names()[0])
+    }
+
+    // Note a number of fifths above the major tonic
+    fn note(&self, offset: i32) -> Note {
+        Note::from_fifths(self.accidentals as i32 + offset).expect("signature is within the line of fifths")
+    }
+}
+
+impl From<PitchGroup> for KeySignature {
+    /// Signature of the group's major key, from six sharps for F# to five flats for Db.
+    fn from(pg: PitchGroup) -> KeySignature {
+        KeySignature::from_major(&pg.major_key()).unwrap()
+    }
+}
+
+/// Written as a count, e.g. "3#", "4b" or "0".
+impl fmt::Display for KeySignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.accidentals {
+            0 => write!(f, "0"),
+            n if n > 0 => write!(f, "{}#", n),
+            n => write!(f, "{}b", -n),
+        }
+    }
+}
+
+/// Parse a count of sharps or flats, e.g. "3#", "4b" or "0".
+impl FromStr for KeySignature {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<KeySignature, Self::Err> {
+        let s = s.trim();
+        let (count, sign) = match s.chars().last() {
+            Some('#') => (&s[..s.len() - 1], 1),
+            Some('b') => (&s[..s.len() - 1], -1),
+            _ => (s, 1),
+        };
+        let count: i8 = count.parse().map_err(|_| "Invalid Key Signature")?;
+        if count < 0 { return Err("Invalid Key Signature"); }
+        KeySignature::new(sign * count).ok_or("Key Signature out of range")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KeySignature;
+    use crate::types::{Note, PitchClass, PitchGroup};
+
+    fn notes(names: &str) -> Vec<Note> { names.split_whitespace().map(|n| n.parse().unwrap()).collect() }
+
+    #[test]
+    fn test_tonics() {
+        let tonics = |n: i8| { let key = KeySignature::new(n).unwrap(); (key.major().to_string(), key.minor().to_string()) };
+        assert_eq!(tonics(0), ("C".to_string(), "A".to_string()));
+        assert_eq!(tonics(3), ("A".to_string(), "F#".to_string()));
+        assert_eq!(tonics(-6), ("Gb".to_string(), "Eb".to_string()));
+        assert_eq!(tonics(8), ("G#".to_string(), "E#".to_string()));
+        assert_eq!(tonics(-8), ("Fb".to_string(), "Db".to_string()));
+        assert_eq!(KeySignature::new(15), None);
+        assert_eq!(KeySignature::from_minor(&"Eb".parse().unwrap()), KeySignature::new(-6));
+        assert_eq!(KeySignature::from_major(&"D#".parse().unwrap()), KeySignature::new(9));
+        assert_eq!(Some(KeySignature::from(PitchGroup::Cs)), KeySignature::new(-5));
+        assert_eq!(KeySignature::new(-5).unwrap().pitch_group(), PitchGroup::Cs);
+        assert_eq!(KeySignature::new(7).unwrap().enharmonic(), KeySignature::new(-5));
+        assert!(KeySignature::new(-9).unwrap().is_theoretical());
+    }
+
+    #[test]
+    fn test_notes() {
+        assert_eq!(KeySignature::new(-6).unwrap().notes().to_vec(), notes("Gb Ab Bb Cb Db Eb F"));
+        assert_eq!(KeySignature::new(8).unwrap().notes().to_vec(), notes("G# A# B# C# D# E# F##"));
+        assert_eq!(KeySignature::new(-3).unwrap().altered(), notes("Bb Eb Ab"));
+        assert_eq!(KeySignature::new(9).unwrap().altered(), notes("G# D# A# E# B# F## C##"));
+        assert!(KeySignature::new(2).unwrap().contains(&"C#".parse().unwrap()));
+        assert!(!KeySignature::new(2).unwrap().contains(&"Db".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_spell() {
+        let spell = |n: i8| -> Vec<String> {
+            let key = KeySignature::new(n).unwrap();
+            (0..12).map(|i| key.spell(&PitchClass::from_index(i)).to_string()).collect()
+        };
+        assert_eq!(spell(0).join(" "), "C C# D Eb E F F# G G# A Bb B");
+        assert_eq!(spell(-6).join(" "), "C Db D Eb Fb F Gb G Ab A Bb Cb");
+        assert_eq!(spell(4).join(" "), "C C# D D# E E# F# G G# A A# B");
+        assert_eq!(spell(-12).join(" "), "Dbb Db Ebb Eb Fb Gbb Gb Abb Ab Bbb Bb Cb");
+    }
+
+    #[test]
+    fn test_parse() {
+        for n in -14..=14 {
+            let key = KeySignature::new(n).unwrap();
+            assert_eq!(key.to_string().parse::<KeySignature>(), Ok(key));
+        }
+        assert_eq!("4#".parse::<KeySignature>().unwrap().major(), "E".parse().unwrap());
+        assert!("15b".parse::<KeySignature>().is_err());
+        assert!("-3#".parse::<KeySignature>().is_err());
+    }
+}
@@ -14,7 +14,7 @@
 // limitations under the License.
 //
 
-use super::{Degree, Interval, Note, PerfectQuality, MajorQuality, PitchClass, PitchGroup};
+use super::{CompoundInterval, Degree, Interval, Note, PerfectQuality, MajorQuality, Pitch, PitchClass, PitchGroup, Tone};
 
 /// The Pitch Matrix is a set of static Reference Tables containing Ordered
 /// [Note](audiotheorem::types::Note) [Accidentals](audiotheorem::types::Accidental),
@@ -63,7 +63,7 @@ impl Matrix {
     /// | As | M2 |    | M3 | P4 |    | P5 |    | M6 |    | M7 | P1 |    |
     /// | Fn | P5 |    | M6 |    | M7 | P1 |    | M2 |    | M3 | P4 |    |
     ///
-    pub fn interval(pc: &PitchClass, pg: &PitchGroup) -> Option<Interval> { // TODO: Extent this to include 2 octave intervals using a "ladder" system
+    pub fn interval(pc: &PitchClass, pg: &PitchGroup) -> Option<Interval> { // See Matrix::ladder for intervals past the octave
         use crate::types::{Interval::*, MajorQuality::*, PerfectQuality::*};
         match (pc, pg) {
             // Column 0                                  we can sort by |  index   |  group  |  steps
@@ -178,7 +178,7 @@ impl Matrix {
             (PitchClass::Cn, PitchGroup::Cs) => Some(Subtonic(Major)), // Row 7
             (PitchClass::Cn, PitchGroup::Gs) => Some(Mediant(Major)), // Row 8
             (PitchClass::Cn, PitchGroup::Ds) => Some(Submediant(Major)), // Row 9
-            (PitchClass::Cn, PitchGroup::As) => Some(Subtonic(Major)), // Row 10
+            (PitchClass::Cn, PitchGroup::As) => Some(Supertonic(Major)), // Row 10
             (PitchClass::Cn, PitchGroup::Fn) => Some(Dominant(Perfect)), // Row 11
             // Column 1
             (PitchClass::Cs, PitchGroup::Dn) => Some(Subtonic(Major)), // Row 2
@@ -638,11 +638,88 @@ impl Matrix {
             (_, _) => None,
         }
     }
+
+    /// [Tone](audiotheorem::types::Tone) of a [Pitch](audiotheorem::types::Pitch) spelled as it is in the
+    /// [PitchGroup](audiotheorem::types::PitchGroup), with the octave following the letter so E# in the
+    /// F# group sits in the octave of its E.
+    pub fn spell(pitch: &Pitch, pg: &PitchGroup) -> Option<Tone> {
+        Tone::spelled(pitch, Matrix::natural(&pitch.pitch_class(), pg)?)
+    }
+
+    /// The "ladder" [CompoundInterval](audiotheorem::types::CompoundInterval) from a root
+    /// [Tone](audiotheorem::types::Tone) to a [Pitch](audiotheorem::types::Pitch) across any number of
+    /// octaves, both spelled in the [PitchGroup](audiotheorem::types::PitchGroup), so the 9ths, 11ths
+    /// and 13ths of extended chords keep their names. None if either falls outside the group.
+    pub fn ladder(pitch: &Pitch, root: &Tone, pg: &PitchGroup) -> Option<CompoundInterval> {
+        let root = Matrix::spell(&Matrix::pitch(root)?, pg)?;
+        CompoundInterval::between(root, Matrix::spell(pitch, pg)?)
+    }
+
+    /// [Matrix::ladder] for a [Tone](audiotheorem::types::Tone), respelled in the
+    /// [PitchGroup](audiotheorem::types::PitchGroup).
+    pub fn ladder_tone(tone: &Tone, root: &Tone, pg: &PitchGroup) -> Option<CompoundInterval> {
+        Matrix::ladder(&Matrix::pitch(tone)?, root, pg)
+    }
+
+    /// Compound [Degree](audiotheorem::types::Degree) of a [Pitch](audiotheorem::types::Pitch) above a
+    /// root [Tone](audiotheorem::types::Tone) and the octaves between them, so a 9th is the Supertonic
+    /// one octave up. None below the root.
+    pub fn ladder_degree(pitch: &Pitch, root: &Tone, pg: &PitchGroup) -> Option<(Degree, u8)> {
+        Matrix::ladder(pitch, root, pg).filter(|interval| !interval.descending).map(|interval| interval.degree())
+    }
+
+    // Sounding pitch of a tone, letting B#4 and Cb4 cross the octave
+    fn pitch(tone: &Tone) -> Option<Pitch> {
+        u8::try_from(tone.height()).ok().filter(|index| *index < 144).map(Pitch::from_index)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Matrix, PitchClass, PitchGroup};
+    use super::{Degree, Matrix, Pitch, PitchClass, PitchGroup, Tone};
+    use crate::types::{MajorQuality, PerfectQuality};
+
+    #[test]
+    fn test_degree_positions() {
+        for pg in PitchGroup::all() {
+            for (step, pc) in pg.ionian().notes().iter().enumerate() {
+                assert_eq!(Matrix::degree(pc, &pg).map(|d| d.to_index()), Some(step as u8), "{:?} in {:?}", pc, pg);
+            }
+        }
+    }
+
+    fn ladder(pitch: &str, root: &str, pg: PitchGroup) -> Option<String> {
+        let pitch: Pitch = pitch.parse().unwrap();
+        Matrix::ladder(&pitch, &root.parse().unwrap(), &pg).map(|interval| interval.to_string())
+    }
+
+    #[test]
+    fn test_ladder_intervals() {
+        assert_eq!(ladder("D5", "C4", PitchGroup::Cn).as_deref(), Some("M9"));
+        assert_eq!(ladder("F5", "C4", PitchGroup::Cn).as_deref(), Some("P11"));
+        assert_eq!(ladder("A5", "C4", PitchGroup::Cn).as_deref(), Some("M13"));
+        assert_eq!(ladder("C6", "C4", PitchGroup::Cn).as_deref(), Some("P15"));
+        assert_eq!(ladder("A3", "C5", PitchGroup::Cn).as_deref(), Some("-m10"));
+        assert_eq!(ladder("C#5", "C4", PitchGroup::Cn), None);
+        // F5 is E#5 in F#, a major seventh and an octave above F#3
+        assert_eq!(ladder("F5", "F#3", PitchGroup::Fs).as_deref(), Some("M14"));
+        // Db is spelled from the group even when the root is written as C#
+        assert_eq!(ladder("Eb5", "C#4", PitchGroup::Cs).as_deref(), Some("M9"));
+        let (e, f): (Tone, Tone) = ("E#5".parse().unwrap(), "F#3".parse().unwrap());
+        assert_eq!(Matrix::ladder_tone(&e, &f, &PitchGroup::Fs).map(|i| i.to_string()).as_deref(), Some("M14"));
+        assert_eq!(Matrix::spell(&"F4".parse().unwrap(), &PitchGroup::Fs), Some("E#4".parse().unwrap()));
+        assert_eq!(Matrix::spell(&"C#4".parse().unwrap(), &PitchGroup::Cs), Some("Db4".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_ladder_degrees() {
+        let c4: Tone = "C4".parse().unwrap();
+        let degree = |pitch: &str| Matrix::ladder_degree(&pitch.parse().unwrap(), &c4, &PitchGroup::Cn);
+        assert_eq!(degree("D5"), Some((Degree::Supertonic(MajorQuality::Major), 1)));
+        assert_eq!(degree("F6"), Some((Degree::Subdominant(PerfectQuality::Perfect), 2)));
+        assert_eq!(degree("G4"), Some((Degree::Dominant(PerfectQuality::Perfect), 0)));
+        assert_eq!(degree("G3"), None);
+    }
 
     #[test]
     fn print_natural_matrix() {
@@ -17,7 +17,9 @@
 use super::PitchClass;
 use crate::types::note::Accidental::Natural;
 use crate::types::{Interval, MajorQuality, PerfectQuality, Steps};
+use crate::types::interval::LETTER_STEPS;
 use std::hash::Hash;
+use std::str::FromStr;
 use std::{cmp, fmt};
 
 /// [Notes](audiotheorem::types::Note) are names given to a specific
@@ -68,34 +70,53 @@ impl Note {
             B(Natural),
         ]
     }
-    /// True if a [Note](audiotheorem::types::Note) is sharp or double sharp.
+    /// Every spelling of every letter, from double flat to double sharp.
+    pub fn all() -> [Note; 35] {
+        use self::{Accidental::*, Note::*};
+        let letters: [fn(Accidental) -> Note; 7] = [C, D, E, F, G, A, B];
+        let accidentals = [DoubleFlat, Flat, Natural, Sharp, DoubleSharp];
+        let mut notes = [C(Natural); 35];
+        for (i, letter) in letters.iter().enumerate() {
+            for (j, accidental) in accidentals.iter().enumerate() {
+                notes[i * accidentals.len() + j] = letter(*accidental);
+            }
+        }
+        notes
+    }
+    /// True if a [Note](audiotheorem::types::Note) is sharp, double or triple sharp.
     pub fn sharp(&self) -> bool {
         match self.accidental() {
+            Accidental::TripleFlat => false,
             Accidental::DoubleFlat => false,
             Accidental::Flat => false,
             Accidental::Natural => false,
             Accidental::Sharp => true,
             Accidental::DoubleSharp => true,
+            Accidental::TripleSharp => true,
         }
     }
-    /// True if a [Note](audiotheorem::types::Note) is flat or double flat.
+    /// True if a [Note](audiotheorem::types::Note) is flat, double or triple flat.
     pub fn flat(&self) -> bool {
         match self.accidental() {
+            Accidental::TripleFlat => true,
             Accidental::DoubleFlat => true,
             Accidental::Flat => true,
             Accidental::Natural => false,
             Accidental::Sharp => false,
             Accidental::DoubleSharp => false,
+            Accidental::TripleSharp => false,
         }
     }
     /// True if a [Note](audiotheorem::types::Note) is natural.
     pub fn natural(&self) -> bool {
         match self.accidental() {
+            Accidental::TripleFlat => false,
             Accidental::DoubleFlat => false,
             Accidental::Flat => false,
             Accidental::Natural => true,
             Accidental::Sharp => false,
             Accidental::DoubleSharp => false,
+            Accidental::TripleSharp => false,
         }
     }
     /// Return associated [Accidental](audiotheorem::types::Accidental).
@@ -111,6 +132,37 @@ impl Note {
         }
     }
 
+    // Position of the letter name from C = 0 to B = 6
+    pub(crate) fn letter(&self) -> i32 {
+        match *self {
+            Note::C(_) => 0,
+            Note::D(_) => 1,
+            Note::E(_) => 2,
+            Note::F(_) => 3,
+            Note::G(_) => 4,
+            Note::A(_) => 5,
+            Note::B(_) => 6,
+        }
+    }
+
+    /// Position on the line of fifths with C at 0, G at 1 and F at -1, running from -22 for Fbbb to
+    /// 26 for B###. Each sharp moves a [Note](audiotheorem::types::Note) seven places to the right.
+    pub fn fifths(&self) -> i32 {
+        [0, 2, 4, -1, 1, 3, 5][self.letter() as usize] + 7 * self.accidental().offset()
+    }
+    /// [Note](audiotheorem::types::Note) at a position on the line of fifths, if it can be spelled with
+    /// at most a triple accidental.
+    pub fn from_fifths(position: i32) -> Option<Note> {
+        let accidental = Accidental::from_offset((position + 1).div_euclid(7))?;
+        let letters: [fn(Accidental) -> Note; 7] = [Note::F, Note::C, Note::G, Note::D, Note::A, Note::E, Note::B];
+        Some(letters[(position + 1).rem_euclid(7) as usize](accidental))
+    }
+
+    // Semitones above the C of the same letter octave, from -3 for Cbbb to 14 for B###
+    pub(crate) fn height(&self) -> i32 {
+        LETTER_STEPS[self.letter() as usize] + self.accidental().offset()
+    }
+
     // Determines the number of the pitchclass index based on the note and accidental
     pub fn index(&self) -> u8 {
         let x1 = match *self {
@@ -123,11 +175,13 @@ impl Note {
             Note::B(_) => 11,
         };
         let x2 = match self.accidental() {
+            Accidental::TripleFlat => 9,
             Accidental::DoubleFlat => 10,
             Accidental::Flat => 11,
             Accidental::Natural => 12,
             Accidental::Sharp => 13,
             Accidental::DoubleSharp => 14,
+            Accidental::TripleSharp => 15,
         };
         (x1 + x2) % 12
     }
@@ -143,6 +197,50 @@ impl Note {
     }
 }
 
+impl Note {
+    /// Parse a note name at the start of some text, returning the rest - used by the
+    /// [Tone](audiotheorem::types::Tone) and chord symbol parsers.
+    pub(crate) fn parse_prefix(text: &str) -> Result<(Note, &str), &'static str> {
+        let mut chars = text.char_indices();
+        let letter = chars.next().ok_or("Missing note name")?.1;
+        let mut shift = 0i8;
+        let mut end = text.len();
+        for (i, c) in chars {
+            match c {
+                '#' | '♯' => shift += 1,
+                'x' | '𝄪' => shift += 2,
+                'b' | '♭' => shift -= 1,
+                '𝄫' => shift -= 2,
+                '♮' => (),
+                _ => { end = i; break; }
+            }
+        }
+        let accidental = Accidental::from_offset(i32::from(shift)).ok_or("Too many accidentals")?;
+        let note = match letter.to_ascii_uppercase() {
+            'A' => Note::A(accidental),
+            'B' => Note::B(accidental),
+            'C' => Note::C(accidental),
+            'D' => Note::D(accidental),
+            'E' => Note::E(accidental),
+            'F' => Note::F(accidental),
+            'G' => Note::G(accidental),
+            _ => return Err("Invalid note name"),
+        };
+        Ok((note, &text[end..]))
+    }
+}
+
+/// Parse note names with up to a triple accidental, e.g. "C", "C#", "Dbb", "E♯", "Fx" or "G#x".
+impl FromStr for Note {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Note, Self::Err> {
+        match Note::parse_prefix(s.trim())? {
+            (note, "") => Ok(note),
+            _ => Err("Unexpected text after note name"),
+        }
+    }
+}
+
 impl fmt::Display for Note {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
@@ -175,42 +273,76 @@ impl fmt::Debug for Note {
 /// [Note](audiotheorem::types::Note) is natural, sharp or flat.
 #[derive(Copy, Clone, Ord, Eq, PartialOrd, PartialEq, Hash)]
 pub enum Accidental {
+    TripleFlat,
     DoubleFlat,
     Flat,
     Natural,
     Sharp,
     DoubleSharp,
+    TripleSharp,
 }
 
 impl Accidental {
-    /// True if a [Note](audiotheorem::types::Note) is sharp or double sharp.
+    /// Semitones the [Accidental](audiotheorem::types::Accidental) raises a natural note by, -3 to 3.
+    pub fn offset(&self) -> i32 {
+        match *self {
+            Accidental::TripleFlat => -3,
+            Accidental::DoubleFlat => -2,
+            Accidental::Flat => -1,
+            Accidental::Natural => 0,
+            Accidental::Sharp => 1,
+            Accidental::DoubleSharp => 2,
+            Accidental::TripleSharp => 3,
+        }
+    }
+    /// [Accidental](audiotheorem::types::Accidental) raising a natural note by `offset` semitones,
+    /// none beyond a triple sharp or flat.
+    pub fn from_offset(offset: i32) -> Option<Accidental> {
+        match offset {
+            -3 => Some(Accidental::TripleFlat),
+            -2 => Some(Accidental::DoubleFlat),
+            -1 => Some(Accidental::Flat),
+            0 => Some(Accidental::Natural),
+            1 => Some(Accidental::Sharp),
+            2 => Some(Accidental::DoubleSharp),
+            3 => Some(Accidental::TripleSharp),
+            _ => None,
+        }
+    }
+    /// True if a [Note](audiotheorem::types::Note) is sharp, double or triple sharp.
     pub fn sharp(&self) -> bool {
         match *self {
+            Accidental::TripleFlat => false,
             Accidental::DoubleFlat => false,
             Accidental::Flat => false,
             Accidental::Natural => false,
             Accidental::Sharp => true,
             Accidental::DoubleSharp => true,
+            Accidental::TripleSharp => true,
         }
     }
-    /// True if a [Note](audiotheorem::types::Note) is flat or double flat.
+    /// True if a [Note](audiotheorem::types::Note) is flat, double or triple flat.
     pub fn flat(&self) -> bool {
         match *self {
+            Accidental::TripleFlat => true,
             Accidental::DoubleFlat => true,
             Accidental::Flat => true,
             Accidental::Natural => false,
             Accidental::Sharp => false,
             Accidental::DoubleSharp => false,
+            Accidental::TripleSharp => false,
         }
     }
     /// True if a [Note](audiotheorem::types::Note) is natural.
     pub fn natural(&self) -> bool {
         match *self {
+            Accidental::TripleFlat => false,
             Accidental::DoubleFlat => false,
             Accidental::Flat => false,
             Accidental::Natural => true,
             Accidental::Sharp => false,
             Accidental::DoubleSharp => false,
+            Accidental::TripleSharp => false,
         }
     }
 }
@@ -218,11 +350,13 @@ impl Accidental {
 impl fmt::Display for Accidental {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
+            Accidental::TripleFlat => format_args!("bbb").fmt(f),
             Accidental::DoubleFlat => format_args!("bb").fmt(f),
             Accidental::Flat => format_args!("b").fmt(f),
             Accidental::Natural => format_args!("").fmt(f),
             Accidental::Sharp => format_args!("#").fmt(f),
             Accidental::DoubleSharp => format_args!("##").fmt(f),
+            Accidental::TripleSharp => format_args!("###").fmt(f),
         }
     }
 }
@@ -230,6 +364,9 @@ impl fmt::Display for Accidental {
 impl fmt::Debug for Accidental {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
+            Accidental::TripleFlat => {
+                fmt::Debug::fmt(&format_args!("TripleFlat"), f)
+            }
             Accidental::DoubleFlat => {
                 fmt::Debug::fmt(&format_args!("DoubleFlat"), f)
             }
@@ -239,6 +376,9 @@ impl fmt::Debug for Accidental {
             Accidental::DoubleSharp => {
                 fmt::Debug::fmt(&format_args!("DoubleSharp"), f)
             }
+            Accidental::TripleSharp => {
+                fmt::Debug::fmt(&format_args!("TripleSharp"), f)
+            }
         }
     }
 }
@@ -549,6 +689,11 @@ mod tests {
         test(
             Note::F(Accidental::DoubleFlat),
             Interval::Fourth(PerfectQuality::Perfect),
+            Some(B(TripleFlat)),
+        );
+        test(
+            Note::F(Accidental::DoubleFlat),
+            Interval::Fourth(PerfectQuality::Diminished),
             None,
         );
     }
@@ -589,4 +734,48 @@ mod tests {
             Some(C(Sharp)),
         );
     }
+
+    #[test]
+    fn test_parse() {
+        fn test(text: &str, expect: Note) {
+            assert_eq!(text.parse::<Note>(), Ok(expect), "Parsing {}", text);
+        }
+        test("C", C(Natural));
+        test("C#", C(Sharp));
+        test("Dbb", D(DoubleFlat));
+        test("E♯", E(Sharp));
+        test("F##", F(DoubleSharp));
+        test("Gx", G(DoubleSharp));
+        test("A𝄫", A(DoubleFlat));
+        test("b♭", B(Flat));
+        test(" B♮ ", B(Natural));
+        assert!("H".parse::<Note>().is_err());
+        assert!("".parse::<Note>().is_err());
+        test("C###", C(TripleSharp));
+        test("Fbbb", F(TripleFlat));
+        test("G#x", G(TripleSharp));
+        assert!("C####".parse::<Note>().is_err());
+        assert!("Dbbbb".parse::<Note>().is_err());
+        assert!("C4".parse::<Note>().is_err());
+        for note in Note::sharps().iter().chain(Note::flats().iter()) {
+            assert_eq!(note.to_string().parse::<Note>(), Ok(*note));
+        }
+        for note in [C(TripleSharp), F(TripleFlat)] {
+            assert_eq!(note.to_string().parse::<Note>(), Ok(note));
+        }
+    }
+
+    #[test]
+    fn test_triple_accidentals() {
+        assert_eq!(C(TripleSharp).to_string(), "C###");
+        assert_eq!(F(TripleFlat).to_string(), "Fbbb");
+        assert_eq!(C(TripleSharp).pitch_class(), PitchClass::Ds);
+        assert_eq!(F(TripleFlat).pitch_class(), PitchClass::Dn);
+        assert_eq!(Note::from_fifths(-22), Some(F(TripleFlat)));
+        assert_eq!(Note::from_fifths(26), Some(B(TripleSharp)));
+        assert_eq!(Note::from_fifths(27), None);
+        assert_eq!(C(DoubleSharp) + First(Augmented), Some(C(TripleSharp)));
+        assert_eq!(F(DoubleFlat) + First(Diminished), Some(F(TripleFlat)));
+        assert_eq!(Interval::distance(C(Natural), C(TripleSharp)), Some(First(PerfectQuality::TripleAugmented)));
+    }
 }
@@ -14,7 +14,7 @@
 // limitations under the License.
 //
 
-use std::{fmt, hash::Hash};
+use std::{fmt, hash::Hash, str::FromStr};
 
 /// [Octave](audiotheorem::types::Octave) of a [Pitch](audiotheorem::types::Pitch).
 #[derive(Copy, Clone, PartialOrd, PartialEq, Eq, Hash)]
@@ -186,6 +186,16 @@ impl fmt::Debug for Octave {
     }
 }
 
+/// Parse a scientific octave number from -1 to 10.
+impl FromStr for Octave {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Octave, Self::Err> {
+        let scientific = s.trim().parse::<i8>().map_err(|_| "Invalid octave number")?;
+        if scientific < -1 { return Err("Octave out of range"); }
+        Octave::from_index((scientific + 1) as u8).ok_or("Octave out of range")
+    }
+}
+
 impl fmt::Display for Octave {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
@@ -14,12 +14,14 @@
 // limitations under the License.
 //
 
-use super::{Note, Octave, PitchClass, Steps};
+use super::{Cents, Note, Octave, PitchClass, Steps};
 use crate::types::interval::PerfectQuality::{Augmented, Diminished};
-use crate::types::{Interval, Matrix, Tone};
+use crate::types::{Accidental, Interval, Matrix, Tone};
 use std::ops;
+use std::str::FromStr;
 
 /// A4 [Pitch](audiotheorem::types::Pitch) [Tuning](audiotheorem::types::Tuning).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Tuning {
     A4_432Hz = 0,
     A4_434Hz = 1,
@@ -85,11 +87,20 @@ impl Pitch {
     /// Get [Octave](audiotheorem::types::Octave) of this [Pitch](audiotheorem::types::Pitch)
     pub fn octave(&self) -> Octave { Octave::from_index(self.0 / 12).unwrap() }
 
-    /// Get the semitone steps between two [Pitches](audiotheorem::types::Pitch).
+    /// Get the signed semitone steps from this [Pitch](audiotheorem::types::Pitch) to another - negative if `other` is lower.
     pub fn distance(&self, other: &Pitch) -> Steps {
-        let l = self.to_index() as u16;
-        let r = other.to_index() as u16;
-        Steps::from(if r > l { r - l } else { l - r })
+        Steps::from(other.to_index() as i32 - self.to_index() as i32)
+    }
+
+    /// Offset this [Pitch](audiotheorem::types::Pitch) by any [Cents](audiotheorem::types::Cents), returning the
+    /// nearest [Pitch](audiotheorem::types::Pitch) and the detune left over (-50 to 50 cents), or None when out of range.
+    pub fn offset(&self, cents: Cents) -> Option<(Pitch, Cents)> {
+        (*self + cents.steps()).map(|pitch| (pitch, cents.remainder()))
+    }
+
+    /// Get Frequency of [Pitch](audiotheorem::types::Pitch) detuned by any [Cents](audiotheorem::types::Cents).
+    pub fn detuned(&self, tuning: Tuning, cents: Cents) -> f32 {
+        (self.frequency(tuning) as f64 * cents.ratio()) as f32
     }
     
     /// Get Frequency of [Pitch](audiotheorem::types::Pitch).
@@ -104,6 +115,20 @@ impl From<u8> for Pitch {
     }
 }
 
+/// Parse a [Pitch](audiotheorem::types::Pitch) from scientific pitch notation, e.g. "A4" or "C-1", or from its index,
+/// e.g. "69". The letter sets the octave, so "B#4" is the same pitch as "C5" and "Cb4" the same as "B3".
+impl FromStr for Pitch {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Pitch, Self::Err> {
+        let s = s.trim();
+        if let Ok(index) = s.parse::<u8>() {
+            return if index < 144 { Ok(Pitch(index)) } else { Err("Pitch out of range") };
+        }
+        let index = s.parse::<Tone>()?.height();
+        if (0..144).contains(&index) { Ok(Pitch(index as u8)) } else { Err("Pitch out of range") }
+    }
+}
+
 impl ops::Add<Interval> for Pitch {
     type Output = Option<Self>;
     fn add(self, interval: Interval) -> Self::Output {
@@ -118,7 +143,7 @@ impl ops::Add<Interval> for Pitch {
 impl ops::Add<Steps> for Pitch {
     type Output = Option<Self>;
     fn add(self, steps: Steps) -> Self::Output {
-        let new_pitch_index = self.to_index() as i32 + steps.value() as i32;
+        let new_pitch_index = self.to_index() as i32 + steps.value();
         if 0 <= new_pitch_index && new_pitch_index < 144 {
             Some(Pitch::from_index(new_pitch_index as u8))
         } else {
@@ -141,7 +166,7 @@ impl std::ops::Sub<Interval> for Pitch {
 impl std::ops::Sub<Steps> for Pitch {
     type Output = Option<Self>;
     fn sub(self, steps: Steps) -> Self::Output {
-        let new_pitch_index = self.to_index() as i32 - steps.value() as i32;
+        let new_pitch_index = self.to_index() as i32 - steps.value();
         if 0 <= new_pitch_index && new_pitch_index < 144 {
             Some(Pitch::from_index(new_pitch_index as u8))
         } else {
@@ -203,6 +228,45 @@ mod tests {
         );
         test(Pitch::from_index(29), Steps::from(30), None);
     }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("A4".parse::<Pitch>(), Ok(Pitch::from_index(69)));
+        assert_eq!("C-1".parse::<Pitch>(), Ok(Pitch::from_index(0)));
+        assert_eq!("Bb7".parse::<Pitch>(), Ok(Pitch::from_index(106)));
+        assert_eq!("B#4".parse::<Pitch>(), Ok(Pitch::from_index(72)));
+        assert_eq!("Cb4".parse::<Pitch>(), Ok(Pitch::from_index(59)));
+        assert_eq!("69".parse::<Pitch>(), Ok(Pitch::from_index(69)));
+        assert!("Cb-1".parse::<Pitch>().is_err());
+        assert!("144".parse::<Pitch>().is_err());
+        for index in 0..144 {
+            let pitch = Pitch::from_index(index);
+            assert_eq!(pitch.tone().to_string().parse::<Pitch>(), Ok(pitch));
+        }
+    }
+
+    #[test]
+    fn test_signed_distance() {
+        use crate::types::Steps;
+        assert_eq!(Pitch::from_index(60).distance(&Pitch::from_index(67)), Steps::from(7));
+        assert_eq!(Pitch::from_index(67).distance(&Pitch::from_index(60)), Steps::from(-7));
+        assert_eq!(Pitch::from_index(69) + Steps::from(-12), Some(Pitch::from_index(57)));
+        assert_eq!(Pitch::from_index(69) - Steps::from(-3), Some(Pitch::from_index(72)));
+    }
+
+    #[test]
+    fn test_cents_offset() {
+        use crate::types::{Cents, Tuning};
+        let (pitch, detune) = Pitch::from_index(60).offset(Cents::from(386.3137)).unwrap();
+        assert_eq!(pitch, Pitch::from_index(64));
+        assert!((detune.cents() + 13.6863).abs() < 1e-9);
+        let (pitch, detune) = Pitch::from_index(60).offset(Cents::from(-149.0)).unwrap();
+        assert_eq!(pitch, Pitch::from_index(59));
+        assert!((detune.cents() + 49.0).abs() < 1e-9);
+        assert_eq!(Pitch::from_index(2).offset(Cents::from(-300.0)), None);
+        assert!((Pitch::from_index(69).detuned(Tuning::A4_440Hz, Cents::from(-1200.0)) - 220.0).abs() < 1e-4);
+        assert!((Pitch::from_index(69).detuned(Tuning::A4_440Hz, Cents::from(701.955)) - 660.0).abs() < 0.01);
+    }
 }
 
 /// Frequency Chart
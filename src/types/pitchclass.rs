@@ -176,8 +176,12 @@ impl PitchClass {
             // Gs
             (Gs, A(_)) => Some(A(Flat)),
             (Gs, G(_)) => Some(G(Sharp)),
-            // Other
+            // Three semitones from the letter, spelled with a triple flat or sharp
             (_, _) => {
+                let shift = (i32::from(self.to_index()) - i32::from(n.index()) + n.accidental().offset() + 6).rem_euclid(12) - 6;
+                if shift.abs() == 3 {
+                    return Note::from_fifths(n.fifths() + 7 * (shift - n.accidental().offset()));
+                }
                 println!("PitchClass: {} Note: {} -> None", self, n);
                 None
             }
@@ -291,7 +295,7 @@ impl std::ops::Add<Steps> for PitchClass {
     type Output = Self;
     fn add(self, steps: Steps) -> Self::Output {
         PitchClass::from_index(
-            ((self.to_index() as u16 + steps.value()) % 12) as u8,
+            (self.to_index() as i32 + steps.value()).rem_euclid(12) as u8,
         )
     }
 }
@@ -307,7 +311,7 @@ impl std::ops::Sub<Steps> for PitchClass {
     type Output = Self;
     fn sub(self, steps: Steps) -> Self::Output {
         PitchClass::from_index(
-            ((144 + self.to_index() as u16 - steps.value()) % 12) as u8,
+            (self.to_index() as i32 - steps.value()).rem_euclid(12) as u8,
         )
     }
 }
@@ -0,0 +1,245 @@
+//
+// Copyright 2024 Hans W. Uhlig, Richard I. Christopher. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+
+use super::{Note, PitchClass, PitchGroup, Steps};
+use std::fmt;
+use std::ops::{BitAnd, BitOr, BitXor, Not, Sub};
+
+/// [PitchClassSet](audiotheorem::types::PitchClassSet) is an unordered set of
+/// [PitchClasses](audiotheorem::types::PitchClass) held as one bit each, so it can be copied, compared
+/// and combined without allocating.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PitchClassSet {
+    // Bit n is the pitch class with index n
+    mask: u16,
+}
+
+impl PitchClassSet {
+    pub const EMPTY: PitchClassSet = PitchClassSet { mask: 0 };
+    pub const CHROMATIC: PitchClassSet = PitchClassSet { mask: 0xFFF };
+
+    pub const fn new() -> PitchClassSet { PitchClassSet::EMPTY }
+
+    /// Set from a mask of pitch class indices, ignoring bits above the twelfth.
+    pub const fn from_mask(mask: u16) -> PitchClassSet { PitchClassSet { mask: mask & 0xFFF } }
+
+    pub const fn mask(&self) -> u16 { self.mask }
+
+    pub fn len(&self) -> usize { self.mask.count_ones() as usize }
+
+    pub fn is_empty(&self) -> bool { self.mask == 0 }
+
+    pub fn contains(&self, pc: &PitchClass) -> bool { self.mask & bit(pc) != 0 }
+
+    /// Add a [PitchClass](audiotheorem::types::PitchClass), true if it was not already there.
+    pub fn insert(&mut self, pc: PitchClass) -> bool {
+        let added = !self.contains(&pc);
+        self.mask |= bit(&pc);
+        added
+    }
+
+    /// Take out a [PitchClass](audiotheorem::types::PitchClass), true if it was there.
+    pub fn remove(&mut self, pc: &PitchClass) -> bool {
+        let removed = self.contains(pc);
+        self.mask &= !bit(pc);
+        removed
+    }
+
+    pub fn union(&self, other: PitchClassSet) -> PitchClassSet { PitchClassSet { mask: self.mask | other.mask } }
+    pub fn intersection(&self, other: PitchClassSet) -> PitchClassSet { PitchClassSet { mask: self.mask & other.mask } }
+    pub fn difference(&self, other: PitchClassSet) -> PitchClassSet { PitchClassSet { mask: self.mask & !other.mask } }
+    pub fn symmetric_difference(&self, other: PitchClassSet) -> PitchClassSet { PitchClassSet { mask: self.mask ^ other.mask } }
+    /// Every [PitchClass](audiotheorem::types::PitchClass) not in the set.
+    pub fn complement(&self) -> PitchClassSet { PitchClassSet { mask: !self.mask & 0xFFF } }
+
+    pub fn is_subset(&self, other: PitchClassSet) -> bool { self.mask & !other.mask == 0 }
+    pub fn is_superset(&self, other: PitchClassSet) -> bool { other.is_subset(*self) }
+    pub fn is_disjoint(&self, other: PitchClassSet) -> bool { self.mask & other.mask == 0 }
+
+    /// Move every [PitchClass](audiotheorem::types::PitchClass) up by some [Steps](audiotheorem::types::Steps).
+    pub fn transpose(&self, steps: Steps) -> PitchClassSet {
+        let n = steps.value().rem_euclid(12) as u32;
+        PitchClassSet { mask: ((self.mask << n) | (self.mask >> (12 - n))) & 0xFFF }
+    }
+
+    /// Mirror every [PitchClass](audiotheorem::types::PitchClass) around C.
+    pub fn invert(&self) -> PitchClassSet {
+        self.iter().map(|pc| PitchClass::from_index((12 - pc.to_index()) % 12)).collect()
+    }
+
+    /// [PitchClasses](audiotheorem::types::PitchClass) from C upwards.
+    pub fn iter(&self) -> Iter { Iter { mask: self.mask } }
+}
+
+#[inline]
+fn bit(pc: &PitchClass) -> u16 { 1 << pc.to_index() }
+
+/// Iterator over a [PitchClassSet](audiotheorem::types::PitchClassSet) from C upwards.
+#[derive(Clone, Debug)]
+pub struct Iter {
+    mask: u16,
+}
+
+impl Iterator for Iter {
+    type Item = PitchClass;
+
+    fn next(&mut self) -> Option<PitchClass> {
+        if self.mask == 0 { return None; }
+        let index = self.mask.trailing_zeros() as u8;
+        self.mask &= self.mask - 1;
+        Some(PitchClass::from_index(index))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.mask.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for Iter {}
+
+impl IntoIterator for PitchClassSet {
+    type Item = PitchClass;
+    type IntoIter = Iter;
+
+    fn into_iter(self) -> Iter { self.iter() }
+}
+
+impl IntoIterator for &PitchClassSet {
+    type Item = PitchClass;
+    type IntoIter = Iter;
+
+    fn into_iter(self) -> Iter { self.iter() }
+}
+
+impl FromIterator<PitchClass> for PitchClassSet {
+    fn from_iter<I: IntoIterator<Item = PitchClass>>(iter: I) -> PitchClassSet {
+        PitchClassSet { mask: iter.into_iter().fold(0, |mask, pc| mask | bit(&pc)) }
+    }
+}
+
+impl From<&[PitchClass]> for PitchClassSet {
+    fn from(pitch_classes: &[PitchClass]) -> PitchClassSet { pitch_classes.iter().copied().collect() }
+}
+
+impl From<&[Note]> for PitchClassSet {
+    fn from(notes: &[Note]) -> PitchClassSet { notes.iter().map(Note::pitch_class).collect() }
+}
+
+impl From<PitchClass> for PitchClassSet {
+    fn from(pc: PitchClass) -> PitchClassSet { PitchClassSet { mask: bit(&pc) } }
+}
+
+impl From<PitchGroup> for PitchClassSet {
+    /// The seven [PitchClasses](audiotheorem::types::PitchClass) of the group.
+    fn from(pg: PitchGroup) -> PitchClassSet {
+        const GROUPS: [u16; 12] = {
+            // Major scale on each group's tonic, in PitchGroup order around the circle of fifths
+            let major: u16 = 0b1010_1011_0101;
+            let mut groups = [0; 12];
+            let mut i = 0;
+            while i < 12 {
+                let n = (i * 7) % 12;
+                groups[i] = ((major << n) | (major >> (12 - n))) & 0xFFF;
+                i += 1;
+            }
+            groups
+        };
+        PitchClassSet { mask: GROUPS[pg.to_index() as usize] }
+    }
+}
+
+impl BitOr for PitchClassSet {
+    type Output = PitchClassSet;
+    fn bitor(self, other: PitchClassSet) -> PitchClassSet { self.union(other) }
+}
+
+impl BitAnd for PitchClassSet {
+    type Output = PitchClassSet;
+    fn bitand(self, other: PitchClassSet) -> PitchClassSet { self.intersection(other) }
+}
+
+impl BitXor for PitchClassSet {
+    type Output = PitchClassSet;
+    fn bitxor(self, other: PitchClassSet) -> PitchClassSet { self.symmetric_difference(other) }
+}
+
+impl Sub for PitchClassSet {
+    type Output = PitchClassSet;
+    fn sub(self, other: PitchClassSet) -> PitchClassSet { self.difference(other) }
+}
+
+impl Not for PitchClassSet {
+    type Output = PitchClassSet;
+    fn not(self) -> PitchClassSet { self.complement() }
+}
+
+/// Written as its members, e.g. "{Cn, En, Gn}".
+impl fmt::Display for PitchClassSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{")?;
+        for (i, pc) in self.iter().enumerate() {
+            if i > 0 { write!(f, ", ")?; }
+            write!(f, "{}", pc)?;
+        }
+        write!(f, "}}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PitchClassSet;
+    use crate::types::{Note, PitchClass::*, PitchGroup, Steps};
+
+    #[test]
+    fn test_algebra() {
+        let c = PitchClassSet::from(&[Cn, En, Gn][..]);
+        let a = PitchClassSet::from(&[An, Cn, En][..]);
+        assert_eq!(c & a, PitchClassSet::from(&[Cn, En][..]));
+        assert_eq!((c | a).len(), 4);
+        assert_eq!(c - a, PitchClassSet::from(Gn));
+        assert_eq!(c ^ a, PitchClassSet::from(&[Gn, An][..]));
+        assert_eq!((!c).len(), 9);
+        assert!(PitchClassSet::from(Cn).is_subset(c));
+        assert!(c.is_disjoint(PitchClassSet::from(&[Cs, Ds][..])));
+
+        let mut set = PitchClassSet::new();
+        assert!(set.insert(Dn));
+        assert!(!set.insert(Dn));
+        assert!(set.remove(&Dn));
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn test_iteration_and_transposition() {
+        let notes: Vec<Note> = ["G", "B", "D", "F"].iter().map(|n| n.parse().unwrap()).collect();
+        let g7 = PitchClassSet::from(&notes[..]);
+        assert_eq!(g7.iter().collect::<Vec<_>>(), vec![Dn, Fn, Gn, Bn]);
+        assert_eq!(g7.iter().len(), 4);
+        assert_eq!(g7.transpose(Steps::from(5)), PitchClassSet::from(&[Cn, En, Gn, As][..]));
+        assert_eq!(g7.transpose(Steps::from(-7)), g7.transpose(Steps::from(5)));
+        assert_eq!(PitchClassSet::from(&[Cn, En, Gn][..]).invert(), PitchClassSet::from(&[Cn, Fn, Gs][..]));
+        assert_eq!(g7.to_string(), "{Dn, Fn, Gn, Bn}");
+    }
+
+    #[test]
+    fn test_pitch_groups() {
+        for pg in PitchGroup::all() {
+            assert_eq!(PitchClassSet::from(pg), PitchClassSet::from(&pg.pitch_classes()[..]));
+        }
+    }
+}
@@ -14,8 +14,7 @@
 // limitations under the License.
 //
 
-use super::{Accidental, Interval, Matrix, Note, PitchClass, PitchMode};
-use std::collections::HashSet;
+use super::{Accidental, Interval, Matrix, ModeFamily, Note, PitchClass, PitchClassSet, PitchMode, Steps};
 use std::fmt;
 use tracing::{debug, instrument};
 
@@ -376,8 +375,128 @@ impl PitchGroup {
         }
     }
 
+    /// Get the seven [PitchModes](audiotheorem::types::PitchMode) of a
+    /// [ModeFamily](audiotheorem::types::ModeFamily) for this [PitchGroup](audiotheorem::types::PitchGroup).
+    /// Harmonic and melodic minor are built on the relative minor and harmonic major and double harmonic
+    /// on the major key, so the Cn group gives A harmonic minor and C harmonic major.
+    pub fn modes(&self, family: ModeFamily) -> [PitchMode; 7] {
+        let root = self.pitch_class() + Steps::from(i32::from(family.anchor()));
+        let scale = family.steps().map(|step| root + Steps::from(i32::from(step)));
+        let mut modes = [PitchMode::ionian(scale); 7];
+        for (rotation, mode) in modes.iter_mut().enumerate() {
+            let mut notes = scale;
+            notes.rotate_left(rotation);
+            *mode = PitchMode::from_family(family, rotation, notes);
+        }
+        modes
+    }
+    /// Spell the first mode of a [ModeFamily](audiotheorem::types::ModeFamily) on this
+    /// [PitchGroup](audiotheorem::types::PitchGroup). Each altered degree keeps the letter of the diatonic
+    /// note it replaces, so the Cn group spells A harmonic minor with a G#.
+    pub fn spell(&self, family: ModeFamily) -> [Note; 7] {
+        let scale = self.modes(family)[0];
+        let diatonic = self.modes(ModeFamily::Diatonic).into_iter()
+            .find(|mode| mode.tonic() == scale.tonic())
+            .expect("every family starts on a note of its group");
+        let mut notes = [Note::C(Accidental::Natural); 7];
+        for (note, (pc, natural)) in notes.iter_mut().zip(scale.notes().iter().zip(diatonic.notes())) {
+            let spelled = Matrix::natural(natural, self).expect("diatonic notes are spelled by the matrix");
+            let shift = (i32::from(pc.to_index()) - i32::from(natural.to_index()) + 6).rem_euclid(12) - 6;
+            *note = Note::from_fifths(spelled.fifths() + 7 * shift).expect("families alter a degree by one semitone");
+        }
+        notes
+    }
+    /// Get Harmonic Minor [PitchMode](audiotheorem::types::PitchMode) for this
+    /// [PitchGroup](audiotheorem::types::PitchGroup).
+    pub fn harmonic_minor(&self) -> PitchMode { self.modes(ModeFamily::HarmonicMinor)[0] }
+    /// Get Locrian Natural 6 [PitchMode](audiotheorem::types::PitchMode), the second mode of harmonic minor for this
+    /// [PitchGroup](audiotheorem::types::PitchGroup).
+    pub fn locrian_natural6(&self) -> PitchMode { self.modes(ModeFamily::HarmonicMinor)[1] }
+    /// Get Ionian Augmented [PitchMode](audiotheorem::types::PitchMode), the third mode of harmonic minor for this
+    /// [PitchGroup](audiotheorem::types::PitchGroup).
+    pub fn ionian_augmented(&self) -> PitchMode { self.modes(ModeFamily::HarmonicMinor)[2] }
+    /// Get Dorian #4 [PitchMode](audiotheorem::types::PitchMode), the fourth mode of harmonic minor for this
+    /// [PitchGroup](audiotheorem::types::PitchGroup).
+    pub fn dorian_sharp4(&self) -> PitchMode { self.modes(ModeFamily::HarmonicMinor)[3] }
+    /// Get Phrygian Dominant [PitchMode](audiotheorem::types::PitchMode), the fifth mode of harmonic minor for this
+    /// [PitchGroup](audiotheorem::types::PitchGroup).
+    pub fn phrygian_dominant(&self) -> PitchMode { self.modes(ModeFamily::HarmonicMinor)[4] }
+    /// Get Lydian #2 [PitchMode](audiotheorem::types::PitchMode), the sixth mode of harmonic minor for this
+    /// [PitchGroup](audiotheorem::types::PitchGroup).
+    pub fn lydian_sharp2(&self) -> PitchMode { self.modes(ModeFamily::HarmonicMinor)[5] }
+    /// Get Ultralocrian [PitchMode](audiotheorem::types::PitchMode), the seventh mode of harmonic minor for this
+    /// [PitchGroup](audiotheorem::types::PitchGroup).
+    pub fn ultralocrian(&self) -> PitchMode { self.modes(ModeFamily::HarmonicMinor)[6] }
+    /// Get Melodic Minor [PitchMode](audiotheorem::types::PitchMode) for this
+    /// [PitchGroup](audiotheorem::types::PitchGroup).
+    pub fn melodic_minor(&self) -> PitchMode { self.modes(ModeFamily::MelodicMinor)[0] }
+    /// Get Dorian b2 [PitchMode](audiotheorem::types::PitchMode), the second mode of melodic minor for this
+    /// [PitchGroup](audiotheorem::types::PitchGroup).
+    pub fn dorian_flat2(&self) -> PitchMode { self.modes(ModeFamily::MelodicMinor)[1] }
+    /// Get Lydian Augmented [PitchMode](audiotheorem::types::PitchMode), the third mode of melodic minor for this
+    /// [PitchGroup](audiotheorem::types::PitchGroup).
+    pub fn lydian_augmented(&self) -> PitchMode { self.modes(ModeFamily::MelodicMinor)[2] }
+    /// Get Lydian Dominant [PitchMode](audiotheorem::types::PitchMode), the fourth mode of melodic minor for this
+    /// [PitchGroup](audiotheorem::types::PitchGroup).
+    pub fn lydian_dominant(&self) -> PitchMode { self.modes(ModeFamily::MelodicMinor)[3] }
+    /// Get Mixolydian b6 [PitchMode](audiotheorem::types::PitchMode), the fifth mode of melodic minor for this
+    /// [PitchGroup](audiotheorem::types::PitchGroup).
+    pub fn mixolydian_flat6(&self) -> PitchMode { self.modes(ModeFamily::MelodicMinor)[4] }
+    /// Get Locrian Natural 2 [PitchMode](audiotheorem::types::PitchMode), the sixth mode of melodic minor for this
+    /// [PitchGroup](audiotheorem::types::PitchGroup).
+    pub fn locrian_natural2(&self) -> PitchMode { self.modes(ModeFamily::MelodicMinor)[5] }
+    /// Get Altered [PitchMode](audiotheorem::types::PitchMode), the seventh mode of melodic minor for this
+    /// [PitchGroup](audiotheorem::types::PitchGroup).
+    pub fn altered(&self) -> PitchMode { self.modes(ModeFamily::MelodicMinor)[6] }
+    /// Get Harmonic Major [PitchMode](audiotheorem::types::PitchMode) for this
+    /// [PitchGroup](audiotheorem::types::PitchGroup).
+    pub fn harmonic_major(&self) -> PitchMode { self.modes(ModeFamily::HarmonicMajor)[0] }
+    /// Get Dorian b5 [PitchMode](audiotheorem::types::PitchMode), the second mode of harmonic major for this
+    /// [PitchGroup](audiotheorem::types::PitchGroup).
+    pub fn dorian_flat5(&self) -> PitchMode { self.modes(ModeFamily::HarmonicMajor)[1] }
+    /// Get Phrygian b4 [PitchMode](audiotheorem::types::PitchMode), the third mode of harmonic major for this
+    /// [PitchGroup](audiotheorem::types::PitchGroup).
+    pub fn phrygian_flat4(&self) -> PitchMode { self.modes(ModeFamily::HarmonicMajor)[2] }
+    /// Get Lydian b3 [PitchMode](audiotheorem::types::PitchMode), the fourth mode of harmonic major for this
+    /// [PitchGroup](audiotheorem::types::PitchGroup).
+    pub fn lydian_flat3(&self) -> PitchMode { self.modes(ModeFamily::HarmonicMajor)[3] }
+    /// Get Mixolydian b2 [PitchMode](audiotheorem::types::PitchMode), the fifth mode of harmonic major for this
+    /// [PitchGroup](audiotheorem::types::PitchGroup).
+    pub fn mixolydian_flat2(&self) -> PitchMode { self.modes(ModeFamily::HarmonicMajor)[4] }
+    /// Get Lydian Augmented #2 [PitchMode](audiotheorem::types::PitchMode), the sixth mode of harmonic major for this
+    /// [PitchGroup](audiotheorem::types::PitchGroup).
+    pub fn lydian_augmented_sharp2(&self) -> PitchMode { self.modes(ModeFamily::HarmonicMajor)[5] }
+    /// Get Locrian bb7 [PitchMode](audiotheorem::types::PitchMode), the seventh mode of harmonic major for this
+    /// [PitchGroup](audiotheorem::types::PitchGroup).
+    pub fn locrian_double_flat7(&self) -> PitchMode { self.modes(ModeFamily::HarmonicMajor)[6] }
+    /// Get Double Harmonic [PitchMode](audiotheorem::types::PitchMode) for this
+    /// [PitchGroup](audiotheorem::types::PitchGroup).
+    pub fn double_harmonic(&self) -> PitchMode { self.modes(ModeFamily::DoubleHarmonic)[0] }
+    /// Get Lydian #2 #6 [PitchMode](audiotheorem::types::PitchMode), the second mode of double harmonic for this
+    /// [PitchGroup](audiotheorem::types::PitchGroup).
+    pub fn lydian_sharp2_sharp6(&self) -> PitchMode { self.modes(ModeFamily::DoubleHarmonic)[1] }
+    /// Get Ultraphrygian [PitchMode](audiotheorem::types::PitchMode), the third mode of double harmonic for this
+    /// [PitchGroup](audiotheorem::types::PitchGroup).
+    pub fn ultraphrygian(&self) -> PitchMode { self.modes(ModeFamily::DoubleHarmonic)[2] }
+    /// Get Hungarian Minor [PitchMode](audiotheorem::types::PitchMode), the fourth mode of double harmonic for this
+    /// [PitchGroup](audiotheorem::types::PitchGroup).
+    pub fn hungarian_minor(&self) -> PitchMode { self.modes(ModeFamily::DoubleHarmonic)[3] }
+    /// Get Oriental [PitchMode](audiotheorem::types::PitchMode), the fifth mode of double harmonic for this
+    /// [PitchGroup](audiotheorem::types::PitchGroup).
+    pub fn oriental(&self) -> PitchMode { self.modes(ModeFamily::DoubleHarmonic)[4] }
+    /// Get Ionian #2 #5 [PitchMode](audiotheorem::types::PitchMode), the sixth mode of double harmonic for this
+    /// [PitchGroup](audiotheorem::types::PitchGroup).
+    pub fn ionian_augmented_sharp2(&self) -> PitchMode { self.modes(ModeFamily::DoubleHarmonic)[5] }
+    /// Get Locrian bb3 bb7 [PitchMode](audiotheorem::types::PitchMode), the seventh mode of double harmonic for this
+    /// [PitchGroup](audiotheorem::types::PitchGroup).
+    pub fn locrian_double_flat3_double_flat7(&self) -> PitchMode { self.modes(ModeFamily::DoubleHarmonic)[6] }
+
     /// Find which [PitchGroups](audiotheorem::types::PitchGroup) a given set of provided
     /// [Note](audiotheorem::types::Note) belong to.  
+    ///
+    /// When no diatonic group holds them, the other [ModeFamilies](audiotheorem::types::ModeFamily) are
+    /// searched instead, so A B C G# finds the Cn group through A harmonic minor. Use
+    /// [PitchGroup::spell](audiotheorem::types::PitchGroup::spell) to tell which family matched.
     #[instrument]
     // This search function uses the names of the notes to find the pitch groups
     // We could also implement a search function that uses the pitch classes of the notes
@@ -392,7 +511,7 @@ impl PitchGroup {
         let is_natural = notes.iter().all(|n| n.natural());
         let is_enharmonic = notes.iter().all(|n| n.enharmonic());
         if is_sharp && is_flat {
-            return Err(&"Both Sharp and Flat");
+            return PitchGroup::find_families(notes).ok_or("Both Sharp and Flat");
         }
         debug!(
             "Sharp: {:?} Flat: {:?} Natural: {:?}",
@@ -401,14 +520,12 @@ impl PitchGroup {
 
         // Get Pitch Groups which have ALL of these notes
         let pitch_groups = {
-            let mut remaining: HashSet<PitchGroup> =
-                PitchGroup::all().iter().copied().collect();
-            for note in notes {
-                let groups: HashSet<PitchGroup> =
-                    note.pitch_class().groups().iter().copied().collect();
-                remaining = remaining.intersection(&groups).copied().collect();
-            }
-            remaining
+            let wanted = PitchClassSet::from(notes);
+            PitchGroup::all()
+                .iter()
+                .copied()
+                .filter(|pg| PitchClassSet::from(*pg).is_superset(wanted))
+                .collect::<Vec<PitchGroup>>()
         };
         debug!("Potential PitchGroups: {:?}", &pitch_groups);
 
@@ -483,24 +600,44 @@ impl PitchGroup {
             groups
         };
         debug!("Verified PitchGroups: {:?}", &groups);
+        if groups.is_empty() {
+            return Ok(PitchGroup::find_families(notes).unwrap_or_default());
+        }
         Ok(groups)
     }
 
+    // Groups with a non-diatonic family scale spelling every note, none if there are no such groups
+    fn find_families(notes: &[Note]) -> Option<Vec<PitchGroup>> {
+        let groups: Vec<PitchGroup> = PitchGroup::all().into_iter()
+            .filter(|pg| ModeFamily::all()[1..].iter().any(|family| {
+                let scale = pg.spell(*family);
+                notes.iter().all(|note| scale.contains(note))
+            }))
+            .collect();
+        debug!("Family PitchGroups: {:?}", &groups);
+        if groups.is_empty() { None } else { Some(groups) }
+    }
+
 
     // Added by NeoTec Circa 2024, Richard I Christopher.
     // This is a core component of the Audio Theorem project under Nexus Proprietary License.
+    #[deprecated(note = "collect the pitch classes into a PitchClassSet and use PitchGroup::split_set")]
     pub fn split_classes(pitch_classes: Vec<PitchClass>) -> (Vec<PitchGroup>, Vec<PitchGroup>) {
-        // This is a safeguard to ensure that the pitch_classes vector is not empty
-        if pitch_classes.is_empty() { return (Vec::new(), Vec::new()); } // What do we really want to return here? are all pitch groups valid, or none?
-
-        let all: HashSet<PitchGroup> = PitchGroup::all().iter().copied().collect();
-        let pitch_class_groups: HashSet<PitchGroup> = pitch_classes.iter().map(|pc| pc.groups()).flatten().collect();
+        PitchGroup::split_set(PitchClassSet::from(&pitch_classes[..]))
+    }
 
-        let harmonic_groups: Vec<PitchGroup> = all.intersection(&pitch_class_groups).copied().collect();
-        let dissonant_groups: Vec<PitchGroup> = all.difference(&pitch_class_groups).copied().collect();
+    /// Split the [PitchGroups](audiotheorem::types::PitchGroup) into the harmonic ones, sharing at least
+    /// one of the [PitchClasses](audiotheorem::types::PitchClass), and the dissonant ones sharing none.
+    pub fn split_set(pitch_classes: PitchClassSet) -> (Vec<PitchGroup>, Vec<PitchGroup>) {
+        // This is a safeguard to ensure that the pitch_classes set is not empty
+        if pitch_classes.is_empty() { return (Vec::new(), Vec::new()); } // What do we really want to return here? are all pitch groups valid, or none?
 
-        (harmonic_groups, dissonant_groups)
+        // Harmonic groups share at least one pitch class with what is played, dissonant groups share none.
+        // Only the diatonic sets count here - every other family shares five or six pitch classes with the
+        // diatonic set of its group, so letting them in as well would make nearly every group harmonic.
+        PitchGroup::all().iter().partition(|pg| !PitchClassSet::from(**pg).is_disjoint(pitch_classes))
     }
+
     // Added by NeoTec Circa 2024, Richard I Christopher. ^^
     // get off my dick.
 
@@ -547,7 +684,69 @@ impl PartialEq<PitchClass> for PitchGroup {
 #[cfg(test)]
 mod tests {
     use super::PitchGroup;
-    use crate::types::{Accidental::*, Note::*, PitchClass::*};
+    use crate::types::{Accidental::*, ModeFamily, Note::*, PitchClass::*, PitchClassSet, PitchMode};
+
+    #[test]
+    fn test_diatonic_family() {
+        for pg in PitchGroup::all() {
+            let modes = [pg.ionian(), pg.dorian(), pg.phrygian(), pg.lydian(), pg.mixolydian(), pg.aeolian(), pg.locrian()];
+            assert_eq!(pg.modes(ModeFamily::Diatonic), modes);
+        }
+    }
+
+    #[test]
+    fn test_mode_families() {
+        assert_eq!(PitchGroup::Cn.harmonic_minor(), PitchMode::HarmonicMinor([An, Bn, Cn, Dn, En, Fn, Gs]));
+        assert_eq!(PitchGroup::Cn.phrygian_dominant(), PitchMode::PhrygianDominant([En, Fn, Gs, An, Bn, Cn, Dn]));
+        assert_eq!(PitchGroup::Cn.melodic_minor().notes(), &[An, Bn, Cn, Dn, En, Fs, Gs]);
+        assert_eq!(PitchGroup::As.lydian_dominant().notes(), &[Cn, Dn, En, Fs, Gn, An, As]);
+        assert_eq!(PitchGroup::As.altered().notes(), &[Fs, Gn, An, As, Cn, Dn, En]);
+        assert_eq!(PitchGroup::Cn.harmonic_major().notes(), &[Cn, Dn, En, Fn, Gn, Gs, Bn]);
+        assert_eq!(PitchGroup::Cn.hungarian_minor().notes(), &[Fn, Gn, Gs, Bn, Cn, Cs, En]);
+
+        let altered = PitchGroup::As.altered();
+        assert_eq!((altered.family(), altered.rotation(), altered.name()), (ModeFamily::MelodicMinor, 6, "Altered"));
+        assert_eq!(altered.tonic(), Fs);
+        assert_eq!(PitchGroup::Cn.phrygian_dominant().dominant(), Bn);
+        assert_eq!(PitchMode::all(ModeFamily::DoubleHarmonic).len(), 84);
+    }
+
+    #[test]
+    fn test_find_modes() {
+        let found = PitchMode::find(&[En, Fn, Gs, An, Dn]);
+        assert!(found.contains(&PitchGroup::Cn.phrygian_dominant()));
+        assert!(found.iter().all(|mode| mode.tonic() == En));
+        assert!(found.iter().all(|mode| mode.family() != ModeFamily::Diatonic));
+        assert!(PitchMode::find(&[Cn, Dn, En]).contains(&PitchGroup::Cn.ionian()));
+        assert!(PitchMode::find(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_find_families() {
+        assert_eq!(PitchGroup::Cn.spell(ModeFamily::HarmonicMinor), [A(Natural), B(Natural), C(Natural), D(Natural), E(Natural), F(Natural), G(Sharp)]);
+        assert_eq!(PitchGroup::As.spell(ModeFamily::MelodicMinor), [G(Natural), A(Natural), B(Flat), C(Natural), D(Natural), E(Natural), F(Sharp)]);
+        assert_eq!(PitchGroup::Cn.spell(ModeFamily::HarmonicMajor)[5], A(Flat));
+        // A harmonic minor, and E harmonic major with its flat sixth
+        assert_eq!(PitchGroup::find(&[A(Natural), C(Natural), G(Sharp)]), Ok(vec![PitchGroup::Cn, PitchGroup::En]));
+        // D harmonic major, A double harmonic and D harmonic minor all mix a flat and a sharp
+        assert_eq!(PitchGroup::find(&[D(Natural), B(Flat), C(Sharp)]), Ok(vec![PitchGroup::Dn, PitchGroup::An, PitchGroup::Fn]));
+        assert_eq!(PitchGroup::find(&[C(Sharp), D(Flat)]), Err("Both Sharp and Flat"));
+        // Diatonic groups still come first
+        assert_eq!(PitchGroup::find(&[C(Natural), E(Natural), G(Natural)]).unwrap()[0], PitchGroup::Cn);
+    }
+
+    #[test]
+    fn test_split_classes() {
+        // C, Bb and F are the only groups sharing none of F#, G#, C#
+        let (harmonic, dissonant) = PitchGroup::split_set(PitchClassSet::from(&[Fs, Gs, Cs][..]));
+        assert_eq!(dissonant, vec![PitchGroup::Cn, PitchGroup::As, PitchGroup::Fn]);
+        assert_eq!(harmonic.len(), 9);
+        assert_eq!(PitchGroup::split_set(PitchClassSet::EMPTY), (vec![], vec![]));
+        #[allow(deprecated)]
+        let old = PitchGroup::split_classes(vec![Fs, Gs, Cs]);
+        assert_eq!(old, (harmonic, dissonant));
+        assert_eq!(PitchGroup::find(&[C(Natural), D(Natural), A(Natural)]), Ok(vec![PitchGroup::Cn, PitchGroup::Gn, PitchGroup::As, PitchGroup::Fn]));
+    }
 
     #[test]
     fn test_find_pitchgroup() {
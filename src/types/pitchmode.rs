@@ -14,11 +14,66 @@
 // limitations under the License.
 //
 
-use crate::types::PitchClass;
+use crate::types::{Note, PitchClass, PitchGroup};
 use std::fmt;
 
+/// Families of seven note scales whose rotations give a set of modes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ModeFamily {
+    /// Ionian through Locrian.
+    Diatonic,
+    /// Rotations of harmonic minor, e.g. Phrygian Dominant.
+    HarmonicMinor,
+    /// Rotations of ascending melodic minor, e.g. Lydian Dominant and Altered.
+    MelodicMinor,
+    /// Rotations of harmonic major, major with a flat sixth.
+    HarmonicMajor,
+    /// Rotations of double harmonic, e.g. Hungarian Minor.
+    DoubleHarmonic,
+}
+
+impl ModeFamily {
+    pub fn all() -> [ModeFamily; 5] {
+        use ModeFamily::*;
+        [Diatonic, HarmonicMinor, MelodicMinor, HarmonicMajor, DoubleHarmonic]
+    }
+    /// Semitones above the tonic of the first mode.
+    pub fn steps(&self) -> [u8; 7] {
+        match *self {
+            ModeFamily::Diatonic => [0, 2, 4, 5, 7, 9, 11],
+            ModeFamily::HarmonicMinor => [0, 2, 3, 5, 7, 8, 11],
+            ModeFamily::MelodicMinor => [0, 2, 3, 5, 7, 9, 11],
+            ModeFamily::HarmonicMajor => [0, 2, 4, 5, 7, 8, 11],
+            ModeFamily::DoubleHarmonic => [0, 1, 4, 5, 7, 8, 11],
+        }
+    }
+    /// Semitones from a [PitchGroup](audiotheorem::types::PitchGroup) to the tonic of the first mode - the
+    /// minor families sit on the relative minor, so the Cn group holds A harmonic minor and C harmonic major.
+    pub(crate) fn anchor(&self) -> u8 {
+        match *self {
+            ModeFamily::HarmonicMinor | ModeFamily::MelodicMinor => 9,
+            _ => 0,
+        }
+    }
+}
+
+impl fmt::Display for ModeFamily {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ModeFamily::Diatonic => write!(f, "Diatonic"),
+            ModeFamily::HarmonicMinor => write!(f, "Harmonic Minor"),
+            ModeFamily::MelodicMinor => write!(f, "Melodic Minor"),
+            ModeFamily::HarmonicMajor => write!(f, "Harmonic Major"),
+            ModeFamily::DoubleHarmonic => write!(f, "Double Harmonic"),
+        }
+    }
+}
+
 /// Sequence of [PitchClasses](audiotheorem::types::PitchClass) in modal order for a given
 /// [PitchGroup](audiotheorem::types::PitchGroup).
+///
+/// Besides the seven diatonic modes, each rotation of the harmonic minor, melodic minor, harmonic major
+/// and double harmonic scales, see [ModeFamily](audiotheorem::types::ModeFamily).
 #[derive(Copy, Clone, PartialOrd, PartialEq, Debug)]
 pub enum PitchMode {
     Ionian([PitchClass; 7]),
@@ -28,6 +83,38 @@ pub enum PitchMode {
     Mixolydian([PitchClass; 7]),
     Aeolian([PitchClass; 7]),
     Locrian([PitchClass; 7]),
+    // Harmonic Minor
+    HarmonicMinor([PitchClass; 7]),
+    LocrianNatural6([PitchClass; 7]),
+    IonianAugmented([PitchClass; 7]),
+    DorianSharp4([PitchClass; 7]),
+    PhrygianDominant([PitchClass; 7]),
+    LydianSharp2([PitchClass; 7]),
+    Ultralocrian([PitchClass; 7]),
+    // Melodic Minor
+    MelodicMinor([PitchClass; 7]),
+    DorianFlat2([PitchClass; 7]),
+    LydianAugmented([PitchClass; 7]),
+    LydianDominant([PitchClass; 7]),
+    MixolydianFlat6([PitchClass; 7]),
+    LocrianNatural2([PitchClass; 7]),
+    Altered([PitchClass; 7]),
+    // Harmonic Major
+    HarmonicMajor([PitchClass; 7]),
+    DorianFlat5([PitchClass; 7]),
+    PhrygianFlat4([PitchClass; 7]),
+    LydianFlat3([PitchClass; 7]),
+    MixolydianFlat2([PitchClass; 7]),
+    LydianAugmentedSharp2([PitchClass; 7]),
+    LocrianDoubleFlat7([PitchClass; 7]),
+    // Double Harmonic
+    DoubleHarmonic([PitchClass; 7]),
+    LydianSharp2Sharp6([PitchClass; 7]),
+    Ultraphrygian([PitchClass; 7]),
+    HungarianMinor([PitchClass; 7]),
+    Oriental([PitchClass; 7]),
+    IonianAugmentedSharp2([PitchClass; 7]),
+    LocrianDoubleFlat3DoubleFlat7([PitchClass; 7]),
 }
 
 impl PitchMode {
@@ -52,18 +139,202 @@ impl PitchMode {
     pub(crate) const fn locrian(notes: [PitchClass; 7]) -> Self {
         PitchMode::Locrian(notes)
     }
+    // Mode of a family by its rotation, 0 being the family's own scale
+    pub(crate) fn from_family(family: ModeFamily, rotation: usize, notes: [PitchClass; 7]) -> Self {
+        use PitchMode::*;
+        let modes: [fn([PitchClass; 7]) -> PitchMode; 7] = match family {
+            ModeFamily::Diatonic => [Ionian, Dorian, Phrygian, Lydian, Mixolydian, Aeolian, Locrian],
+            ModeFamily::HarmonicMinor => [HarmonicMinor, LocrianNatural6, IonianAugmented, DorianSharp4, PhrygianDominant, LydianSharp2, Ultralocrian],
+            ModeFamily::MelodicMinor => [MelodicMinor, DorianFlat2, LydianAugmented, LydianDominant, MixolydianFlat6, LocrianNatural2, Altered],
+            ModeFamily::HarmonicMajor => [HarmonicMajor, DorianFlat5, PhrygianFlat4, LydianFlat3, MixolydianFlat2, LydianAugmentedSharp2, LocrianDoubleFlat7],
+            ModeFamily::DoubleHarmonic => [DoubleHarmonic, LydianSharp2Sharp6, Ultraphrygian, HungarianMinor, Oriental, IonianAugmentedSharp2, LocrianDoubleFlat3DoubleFlat7],
+        };
+        modes[rotation % 7](notes)
+    }
     pub(crate) fn notes(&self) -> &[PitchClass; 7] {
         match self {
-            PitchMode::Ionian(notes) => notes,
-            PitchMode::Dorian(notes) => notes,
-            PitchMode::Phrygian(notes) => notes,
-            PitchMode::Lydian(notes) => notes,
-            PitchMode::Mixolydian(notes) => notes,
-            PitchMode::Aeolian(notes) => notes,
-            PitchMode::Locrian(notes) => notes,
+            PitchMode::Ionian(notes)
+            | PitchMode::Dorian(notes)
+            | PitchMode::Phrygian(notes)
+            | PitchMode::Lydian(notes)
+            | PitchMode::Mixolydian(notes)
+            | PitchMode::Aeolian(notes)
+            | PitchMode::Locrian(notes) => notes,
+            PitchMode::HarmonicMinor(notes)
+            | PitchMode::LocrianNatural6(notes)
+            | PitchMode::IonianAugmented(notes)
+            | PitchMode::DorianSharp4(notes)
+            | PitchMode::PhrygianDominant(notes)
+            | PitchMode::LydianSharp2(notes)
+            | PitchMode::Ultralocrian(notes) => notes,
+            PitchMode::MelodicMinor(notes)
+            | PitchMode::DorianFlat2(notes)
+            | PitchMode::LydianAugmented(notes)
+            | PitchMode::LydianDominant(notes)
+            | PitchMode::MixolydianFlat6(notes)
+            | PitchMode::LocrianNatural2(notes)
+            | PitchMode::Altered(notes) => notes,
+            PitchMode::HarmonicMajor(notes)
+            | PitchMode::DorianFlat5(notes)
+            | PitchMode::PhrygianFlat4(notes)
+            | PitchMode::LydianFlat3(notes)
+            | PitchMode::MixolydianFlat2(notes)
+            | PitchMode::LydianAugmentedSharp2(notes)
+            | PitchMode::LocrianDoubleFlat7(notes) => notes,
+            PitchMode::DoubleHarmonic(notes)
+            | PitchMode::LydianSharp2Sharp6(notes)
+            | PitchMode::Ultraphrygian(notes)
+            | PitchMode::HungarianMinor(notes)
+            | PitchMode::Oriental(notes)
+            | PitchMode::IonianAugmentedSharp2(notes)
+            | PitchMode::LocrianDoubleFlat3DoubleFlat7(notes) => notes,
+        }
+    }
+    /// [ModeFamily](audiotheorem::types::ModeFamily) this mode is a rotation of.
+    pub fn family(&self) -> ModeFamily {
+        match self {
+            PitchMode::Ionian(_)
+            | PitchMode::Dorian(_)
+            | PitchMode::Phrygian(_)
+            | PitchMode::Lydian(_)
+            | PitchMode::Mixolydian(_)
+            | PitchMode::Aeolian(_)
+            | PitchMode::Locrian(_) => ModeFamily::Diatonic,
+            PitchMode::HarmonicMinor(_)
+            | PitchMode::LocrianNatural6(_)
+            | PitchMode::IonianAugmented(_)
+            | PitchMode::DorianSharp4(_)
+            | PitchMode::PhrygianDominant(_)
+            | PitchMode::LydianSharp2(_)
+            | PitchMode::Ultralocrian(_) => ModeFamily::HarmonicMinor,
+            PitchMode::MelodicMinor(_)
+            | PitchMode::DorianFlat2(_)
+            | PitchMode::LydianAugmented(_)
+            | PitchMode::LydianDominant(_)
+            | PitchMode::MixolydianFlat6(_)
+            | PitchMode::LocrianNatural2(_)
+            | PitchMode::Altered(_) => ModeFamily::MelodicMinor,
+            PitchMode::HarmonicMajor(_)
+            | PitchMode::DorianFlat5(_)
+            | PitchMode::PhrygianFlat4(_)
+            | PitchMode::LydianFlat3(_)
+            | PitchMode::MixolydianFlat2(_)
+            | PitchMode::LydianAugmentedSharp2(_)
+            | PitchMode::LocrianDoubleFlat7(_) => ModeFamily::HarmonicMajor,
+            PitchMode::DoubleHarmonic(_)
+            | PitchMode::LydianSharp2Sharp6(_)
+            | PitchMode::Ultraphrygian(_)
+            | PitchMode::HungarianMinor(_)
+            | PitchMode::Oriental(_)
+            | PitchMode::IonianAugmentedSharp2(_)
+            | PitchMode::LocrianDoubleFlat3DoubleFlat7(_) => ModeFamily::DoubleHarmonic,
+        }
+    }
+    /// Which rotation of its family this mode is, from 0 for the family's own scale to 6.
+    pub fn rotation(&self) -> u8 {
+        match self {
+            PitchMode::Ionian(_)
+            | PitchMode::HarmonicMinor(_)
+            | PitchMode::MelodicMinor(_)
+            | PitchMode::HarmonicMajor(_)
+            | PitchMode::DoubleHarmonic(_) => 0,
+            PitchMode::Dorian(_)
+            | PitchMode::LocrianNatural6(_)
+            | PitchMode::DorianFlat2(_)
+            | PitchMode::DorianFlat5(_)
+            | PitchMode::LydianSharp2Sharp6(_) => 1,
+            PitchMode::Phrygian(_)
+            | PitchMode::IonianAugmented(_)
+            | PitchMode::LydianAugmented(_)
+            | PitchMode::PhrygianFlat4(_)
+            | PitchMode::Ultraphrygian(_) => 2,
+            PitchMode::Lydian(_)
+            | PitchMode::DorianSharp4(_)
+            | PitchMode::LydianDominant(_)
+            | PitchMode::LydianFlat3(_)
+            | PitchMode::HungarianMinor(_) => 3,
+            PitchMode::Mixolydian(_)
+            | PitchMode::PhrygianDominant(_)
+            | PitchMode::MixolydianFlat6(_)
+            | PitchMode::MixolydianFlat2(_)
+            | PitchMode::Oriental(_) => 4,
+            PitchMode::Aeolian(_)
+            | PitchMode::LydianSharp2(_)
+            | PitchMode::LocrianNatural2(_)
+            | PitchMode::LydianAugmentedSharp2(_)
+            | PitchMode::IonianAugmentedSharp2(_) => 5,
+            PitchMode::Locrian(_)
+            | PitchMode::Ultralocrian(_)
+            | PitchMode::Altered(_)
+            | PitchMode::LocrianDoubleFlat7(_)
+            | PitchMode::LocrianDoubleFlat3DoubleFlat7(_) => 6,
+        }
+    }
+    /// Name of the mode, e.g. "Dorian" or "Phrygian Dominant".
+    pub fn name(&self) -> &'static str {
+        match self {
+            PitchMode::Ionian(_) => "Ionian",
+            PitchMode::Dorian(_) => "Dorian",
+            PitchMode::Phrygian(_) => "Phrygian",
+            PitchMode::Lydian(_) => "Lydian",
+            PitchMode::Mixolydian(_) => "Mixolydian",
+            PitchMode::Aeolian(_) => "Aeolian",
+            PitchMode::Locrian(_) => "Locrian",
+            PitchMode::HarmonicMinor(_) => "Harmonic Minor",
+            PitchMode::LocrianNatural6(_) => "Locrian Natural 6",
+            PitchMode::IonianAugmented(_) => "Ionian Augmented",
+            PitchMode::DorianSharp4(_) => "Dorian #4",
+            PitchMode::PhrygianDominant(_) => "Phrygian Dominant",
+            PitchMode::LydianSharp2(_) => "Lydian #2",
+            PitchMode::Ultralocrian(_) => "Ultralocrian",
+            PitchMode::MelodicMinor(_) => "Melodic Minor",
+            PitchMode::DorianFlat2(_) => "Dorian b2",
+            PitchMode::LydianAugmented(_) => "Lydian Augmented",
+            PitchMode::LydianDominant(_) => "Lydian Dominant",
+            PitchMode::MixolydianFlat6(_) => "Mixolydian b6",
+            PitchMode::LocrianNatural2(_) => "Locrian Natural 2",
+            PitchMode::Altered(_) => "Altered",
+            PitchMode::HarmonicMajor(_) => "Harmonic Major",
+            PitchMode::DorianFlat5(_) => "Dorian b5",
+            PitchMode::PhrygianFlat4(_) => "Phrygian b4",
+            PitchMode::LydianFlat3(_) => "Lydian b3",
+            PitchMode::MixolydianFlat2(_) => "Mixolydian b2",
+            PitchMode::LydianAugmentedSharp2(_) => "Lydian Augmented #2",
+            PitchMode::LocrianDoubleFlat7(_) => "Locrian bb7",
+            PitchMode::DoubleHarmonic(_) => "Double Harmonic",
+            PitchMode::LydianSharp2Sharp6(_) => "Lydian #2 #6",
+            PitchMode::Ultraphrygian(_) => "Ultraphrygian",
+            PitchMode::HungarianMinor(_) => "Hungarian Minor",
+            PitchMode::Oriental(_) => "Oriental",
+            PitchMode::IonianAugmentedSharp2(_) => "Ionian #2 #5",
+            PitchMode::LocrianDoubleFlat3DoubleFlat7(_) => "Locrian bb3 bb7",
         }
     }
 
+    /// Every mode of a [ModeFamily](audiotheorem::types::ModeFamily) on every
+    /// [PitchGroup](audiotheorem::types::PitchGroup), 84 in all.
+    pub fn all(family: ModeFamily) -> Vec<PitchMode> {
+        PitchGroup::all().iter().flat_map(|pg| pg.modes(family)).collect()
+    }
+
+    /// Every mode of every [ModeFamily](audiotheorem::types::ModeFamily) holding all of the
+    /// [PitchClasses](audiotheorem::types::PitchClass), named from the first of them as its tonic, so
+    /// E F G# A finds E Phrygian Dominant.
+    pub fn find(pitch_classes: &[PitchClass]) -> Vec<PitchMode> {
+        let Some(tonic) = pitch_classes.first() else { return Vec::new(); };
+        ModeFamily::all().iter()
+            .flat_map(|family| PitchGroup::all().map(|pg| pg.modes(*family)))
+            .filter(|modes| pitch_classes.iter().all(|pc| modes[0].notes().contains(pc)))
+            .filter_map(|modes| modes.into_iter().find(|mode| mode.tonic() == *tonic))
+            .collect()
+    }
+
+    /// [PitchMode::find](audiotheorem::types::PitchMode::find) for [Notes](audiotheorem::types::Note).
+    pub fn find_notes(notes: &[Note]) -> Vec<PitchMode> {
+        let pitch_classes: Vec<PitchClass> = notes.iter().map(|n| n.pitch_class()).collect();
+        PitchMode::find(&pitch_classes)
+    }
+
     // TODO: Migrate all of this to Scale type
     /// Get [PitchClass](audiotheorem::types::PitchClass) in the Tonic position of this
     /// [PitchMode](audiotheorem::types::PitchMode).
@@ -90,28 +361,6 @@ impl PitchMode {
 
 impl fmt::Display for PitchMode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match *self {
-            PitchMode::Ionian(notes) => {
-                format_args!("Ionian({:?})", notes).fmt(f)
-            }
-            PitchMode::Dorian(notes) => {
-                format_args!("Dorian({:?})", notes).fmt(f)
-            }
-            PitchMode::Phrygian(notes) => {
-                format_args!("Phrygian({:?})", notes).fmt(f)
-            }
-            PitchMode::Lydian(notes) => {
-                format_args!("Lydian({:?})", notes).fmt(f)
-            }
-            PitchMode::Mixolydian(notes) => {
-                format_args!("Mixolydian({:?})", notes).fmt(f)
-            }
-            PitchMode::Aeolian(notes) => {
-                format_args!("Aeolian({:?})", notes).fmt(f)
-            }
-            PitchMode::Locrian(notes) => {
-                format_args!("Locrian({:?})", notes).fmt(f)
-            }
-        }
+        format_args!("{}({:?})", self.name().replace(' ', ""), self.notes()).fmt(f)
     }
 }
@@ -0,0 +1,114 @@
+//
+// Copyright 2024 Hans W. Uhlig, Richard I. Christopher. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+
+use super::{Cents, Pitch, Tuning};
+use std::fmt;
+
+/// How finely the 12 octaves from Cn[-1] (~8Hz) to Bn[10] (~32kHz) are divided into bands - each
+/// step up narrows the bands and increases the resolution.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Resolution {
+    /// 1 band per semitone - 144 bands
+    Semitone,
+    /// 5 bands per semitone - 720 bands
+    Subtone,
+    /// 10 bands per semitone - 1440 bands
+    Substep,
+    /// 100 bands per semitone - 14400 bands
+    Cent,
+}
+
+impl Resolution {
+    /// Number of bands in each semitone.
+    pub fn per_semitone(&self) -> usize {
+        match self {
+            Resolution::Semitone => 1,
+            Resolution::Subtone => 5,
+            Resolution::Substep => 10,
+            Resolution::Cent => 100,
+        }
+    }
+    /// Total number of bands across all 144 [Pitches](audiotheorem::types::Pitch).
+    pub fn subdivisions(&self) -> usize { 144 * self.per_semitone() }
+
+    /// Width of a band in [Cents](audiotheorem::types::Cents).
+    pub fn width(&self) -> Cents { Cents::from(100.0 / self.per_semitone() as f64) }
+
+    /// Band containing a [Pitch](audiotheorem::types::Pitch) detuned by [Cents](audiotheorem::types::Cents) - bands are centered on their pitch.
+    pub fn band(&self, pitch: Pitch, cents: Cents) -> Option<usize> {
+        let position = (pitch.to_index() as f64 * 100.0 + cents.cents()) / self.width().cents();
+        let band = (position + 0.5).floor();
+        if band >= 0.0 && (band as usize) < self.subdivisions() { Some(band as usize) } else { None }
+    }
+
+    /// Band containing a frequency in Hz for a given A4 [Tuning](audiotheorem::types::Tuning).
+    pub fn frequency_band(&self, frequency: f64, tuning: Tuning) -> Option<usize> {
+        let a4 = Pitch::from_index(69);
+        self.band(a4, Cents::between(a4.frequency(tuning) as f64, frequency))
+    }
+
+    /// Nearest [Pitch](audiotheorem::types::Pitch) and detune at the center of a band.
+    pub fn center(&self, band: usize) -> Option<(Pitch, Cents)> {
+        if band >= self.subdivisions() { return None; }
+        Pitch::from_index(0).offset(Cents::from(band as f64 * self.width().cents()))
+    }
+}
+
+impl fmt::Display for Resolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Resolution::Semitone => write!(f, "Semitone"),
+            Resolution::Subtone => write!(f, "Subtone"),
+            Resolution::Substep => write!(f, "Substep"),
+            Resolution::Cent => write!(f, "Cent"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subdivisions() {
+        let bands: Vec<usize> = [Resolution::Semitone, Resolution::Subtone, Resolution::Substep, Resolution::Cent]
+            .iter().map(|r| r.subdivisions()).collect();
+        assert_eq!(bands, vec![144, 720, 1440, 14400]);
+    }
+
+    #[test]
+    fn test_bands() {
+        let c4 = Pitch::from_index(60);
+        assert_eq!(Resolution::Semitone.band(c4, Cents::from(0.0)), Some(60));
+        assert_eq!(Resolution::Semitone.band(c4, Cents::from(-49.0)), Some(60));
+        assert_eq!(Resolution::Semitone.band(c4, Cents::from(51.0)), Some(61));
+        assert_eq!(Resolution::Substep.band(c4, Cents::from(-13.69)), Some(599));
+        assert_eq!(Resolution::Cent.band(c4, Cents::from(-13.69)), Some(5986));
+        assert_eq!(Resolution::Cent.band(Pitch::from_index(0), Cents::from(-1.0)), None);
+        assert_eq!(Resolution::Semitone.band(Pitch::from_index(143), Cents::from(60.0)), None);
+    }
+
+    #[test]
+    fn test_frequency_and_center() {
+        assert_eq!(Resolution::Substep.frequency_band(440.0, Tuning::A4_440Hz), Some(690));
+        assert_eq!(Resolution::Cent.frequency_band(660.0, Tuning::A4_440Hz), Some(7602));
+        let (pitch, cents) = Resolution::Subtone.center(302).unwrap();
+        assert_eq!(pitch, Pitch::from_index(60));
+        assert!((cents.cents() - 40.0).abs() < 1e-9);
+        assert_eq!(Resolution::Subtone.center(720), None);
+    }
+}
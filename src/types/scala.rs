@@ -0,0 +1,380 @@
+//
+// Copyright 2024 Hans W. Uhlig, Richard I. Christopher. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+
+use super::{Pitch, Temperament, TuningSystem};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// A pitch line of a Scala scale - cents if written with a period, otherwise a ratio.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ScalaPitch {
+    Cents(f64),
+    Ratio(u64, u64),
+}
+
+impl ScalaPitch {
+    pub fn cents(&self) -> f64 {
+        match self {
+            ScalaPitch::Cents(cents) => *cents,
+            ScalaPitch::Ratio(n, d) => 1200.0 * (*n as f64 / *d as f64).log2(),
+        }
+    }
+
+    fn parse(line: &str) -> Result<ScalaPitch, &'static str> {
+        // Anything after the value is a comment
+        let value = line.split_whitespace().next().ok_or("Missing pitch value")?;
+        if value.contains('.') {
+            return value.parse::<f64>().map(ScalaPitch::Cents).map_err(|_| "Invalid cents value");
+        }
+
+        let (n, d) = value.split_once('/').unwrap_or((value, "1"));
+        match (n.parse::<u64>(), d.parse::<u64>()) {
+            (Ok(n), Ok(d)) if n > 0 && d > 0 => Ok(ScalaPitch::Ratio(n, d)),
+            _ => Err("Invalid ratio value"),
+        }
+    }
+}
+
+impl fmt::Display for ScalaPitch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScalaPitch::Cents(cents) => write!(f, "{:.6}", cents),
+            ScalaPitch::Ratio(n, d) => write!(f, "{}/{}", n, d),
+        }
+    }
+}
+
+// Lines of a Scala file with the comments removed
+fn lines(text: &str) -> impl Iterator<Item = &str> {
+    text.lines().map(|l| l.trim_end_matches('\r')).filter(|l| !l.starts_with('!'))
+}
+
+/// A Scala `.scl` scale - the degrees above 1/1, the last of which is the period (usually 2/1).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScalaScale {
+    pub description: String,
+    pub pitches: Vec<ScalaPitch>,
+}
+
+impl ScalaScale {
+    pub fn parse(text: &str) -> Result<ScalaScale, &'static str> {
+        let mut lines = lines(text);
+        let description = lines.next().ok_or("Missing description")?.trim().to_string();
+        let count = lines.next()
+                         .and_then(|l| l.split_whitespace().next())
+                         .and_then(|c| c.parse::<usize>().ok())
+                         .ok_or("Missing note count")?;
+
+        let pitches = lines.take(count).map(ScalaPitch::parse).collect::<Result<Vec<ScalaPitch>, &'static str>>()?;
+        if pitches.len() != count { return Err("Fewer pitches than the note count"); }
+
+        Ok(ScalaScale { description, pitches })
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<ScalaScale, Box<dyn Error>> {
+        Ok(ScalaScale::parse(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        fs::write(path, self.to_scl())?;
+        Ok(())
+    }
+
+    pub fn to_scl(&self) -> String {
+        let mut text = format!("! audiotheorem\n!\n{}\n {}\n!\n", self.description, self.pitches.len());
+        for pitch in &self.pitches { text += &format!(" {}\n", pitch); }
+        text
+    }
+
+    /// Scale of a [Temperament](audiotheorem::types::Temperament) - just intonation as ratios, EDOs with all of their steps.
+    pub fn from_temperament(temperament: Temperament) -> ScalaScale {
+        const JUST: [(u64, u64); 11] = [(16, 15), (9, 8), (6, 5), (5, 4), (4, 3), (45, 32), (3, 2), (8, 5), (5, 3), (16, 9), (15, 8)];
+
+        let mut pitches: Vec<ScalaPitch> = match temperament {
+            Temperament::Just => JUST.iter().map(|(n, d)| ScalaPitch::Ratio(*n, *d)).collect(),
+            Temperament::Edo(n) => (1..n.max(1)).map(|i| ScalaPitch::Cents(i as f64 * 1200.0 / n as f64)).collect(),
+            _ => temperament.cents()[1..].iter().map(|c| ScalaPitch::Cents(*c)).collect(),
+        };
+        pitches.push(ScalaPitch::Ratio(2, 1));
+
+        ScalaScale { description: temperament.to_string(), pitches }
+    }
+
+    pub fn len(&self) -> usize { self.pitches.len() }
+    pub fn is_empty(&self) -> bool { self.pitches.is_empty() }
+
+    /// Cents of any degree, counting periods above and below - degree 0 is 1/1.
+    pub fn degree_cents(&self, degree: i32) -> f64 {
+        let len = self.pitches.len() as i32;
+        if len == 0 { return 0.0; }
+        let period = self.pitches[len as usize - 1].cents();
+        let step = degree.rem_euclid(len);
+        let base = if step == 0 { 0.0 } else { self.pitches[step as usize - 1].cents() };
+        degree.div_euclid(len) as f64 * period + base
+    }
+}
+
+/// A Scala `.kbm` keyboard mapping - which scale degree each key plays, and which key sounds the reference frequency.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyboardMapping {
+    pub first: u8,
+    pub last: u8,
+    /// Key playing degree 0 of the scale.
+    pub middle: u8,
+    pub reference: u8,
+    pub frequency: f64,
+    /// Degree repeating the mapping pattern - 0 for the size of the scale.
+    pub octave_degree: usize,
+    /// Degree of each key in the pattern, None for unmapped keys. Empty for a linear mapping.
+    pub mapping: Vec<Option<usize>>,
+}
+
+impl KeyboardMapping {
+    /// Every key plays the next degree, with the given key at the reference frequency.
+    pub fn linear(middle: u8, reference: u8, frequency: f64) -> KeyboardMapping {
+        KeyboardMapping { first: 0, last: 143, middle, reference, frequency, octave_degree: 0, mapping: Vec::new() }
+    }
+
+    pub fn parse(text: &str) -> Result<KeyboardMapping, &'static str> {
+        let mut values = lines(text).filter_map(|l| l.split_whitespace().next());
+        let mut next = || values.next().ok_or("Keyboard mapping ended early");
+
+        let size = next()?.parse::<usize>().map_err(|_| "Invalid map size")?;
+        let mut key = |name| next().and_then(|v| v.parse::<u8>().map_err(|_| name));
+        let first = key("Invalid first note")?;
+        let last = key("Invalid last note")?;
+        let middle = key("Invalid middle note")?;
+        let reference = key("Invalid reference note")?;
+        let frequency = next()?.parse::<f64>().map_err(|_| "Invalid reference frequency")?;
+        let octave_degree = next()?.parse::<usize>().map_err(|_| "Invalid octave degree")?;
+
+        let mut mapping = Vec::with_capacity(size);
+        for _ in 0..size {
+            let value = next()?;
+            mapping.push(if value == "x" { None } else { Some(value.parse::<usize>().map_err(|_| "Invalid mapping entry")?) });
+        }
+
+        Ok(KeyboardMapping { first, last, middle, reference, frequency, octave_degree, mapping })
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<KeyboardMapping, Box<dyn Error>> {
+        Ok(KeyboardMapping::parse(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        fs::write(path, self.to_kbm())?;
+        Ok(())
+    }
+
+    pub fn to_kbm(&self) -> String {
+        let mut text = String::from("! audiotheorem\n");
+        text += &format!("! Map size\n{}\n", self.mapping.len());
+        text += &format!("! First and last note\n{}\n{}\n", self.first, self.last);
+        text += &format!("! Middle note\n{}\n", self.middle);
+        text += &format!("! Reference note and frequency\n{}\n{:.6}\n", self.reference, self.frequency);
+        text += &format!("! Formal octave degree\n{}\n", self.octave_degree);
+        text += "! Mapping\n";
+        for entry in &self.mapping {
+            text += &entry.map_or("x".to_string(), |d| d.to_string());
+            text += "\n";
+        }
+        text
+    }
+
+    /// Scale degree played by a key, relative to degree 0 at the middle key.
+    pub fn degree(&self, key: u8, scale: &ScalaScale) -> Option<i32> {
+        if key < self.first || key > self.last { return None; }
+        let distance = key as i32 - self.middle as i32;
+        if self.mapping.is_empty() { return Some(distance); }
+
+        let size = self.mapping.len() as i32;
+        let octave = if self.octave_degree == 0 { scale.len() } else { self.octave_degree } as i32;
+        let degree = self.mapping[distance.rem_euclid(size) as usize]?;
+        Some(distance.div_euclid(size) * octave + degree as i32)
+    }
+}
+
+/// A [ScalaScale] played through a [KeyboardMapping] across the 144 [Pitch](audiotheorem::types::Pitch) indices.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScalaTuning {
+    pub scale: ScalaScale,
+    pub mapping: KeyboardMapping,
+}
+
+impl ScalaTuning {
+    /// A scale with a linear mapping - degree 0 on middle C and A4 at 440Hz.
+    pub fn new(scale: ScalaScale) -> ScalaTuning {
+        ScalaTuning { scale, mapping: KeyboardMapping::linear(60, 69, 440.0) }
+    }
+
+    /// Scale and keyboard mapping matching a [TuningSystem](audiotheorem::types::TuningSystem).
+    pub fn from_tuning_system(system: &TuningSystem) -> ScalaTuning {
+        let scale = ScalaScale::from_temperament(system.temperament);
+
+        // The scale starts on the root, so the root of octave 4 plays degree 0. The reference pitch might not
+        // be mapped, so the root key is pinned to the frequency it has in the tuning system instead
+        let middle = 60 + system.root.to_index();
+        let mut mapping = KeyboardMapping::linear(middle, middle, system.frequency(Pitch::from_index(middle)));
+
+        // Other EDOs map each key to the nearest step, as the temperament does
+        if let Temperament::Edo(n) = system.temperament {
+            if n > 1 && n != 12 {
+                mapping.octave_degree = n as usize;
+                mapping.mapping = (0..12).map(|i| Some((i as f64 * n as f64 / 12.0).round() as usize % n as usize)).collect();
+            }
+        }
+
+        ScalaTuning { scale, mapping }
+    }
+
+    /// Frequency of a [Pitch](audiotheorem::types::Pitch), or None if its key is unmapped.
+    pub fn frequency(&self, pitch: Pitch) -> Option<f64> {
+        let reference = self.mapping.degree(self.mapping.reference, &self.scale)?;
+        let degree = self.mapping.degree(pitch.to_index(), &self.scale)?;
+        Some(self.mapping.frequency * 2f64.powf((self.scale.degree_cents(degree) - self.scale.degree_cents(reference)) / 1200.0))
+    }
+
+    /// Frequencies of all 144 [Pitches](audiotheorem::types::Pitch).
+    pub fn frequencies(&self) -> Vec<Option<f64>> { (0..144).map(|i| self.frequency(Pitch::from_index(i))).collect() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::PitchClass;
+
+    const MEANTONE: &str = "! meanquar.scl
+!
+1/4-comma meantone scale. Pietro Aaron's temperament (1523)
+ 12
+!
+ 76.04900
+ 193.15686
+ 310.26471
+ 5/4
+ 503.42157
+ 579.47057
+ 696.57843
+ 25/16
+ 889.73529
+ 1006.84314
+ 1082.89214
+ 2/1
+";
+
+    const WHITE_KEYS: &str = "! whitekeys.kbm
+! Size of map:
+12
+! First and last MIDI note
+0
+127
+! Middle note
+60
+! Reference note and frequency
+69
+440.0
+! Scale degree of the formal octave
+7
+! Mapping
+0
+x
+1
+x
+2
+3
+x
+4
+x
+5
+x
+6
+";
+
+    #[test]
+    fn test_parse_scl() {
+        let scale = ScalaScale::parse(MEANTONE).unwrap();
+        assert_eq!(scale.description, "1/4-comma meantone scale. Pietro Aaron's temperament (1523)");
+        assert_eq!(scale.len(), 12);
+        assert_eq!(scale.pitches[3], ScalaPitch::Ratio(5, 4));
+        assert_eq!(scale.pitches[0], ScalaPitch::Cents(76.049));
+        assert!((scale.degree_cents(-1) - (1082.89214 - 1200.0)).abs() < 1e-9);
+        assert!((scale.degree_cents(16) - (1200.0 + 386.3137)).abs() < 1e-3);
+
+        assert_eq!(ScalaPitch::parse("3 comment"), Ok(ScalaPitch::Ratio(3, 1)));
+        assert!(ScalaScale::parse("Too short\n3\n1/1\n").is_err());
+        assert!(ScalaPitch::parse("0/2").is_err());
+        assert!(ScalaPitch::parse("a").is_err());
+
+        // Round trip through text
+        assert_eq!(ScalaScale::parse(&scale.to_scl()).unwrap(), scale);
+    }
+
+    #[test]
+    fn test_parse_kbm() {
+        let mapping = KeyboardMapping::parse(WHITE_KEYS).unwrap();
+        assert_eq!((mapping.first, mapping.last, mapping.middle, mapping.reference), (0, 127, 60, 69));
+        assert_eq!(mapping.octave_degree, 7);
+        assert_eq!(mapping.mapping[1], None);
+        assert_eq!(KeyboardMapping::parse(&mapping.to_kbm()).unwrap(), mapping);
+
+        // A 7 note scale on the white keys
+        let diatonic = ScalaScale { description: "Just major".to_string(), pitches: vec![
+            ScalaPitch::Ratio(9, 8), ScalaPitch::Ratio(5, 4), ScalaPitch::Ratio(4, 3), ScalaPitch::Ratio(3, 2),
+            ScalaPitch::Ratio(5, 3), ScalaPitch::Ratio(15, 8), ScalaPitch::Ratio(2, 1)] };
+        let tuning = ScalaTuning { scale: diatonic, mapping };
+        assert_eq!(tuning.frequency(Pitch::from_index(61)), None);
+        assert!((tuning.frequency(Pitch::from_index(69)).unwrap() - 440.0).abs() < 1e-9);
+        assert!((tuning.frequency(Pitch::from_index(60)).unwrap() - 264.0).abs() < 1e-9);
+        assert!((tuning.frequency(Pitch::from_index(48)).unwrap() - 132.0).abs() < 1e-9);
+        assert!(tuning.frequency(Pitch::from_index(130)).is_none());
+    }
+
+    #[test]
+    fn test_round_trip_temperaments() {
+        for temperament in Temperament::all() {
+            for root in [PitchClass::Cn, PitchClass::Fs, PitchClass::As] {
+                let system = TuningSystem::new(temperament, root).with_reference(Pitch::from_index(69), 432.0);
+                let exported = ScalaTuning::from_tuning_system(&system);
+                let imported = ScalaTuning {
+                    scale: ScalaScale::parse(&exported.scale.to_scl()).unwrap(),
+                    mapping: KeyboardMapping::parse(&exported.mapping.to_kbm()).unwrap(),
+                };
+
+                for index in 0..144 {
+                    let pitch = Pitch::from_index(index);
+                    let expected = system.frequency(pitch);
+                    let actual = imported.frequency(pitch).unwrap();
+                    assert!((1200.0 * (actual / expected).log2()).abs() < 1.0, "{} {:?} {} {} {}", temperament, root, index, actual, expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_edo_scale() {
+        let scale = ScalaScale::from_temperament(Temperament::Edo(31));
+        assert_eq!(scale.len(), 31);
+        assert!((scale.degree_cents(10) - 10.0 * 1200.0 / 31.0).abs() < 1e-9);
+
+        // A linear mapping walks every step of the scale
+        let tuning = ScalaTuning::new(scale);
+        let (c, d) = (tuning.frequency(Pitch::from_index(60)).unwrap(), tuning.frequency(Pitch::from_index(91)).unwrap());
+        assert!((d / c - 2.0).abs() < 1e-9);
+    }
+}
@@ -38,12 +38,14 @@ pub mod sequences {
     pub use super::tritonic::TritonicSequence;
 }
 
-use super::{Accidental::*, Degree, Interval, Note, Note::*, PerfectQuality};
-use crate::types::{Form, Tone};
+use super::{Accidental::*, Degree, Interval, Note, Note::*, PerfectQuality, PitchClass, PitchClassSet};
+use crate::types::{Form, ScaleShape, Tone};
+use std::cmp::Reverse;
 use std::fmt;
 use std::fmt::Debug;
+use std::sync::OnceLock;
 
-#[derive(Clone)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
 pub struct Position {
     pub degree: Degree,
     pub note: Note,
@@ -51,7 +53,7 @@ pub struct Position {
 }
 
 /// Scale is a sequence of intervals and a series of notes that match those intervals.
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub enum Scale {
     /// Monotonic has a limited use in liturgy, and for effect in modern art music.
     Monotonic([Position; 1]),
@@ -77,10 +79,48 @@ pub enum Scale {
 
 impl Scale {
     // Added by NeoTec Circa 2024, for the NEXUS Project
+    /// Every [ScaleSequence] built on every root [Note] that can be spelled.
     pub fn all() -> Vec<Scale> {
-       Vec::new()
+        catalog().iter().map(|entry| entry.scale.clone()).collect()
     }
 
+    /// Scales holding the most of `pitch_classes`, then the fewest tones beyond them.
+    pub fn search(pitch_classes: &[PitchClass]) -> Vec<ScaleMatch> {
+        search(pitch_classes.iter().copied(), &[])
+    }
+
+    /// Scales holding the most of `notes`, preferring the ones that spell them the same way.
+    pub fn search_notes(notes: &[Note]) -> Vec<ScaleMatch> {
+        search(notes.iter().map(Note::pitch_class), notes)
+    }
+
+    /// Positions from the root upwards.
+    pub fn positions(&self) -> &[Position] {
+        match self {
+            Scale::Monotonic(positions) => positions,
+            Scale::Ditonic(positions) => positions,
+            Scale::Tritonic(positions) => positions,
+            Scale::Tetratonic(positions) => positions,
+            Scale::Pentatonic(positions) => positions,
+            Scale::Hexatonic(positions) => positions,
+            Scale::Heptatonic(positions) => positions,
+            Scale::Octatonic(positions) => positions,
+            Scale::Nonatonic(positions) => positions,
+            Scale::Chromatic(positions) => positions,
+        }
+    }
+
+    pub fn root(&self) -> Note { self.positions()[0].note }
+
+    pub fn notes(&self) -> Vec<Note> { self.positions().iter().map(|p| p.note).collect() }
+
+    pub fn pitch_classes(&self) -> Vec<PitchClass> {
+        self.positions().iter().map(|p| p.note.pitch_class()).collect()
+    }
+
+    /// [ScaleShape](audiotheorem::types::ScaleShape) of the scale, the semitones above its root.
+    pub fn shape(&self) -> ScaleShape { ScaleShape::from(self) }
+
     pub fn monotonic(root: Note) -> Option<Scale> {
         use self::{Interval::*, PerfectQuality::*};
         Some(Scale::Monotonic([Position {
@@ -447,6 +487,153 @@ impl Scale {
     }
 }
 
+/// Any of the sequences a [Scale] can be built from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ScaleSequence {
+    Monotonic(sequences::MonotonicSequence),
+    Ditonic(sequences::DitonicSequence),
+    Tritonic(sequences::TritonicSequence),
+    Tetratonic(sequences::TetratonicSequence),
+    Pentatonic(sequences::PentatonicSequence),
+    Hexatonic(sequences::HexatonicSequence),
+    Heptatonic(sequences::HeptatonicSequence),
+    Octatonic(sequences::OctatonicSequence),
+    Nonatonic(sequences::NonatonicSequence),
+    Chromatic(sequences::ChromaticSequence),
+}
+
+impl ScaleSequence {
+    pub fn all() -> Vec<ScaleSequence> {
+        let mut all = Vec::new();
+        all.extend(sequences::MonotonicSequence::all().map(ScaleSequence::Monotonic));
+        all.extend(sequences::DitonicSequence::all().map(ScaleSequence::Ditonic));
+        all.extend(sequences::TritonicSequence::all().map(ScaleSequence::Tritonic));
+        all.extend(sequences::TetratonicSequence::all().map(ScaleSequence::Tetratonic));
+        all.extend(sequences::PentatonicSequence::all().map(ScaleSequence::Pentatonic));
+        all.extend(sequences::HexatonicSequence::all().map(ScaleSequence::Hexatonic));
+        all.extend(sequences::HeptatonicSequence::all().map(ScaleSequence::Heptatonic));
+        all.extend(sequences::OctatonicSequence::all().map(ScaleSequence::Octatonic));
+        all.extend(sequences::NonatonicSequence::all().map(ScaleSequence::Nonatonic));
+        all.extend(sequences::ChromaticSequence::all().map(ScaleSequence::Chromatic));
+        all
+    }
+
+    /// Intervals of each position above the root.
+    pub fn intervals(&self) -> &'static [Interval] {
+        match *self {
+            ScaleSequence::Monotonic(sequence) => sequence.intervals(),
+            ScaleSequence::Ditonic(sequence) => sequence.intervals(),
+            ScaleSequence::Tritonic(sequence) => sequence.intervals(),
+            ScaleSequence::Tetratonic(sequence) => sequence.intervals(),
+            ScaleSequence::Pentatonic(sequence) => sequence.intervals(),
+            ScaleSequence::Hexatonic(sequence) => sequence.intervals(),
+            ScaleSequence::Heptatonic(sequence) => sequence.intervals(),
+            ScaleSequence::Octatonic(sequence) => sequence.intervals(),
+            ScaleSequence::Nonatonic(sequence) => sequence.intervals(),
+            ScaleSequence::Chromatic(sequence) => sequence.intervals(),
+        }
+    }
+
+    /// Build this sequence on `root`, if every position can be spelled.
+    pub fn scale(&self, root: Note) -> Option<Scale> {
+        match *self {
+            ScaleSequence::Monotonic(_) => Scale::monotonic(root),
+            ScaleSequence::Ditonic(sequence) => Scale::ditonic(root, sequence),
+            ScaleSequence::Tritonic(sequence) => Scale::tritonic(root, sequence),
+            ScaleSequence::Tetratonic(sequence) => Scale::tetratonic(root, sequence),
+            ScaleSequence::Pentatonic(sequence) => Scale::pentatonic(root, sequence),
+            ScaleSequence::Hexatonic(sequence) => Scale::hexatonic(root, sequence),
+            ScaleSequence::Heptatonic(sequence) => Scale::heptatonic(root, sequence),
+            ScaleSequence::Octatonic(sequence) => Scale::octatonic(root, sequence),
+            ScaleSequence::Nonatonic(sequence) => Scale::nonatonic(root, sequence),
+            ScaleSequence::Chromatic(sequence) => Scale::chromatic(root, sequence),
+        }
+    }
+}
+
+/// A [Scale] found by [Scale::search], with how well it fits what was searched for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScaleMatch {
+    pub sequence: ScaleSequence,
+    pub scale: Scale,
+    /// Searched pitch classes found in the scale
+    pub matched: usize,
+    /// Searched pitch classes not in the scale
+    pub missing: usize,
+    /// Scale pitch classes that were not searched for
+    pub extra: usize,
+    /// Searched notes spelled the same way as in the scale
+    pub spelled: usize,
+}
+
+impl ScaleMatch {
+    /// Share of the searched pitch classes held by the scale, from 0.0 to 1.0.
+    pub fn coverage(&self) -> f32 {
+        match self.matched + self.missing {
+            0 => 0.0,
+            total => self.matched as f32 / total as f32,
+        }
+    }
+
+    /// True if the scale holds everything that was searched for.
+    pub fn complete(&self) -> bool { self.missing == 0 }
+}
+
+struct CatalogEntry {
+    sequence: ScaleSequence,
+    scale: Scale,
+    pitch_classes: PitchClassSet,
+    accidentals: usize,
+}
+
+// Built once - every sequence on every root, skipping the ones that would need triple accidentals
+fn catalog() -> &'static [CatalogEntry] {
+    static CATALOG: OnceLock<Vec<CatalogEntry>> = OnceLock::new();
+    CATALOG.get_or_init(|| {
+        let mut catalog = Vec::new();
+        for sequence in ScaleSequence::all() {
+            for root in Note::all() {
+                if let Some(scale) = sequence.scale(root) {
+                    if scale.notes().iter().any(|n| matches!(n.accidental(), TripleFlat | TripleSharp)) { continue; }
+                    let accidentals = scale.notes().iter().map(|n| match n.accidental() {
+                        DoubleFlat | DoubleSharp => 2,
+                        Flat | Sharp => 1,
+                        Natural => 0,
+                        TripleFlat | TripleSharp => unreachable!(),
+                    }).sum();
+                    let pitch_classes = PitchClassSet::from(&scale.notes()[..]);
+                    catalog.push(CatalogEntry { sequence, pitch_classes, scale, accidentals });
+                }
+            }
+        }
+        catalog
+    })
+}
+
+fn search(pitch_classes: impl Iterator<Item = PitchClass>, notes: &[Note]) -> Vec<ScaleMatch> {
+    let wanted: PitchClassSet = pitch_classes.collect();
+    if wanted.is_empty() { return Vec::new(); }
+
+    let mut ranked: Vec<(usize, ScaleMatch)> = catalog()
+        .iter()
+        .filter(|entry| !entry.pitch_classes.is_disjoint(wanted))
+        .map(|entry| {
+            let scale_notes = entry.scale.notes();
+            (entry.accidentals, ScaleMatch {
+                sequence: entry.sequence,
+                scale: entry.scale.clone(),
+                matched: (entry.pitch_classes & wanted).len(),
+                missing: (wanted - entry.pitch_classes).len(),
+                extra: (entry.pitch_classes - wanted).len(),
+                spelled: notes.iter().filter(|n| scale_notes.contains(n)).count(),
+            })
+        })
+        .collect();
+    // Stable, so ties keep catalog order
+    ranked.sort_by_key(|(accidentals, m)| (Reverse(m.matched), m.extra, Reverse(m.spelled), *accidentals));
+    ranked.into_iter().map(|(_, m)| m).collect()
+}
+
 impl fmt::Debug for Scale {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -499,6 +686,73 @@ mod tests {
     use super::*;
     use crate::types::scale::chromatic::ChromaticSequence;
     use crate::types::scale::heptatonic::HeptatonicSequence;
+    use crate::types::scale::pentatonic::PentatonicSequence;
+
+    #[test]
+    fn test_all_scales() {
+        let all = Scale::all();
+        assert!(all.contains(&Scale::heptatonic(C(Natural), HeptatonicSequence::MajorScale).unwrap()));
+        assert!(all.contains(&Scale::pentatonic(G(Flat), PentatonicSequence::MinorScale).unwrap()));
+        // Nothing that needs more than a double accidental
+        assert!(all.iter().all(|s| s.notes().iter().all(|n| Note::all().contains(n))));
+        let sequences = ScaleSequence::all().len();
+        assert!(all.len() > sequences * 12 && all.len() <= sequences * Note::all().len());
+    }
+
+    #[test]
+    fn test_minor_pentatonic() {
+        let scale = Scale::pentatonic(A(Natural), PentatonicSequence::MinorScale).unwrap();
+        assert_eq!(scale.notes(), vec![A(Natural), C(Natural), D(Natural), E(Natural), G(Natural)]);
+    }
+
+    #[test]
+    fn test_search_notes() {
+        let notes = [C(Natural), D(Natural), E(Natural), F(Natural), G(Natural), A(Natural), B(Natural)];
+        let results = Scale::search_notes(&notes);
+        let best = &results[0];
+        assert!(best.complete());
+        assert_eq!(best.extra, 0);
+        assert_eq!(best.spelled, 7);
+        assert_eq!(best.coverage(), 1.0);
+        assert_eq!(best.sequence, ScaleSequence::Heptatonic(HeptatonicSequence::MajorScale));
+        assert_eq!(best.scale.root(), C(Natural));
+        // Every exact fit comes before anything holding more tones
+        let first_extra = results.iter().position(|m| m.extra > 0).unwrap();
+        assert!(results[first_extra..].iter().all(|m| m.extra > 0 || !m.complete()));
+    }
+
+    #[test]
+    fn test_search_pitch_classes() {
+        let classes: Vec<PitchClass> = [1, 3, 6, 8, 10].iter().map(|i| PitchClass::from_index(*i)).collect();
+        let results = Scale::search(&classes);
+        let exact: Vec<&ScaleMatch> = results.iter().take_while(|m| m.complete() && m.extra == 0).collect();
+        // Major pentatonic on F#/Gb and minor pentatonic on D#/Eb
+        assert_eq!(exact.len(), 4);
+        assert!(exact.iter().all(|m| matches!(m.scale, Scale::Pentatonic(_))));
+        // Fewest accidentals wins the tie between spellings
+        let d_sharp_minor = results.iter().position(|m| m.scale.root() == D(Sharp) && matches!(m.scale, Scale::Pentatonic(_))).unwrap();
+        let c_double_sharp_minor = results.iter().position(|m| m.scale.root() == C(DoubleSharp) && matches!(m.scale, Scale::Pentatonic(_))).unwrap();
+        assert!(d_sharp_minor < c_double_sharp_minor);
+        // Ranked by coverage first
+        assert!(results.windows(2).all(|w| w[0].matched >= w[1].matched));
+        assert!(Scale::search(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_search_spelling() {
+        let results = Scale::search_notes(&[G(Flat), B(Flat), D(Flat)]);
+        let majors: Vec<&ScaleMatch> = results
+            .iter()
+            .filter(|m| m.sequence == ScaleSequence::Heptatonic(HeptatonicSequence::MajorScale))
+            .collect();
+        // Db and Gb major spell all three, F# major only matches them
+        assert_eq!(majors[0].scale.root(), D(Flat));
+        assert_eq!(majors[1].scale.root(), G(Flat));
+        assert_eq!(majors[1].spelled, 3);
+        let f_sharp = majors.iter().find(|m| m.scale.root() == F(Sharp)).unwrap();
+        assert!(f_sharp.complete());
+        assert_eq!(f_sharp.spelled, 0);
+    }
 
     #[test]
     fn test_f_natural_heptatonic_major() {
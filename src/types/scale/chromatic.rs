@@ -19,13 +19,20 @@ use crate::types::{
     PerfectQuality::*,
 };
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum ChromaticSequence {
     SharpScale,
     FlatScale,
 }
 
 impl ChromaticSequence {
+    /// Every [ChromaticSequence].
+    pub fn all() -> [ChromaticSequence; 2] {
+        [
+            ChromaticSequence::SharpScale,
+            ChromaticSequence::FlatScale,
+        ]
+    }
     pub fn intervals(&self) -> &'static [Interval; 12] {
         match *self {
             ChromaticSequence::SharpScale => &SHARP_CHROMATIC_SCALE,
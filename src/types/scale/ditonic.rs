@@ -15,12 +15,18 @@
 //
 use crate::types::{Interval, Interval::*, MajorQuality::*, PerfectQuality::*};
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum DitonicSequence {
     Placeholder,
 }
 
 impl DitonicSequence {
+    /// Every [DitonicSequence].
+    pub fn all() -> [DitonicSequence; 1] {
+        [
+            DitonicSequence::Placeholder,
+        ]
+    }
     pub fn intervals(&self) -> &'static [Interval; 2] {
         match *self {
             DitonicSequence::Placeholder => &PLACEHOLDER_DIATONIC,
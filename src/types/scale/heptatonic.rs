@@ -18,7 +18,7 @@ use crate::types::{
     Interval, Interval::*, MajorQuality::*, PerfectQuality, PerfectQuality::*,
 };
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum HeptatonicSequence {
     MajorScale,
     MinorScale,
@@ -30,6 +30,18 @@ pub enum HeptatonicSequence {
 }
 
 impl HeptatonicSequence {
+    /// Every [HeptatonicSequence].
+    pub fn all() -> [HeptatonicSequence; 7] {
+        [
+            HeptatonicSequence::MajorScale,
+            HeptatonicSequence::MinorScale,
+            HeptatonicSequence::DiminishedScale,
+            HeptatonicSequence::MelodicMinorScale,
+            HeptatonicSequence::HarmonicMinorScale,
+            HeptatonicSequence::BluesScale,
+            HeptatonicSequence::OvertoneScale,
+        ]
+    }
     pub fn intervals(&self) -> &'static [Interval; 7] {
         match *self {
             HeptatonicSequence::MajorScale => &MAJOR_SCALE,
@@ -19,12 +19,18 @@ use crate::types::{
     PerfectQuality::*,
 };
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum HexatonicSequence {
     AugmentedScale,
 }
 
 impl HexatonicSequence {
+    /// Every [HexatonicSequence].
+    pub fn all() -> [HexatonicSequence; 1] {
+        [
+            HexatonicSequence::AugmentedScale,
+        ]
+    }
     pub fn intervals(&self) -> &'static [Interval; 6] {
         match *self {
             HexatonicSequence::AugmentedScale => &AUGMENTED_SCALE,
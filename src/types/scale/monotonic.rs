@@ -16,12 +16,18 @@
 
 use crate::types::{Interval, Interval::*, PerfectQuality::*};
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum MonotonicSequence {
     Placeholder,
 }
 
 impl MonotonicSequence {
+    /// Every [MonotonicSequence].
+    pub fn all() -> [MonotonicSequence; 1] {
+        [
+            MonotonicSequence::Placeholder,
+        ]
+    }
     pub fn intervals(&self) -> &'static [Interval; 1] {
         match *self {
             MonotonicSequence::Placeholder => &PLACEHOLDER_MONOTONIC,
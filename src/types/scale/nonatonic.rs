@@ -16,12 +16,18 @@
 
 use crate::types::{Interval, Interval::*, MajorQuality::*, PerfectQuality::*};
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum NonatonicSequence {
     Blues,
 }
 
 impl NonatonicSequence {
+    /// Every [NonatonicSequence].
+    pub fn all() -> [NonatonicSequence; 1] {
+        [
+            NonatonicSequence::Blues,
+        ]
+    }
     pub fn intervals(&self) -> &'static [Interval; 9] {
         match *self {
             NonatonicSequence::Blues => &NONATONIC_BLUES,
@@ -16,12 +16,18 @@
 
 use crate::types::{Interval, Interval::*, MajorQuality::*, PerfectQuality::*};
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum OctatonicSequence {
     DominantBebop,
 }
 
 impl OctatonicSequence {
+    /// Every [OctatonicSequence].
+    pub fn all() -> [OctatonicSequence; 1] {
+        [
+            OctatonicSequence::DominantBebop,
+        ]
+    }
     pub fn intervals(&self) -> &'static [Interval; 8] {
         match *self {
             OctatonicSequence::DominantBebop => &OCTATONIC_DOMINANT_BEBOP,
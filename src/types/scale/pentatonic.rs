@@ -16,13 +16,20 @@
 
 use crate::types::{Interval, Interval::*, MajorQuality::*, PerfectQuality::*};
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum PentatonicSequence {
     MajorScale,
     MinorScale,
 }
 
 impl PentatonicSequence {
+    /// Every [PentatonicSequence].
+    pub fn all() -> [PentatonicSequence; 2] {
+        [
+            PentatonicSequence::MajorScale,
+            PentatonicSequence::MinorScale,
+        ]
+    }
     pub fn intervals(&self) -> &'static [Interval; 5] {
         match *self {
             PentatonicSequence::MajorScale => &MAJOR_PENTATONIC_SCALE,
@@ -41,8 +48,8 @@ const MAJOR_PENTATONIC_SCALE: [Interval; 5] = [
 ];
 const MINOR_PENTATONIC_SCALE: [Interval; 5] = [
     First(Perfect),
-    Third(Major),
+    Third(Minor),
     Fourth(Perfect),
     Fifth(Perfect),
-    Seventh(Major),
+    Seventh(Minor),
 ];
@@ -19,7 +19,7 @@ use crate::types::{
     PerfectQuality::*,
 };
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum TetratonicSequence {
     Major,
     Minor,
@@ -35,6 +35,22 @@ pub enum TetratonicSequence {
 }
 
 impl TetratonicSequence {
+    /// Every [TetratonicSequence].
+    pub fn all() -> [TetratonicSequence; 11] {
+        [
+            TetratonicSequence::Major,
+            TetratonicSequence::Minor,
+            TetratonicSequence::Phrygian,
+            TetratonicSequence::Wholetone,
+            TetratonicSequence::Diminished,
+            TetratonicSequence::Harmonic,
+            TetratonicSequence::MixolydianBlues,
+            TetratonicSequence::Blues1,
+            TetratonicSequence::Blues2,
+            TetratonicSequence::FlatChromatic,
+            TetratonicSequence::SharpChromatic,
+        ]
+    }
     pub fn intervals(&self) -> &'static [Interval; 4] {
         match *self {
             TetratonicSequence::Major => &MAJOR_TETRA,
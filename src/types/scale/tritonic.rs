@@ -19,7 +19,7 @@ use crate::types::{
     PerfectQuality::*,
 };
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum TritonicSequence {
     MajorTriad,
     MinorTriad,
@@ -32,6 +32,19 @@ pub enum TritonicSequence {
 }
 
 impl TritonicSequence {
+    /// Every [TritonicSequence].
+    pub fn all() -> [TritonicSequence; 8] {
+        [
+            TritonicSequence::MajorTriad,
+            TritonicSequence::MinorTriad,
+            TritonicSequence::DiminishedTriad,
+            TritonicSequence::AugmentedTriad,
+            TritonicSequence::Sus2Triad,
+            TritonicSequence::Sus4Triad,
+            TritonicSequence::Dim3Triad,
+            TritonicSequence::Aug3Triad,
+        ]
+    }
     pub fn intervals(&self) -> &'static [Interval; 3] {
         match *self {
             TritonicSequence::MajorTriad => &MAJOR_TRIAD,
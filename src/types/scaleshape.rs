@@ -0,0 +1,273 @@
+//
+// Copyright 2024 Hans W. Uhlig, Richard I. Christopher. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+
+use super::{Mode, Note, PitchClass, PitchMode, Scale, ScaleSequence, SetClass, Steps};
+use std::fmt;
+use std::sync::OnceLock;
+
+// Messiaen's modes of limited transposition in his own first rotation
+const MESSIAEN: [u16; 7] = [
+    0b0101_0101_0101, // 1 - whole tone
+    0b0110_1101_1011, // 2 - octatonic, half step first
+    0b1101_1101_1101, // 3
+    0b1001_1110_0111, // 4
+    0b1000_1110_0011, // 5
+    0b1101_0111_0101, // 6
+    0b1011_1110_1111, // 7
+];
+
+/// [ScaleShape](audiotheorem::types::ScaleShape) is a scale reduced to the semitones above its root,
+/// one bit each, with the root always present - 2048 shapes in all, from the root alone to the
+/// chromatic scale. Every [ScaleSequence](audiotheorem::types::ScaleSequence), [Mode](audiotheorem::types::Mode)
+/// and [PitchMode](audiotheorem::types::PitchMode) reduces to one.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ScaleShape {
+    // Bit n is the tone n semitones above the root
+    mask: u16,
+}
+
+impl ScaleShape {
+    pub const CHROMATIC: ScaleShape = ScaleShape { mask: 0xFFF };
+    pub const MAJOR: ScaleShape = ScaleShape { mask: 0b1010_1011_0101 };
+
+    /// Shape from a mask of semitones above the root, which must include the root.
+    pub fn new(mask: u16) -> Option<ScaleShape> {
+        (mask < 0x1000 && mask & 1 == 1).then_some(ScaleShape { mask })
+    }
+
+    /// Shape from semitones above the root, taken modulo the octave. The root is always included.
+    pub fn from_semitones(semitones: &[u8]) -> ScaleShape {
+        ScaleShape { mask: semitones.iter().fold(1, |mask, s| mask | 1 << (s % 12)) }
+    }
+
+    /// Shape of [PitchClasses](audiotheorem::types::PitchClass) heard from a root.
+    pub fn from_pitch_classes(root: PitchClass, pitch_classes: &[PitchClass]) -> ScaleShape {
+        let semitones: Vec<u8> = pitch_classes.iter().map(|pc| Steps::distance(&root, pc).value() as u8).collect();
+        ScaleShape::from_semitones(&semitones)
+    }
+
+    /// Every shape, ordered by mask.
+    pub fn all() -> Vec<ScaleShape> {
+        (0..0x1000u16).step_by(2).map(|mask| ScaleShape { mask: mask | 1 }).collect()
+    }
+
+    /// Every shape with a number of tones.
+    pub fn with_cardinality(cardinality: usize) -> Vec<ScaleShape> {
+        ScaleShape::all().into_iter().filter(|s| s.cardinality() == cardinality).collect()
+    }
+
+    pub fn mask(&self) -> u16 { self.mask }
+
+    /// Number of tones including the root.
+    pub fn cardinality(&self) -> usize { self.mask.count_ones() as usize }
+
+    pub fn contains(&self, semitones: u8) -> bool { semitones < 12 && self.mask & 1 << semitones != 0 }
+
+    /// Semitones above the root, in order.
+    pub fn semitones(&self) -> Vec<u8> { (0..12).filter(|s| self.contains(*s)).collect() }
+
+    /// Steps between neighbouring tones, wrapping round to the octave, e.g. 2 2 1 2 2 2 1 for major.
+    pub fn steps(&self) -> Vec<u8> {
+        let semitones = self.semitones();
+        semitones.iter().zip(semitones.iter().skip(1).chain(Some(&12))).map(|(a, b)| b - a).collect()
+    }
+
+    /// [PitchClasses](audiotheorem::types::PitchClass) of this shape on a root.
+    pub fn pitch_classes(&self, root: PitchClass) -> Vec<PitchClass> {
+        self.semitones().iter().map(|s| root + Steps::from(*s as i32)).collect()
+    }
+
+    /// Mode beginning on the tone `degree` places above the root, so the fifth degree (4) of major is Mixolydian.
+    pub fn mode(&self, degree: usize) -> ScaleShape {
+        let semitones = self.semitones();
+        ScaleShape { mask: rotate(self.mask, semitones[degree % semitones.len()]) }
+    }
+
+    /// Every mode from the root upwards, one per tone, so symmetric shapes repeat.
+    pub fn modes(&self) -> Vec<ScaleShape> {
+        (0..self.cardinality()).map(|degree| self.mode(degree)).collect()
+    }
+
+    /// Tones the shape leaves out, as a shape on the lowest of them. None for the chromatic scale.
+    pub fn complement(&self) -> Option<ScaleShape> {
+        let missing = !self.mask & 0xFFF;
+        (missing != 0).then(|| ScaleShape { mask: rotate(missing, missing.trailing_zeros() as u8) })
+    }
+
+    /// Shape turned upside down, the steps read from the top - Phrygian for Ionian.
+    pub fn inversion(&self) -> ScaleShape {
+        ScaleShape { mask: (0..12).filter(|s| self.contains(*s)).fold(0, |mask, s| mask | 1 << ((12 - s) % 12)) }
+    }
+
+    /// Number of distinct transpositions, 12 unless the shape repeats within the octave.
+    pub fn transpositions(&self) -> u8 {
+        (1..=12).find(|n| rotate(self.mask, *n % 12) == self.mask).unwrap()
+    }
+
+    /// True if the shape repeats within the octave, as the whole tone and octatonic scales do.
+    pub fn is_symmetric(&self) -> bool { self.transpositions() < 12 }
+
+    /// True if some mode of the shape reads the same upside down, as Dorian does.
+    pub fn is_inversionally_symmetric(&self) -> bool {
+        let inversion = self.inversion();
+        self.modes().contains(&inversion)
+    }
+
+    /// Number of Messiaen's mode of limited transposition this shape is a mode of, 1 to 7.
+    pub fn messiaen(&self) -> Option<u8> {
+        let modes = self.modes();
+        MESSIAEN.iter().position(|mask| modes.iter().any(|m| m.mask == *mask)).map(|i| i as u8 + 1)
+    }
+
+    /// [SetClass](audiotheorem::types::SetClass) the shape belongs to, shared by all its modes.
+    pub fn set_class(&self) -> SetClass { SetClass::of(&self.pitch_classes(PitchClass::Cn)) }
+
+    /// Every [ScaleSequence](audiotheorem::types::ScaleSequence) with this shape.
+    pub fn sequences(&self) -> Vec<ScaleSequence> {
+        ScaleSequence::all().into_iter().filter(|s| ScaleShape::from(*s) == *self).collect()
+    }
+
+    /// [Scale](audiotheorem::types::Scale) of this shape on a root, using the first
+    /// [ScaleSequence](audiotheorem::types::ScaleSequence) that spells it. None for shapes no sequence names.
+    pub fn scale(&self, root: Note) -> Option<Scale> {
+        self.sequences().iter().find_map(|sequence| sequence.scale(root))
+    }
+
+    /// Every shape of a named [ScaleSequence](audiotheorem::types::ScaleSequence), computed once.
+    pub fn named() -> &'static [ScaleShape] {
+        static NAMED: OnceLock<Vec<ScaleShape>> = OnceLock::new();
+        NAMED.get_or_init(|| {
+            let mut named: Vec<ScaleShape> = ScaleSequence::all().into_iter().map(ScaleShape::from).collect();
+            named.sort();
+            named.dedup();
+            named
+        })
+    }
+}
+
+// Move the root up by some semitones, keeping the mask within the octave
+fn rotate(mask: u16, semitones: u8) -> u16 {
+    let semitones = semitones % 12;
+    ((mask >> semitones) | (mask << (12 - semitones))) & 0xFFF
+}
+
+impl From<ScaleSequence> for ScaleShape {
+    fn from(sequence: ScaleSequence) -> ScaleShape {
+        let semitones: Vec<u8> = sequence.intervals().iter().map(|i| i.semitones().rem_euclid(12) as u8).collect();
+        ScaleShape::from_semitones(&semitones)
+    }
+}
+
+impl From<&Scale> for ScaleShape {
+    fn from(scale: &Scale) -> ScaleShape {
+        ScaleShape::from_pitch_classes(scale.root().pitch_class(), &scale.pitch_classes())
+    }
+}
+
+impl From<Mode> for ScaleShape {
+    fn from(mode: Mode) -> ScaleShape {
+        let semitones: Vec<u8> = mode.intervals().iter().map(|i| i.semitones().rem_euclid(12) as u8).collect();
+        ScaleShape::from_semitones(&semitones)
+    }
+}
+
+impl From<PitchMode> for ScaleShape {
+    fn from(mode: PitchMode) -> ScaleShape {
+        ScaleShape::from_pitch_classes(mode.tonic(), mode.notes())
+    }
+}
+
+/// Semitones above the root, e.g. "(0 2 4 5 7 9 11)".
+impl fmt::Display for ScaleShape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let semitones: Vec<String> = self.semitones().iter().map(|s| s.to_string()).collect();
+        write!(f, "({})", semitones.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ScaleShape;
+    use crate::types::sequences::{HeptatonicSequence, PentatonicSequence};
+    use crate::types::{Mode, Note, PitchGroup, ScaleSequence};
+
+    #[test]
+    fn test_all() {
+        let all = ScaleShape::all();
+        assert_eq!(all.len(), 2048);
+        assert!(all.iter().all(|s| s.contains(0)));
+        assert_eq!(ScaleShape::with_cardinality(7).len(), 462);
+        assert_eq!(ScaleShape::new(0b10), None);
+        assert_eq!(ScaleShape::from_semitones(&[2, 4, 5, 7, 9, 11]), ScaleShape::MAJOR);
+        assert_eq!(ScaleShape::MAJOR.steps(), vec![2, 2, 1, 2, 2, 2, 1]);
+        assert_eq!(ScaleShape::MAJOR.to_string(), "(0 2 4 5 7 9 11)");
+    }
+
+    #[test]
+    fn test_modes() {
+        let modes = ScaleShape::MAJOR.modes();
+        let expected = [Mode::Ionian, Mode::Dorian, Mode::Phrygian, Mode::Lydian, Mode::Mixolydian, Mode::Aeolian, Mode::Locrian];
+        assert_eq!(modes, expected.map(ScaleShape::from).to_vec());
+        assert_eq!(ScaleShape::MAJOR.inversion(), ScaleShape::from(Mode::Phrygian));
+        assert!(ScaleShape::from(Mode::Dorian).is_inversionally_symmetric());
+        let harmonic = ScaleShape::from(PitchGroup::Cn.harmonic_minor());
+        assert_eq!(harmonic.mode(4), ScaleShape::from(PitchGroup::Cn.phrygian_dominant()));
+    }
+
+    #[test]
+    fn test_complement() {
+        let pentatonic = ScaleShape::from(ScaleSequence::Pentatonic(PentatonicSequence::MajorScale));
+        // The black keys from C# are the major pentatonic from its fourth tone
+        assert_eq!(ScaleShape::MAJOR.complement(), Some(pentatonic.mode(3)));
+        assert_eq!(pentatonic.complement().unwrap().set_class(), ScaleShape::MAJOR.set_class());
+        assert_eq!(ScaleShape::CHROMATIC.complement(), None);
+    }
+
+    #[test]
+    fn test_limited_transposition() {
+        let whole = ScaleShape::from_semitones(&[0, 2, 4, 6, 8, 10]);
+        assert_eq!(whole.transpositions(), 2);
+        assert_eq!(whole.messiaen(), Some(1));
+        let octatonic = ScaleShape::from_semitones(&[0, 1, 3, 4, 6, 7, 9, 10]);
+        assert_eq!(octatonic.transpositions(), 3);
+        assert_eq!(octatonic.messiaen(), Some(2));
+        assert_eq!(ScaleShape::MAJOR.transpositions(), 12);
+        assert_eq!(ScaleShape::MAJOR.messiaen(), None);
+        assert!(!ScaleShape::MAJOR.is_symmetric());
+        let limited: Vec<ScaleShape> = ScaleShape::all().into_iter().filter(|s| s.messiaen().is_some()).collect();
+        // Messiaen's seven modes have 1 + 2 + 3 + 4 + 3 + 4 + 5 distinct rotations between them
+        assert_eq!(limited.len(), 22);
+        assert!(limited.iter().all(|s| s.is_symmetric()));
+    }
+
+    #[test]
+    fn test_conversions() {
+        let major = ScaleSequence::Heptatonic(HeptatonicSequence::MajorScale);
+        let shape = ScaleShape::from(major);
+        assert_eq!(shape, ScaleShape::MAJOR);
+        assert!(shape.sequences().contains(&major));
+        let scale = shape.scale("D".parse::<Note>().unwrap()).unwrap();
+        assert_eq!(ScaleShape::from(&scale), shape);
+        assert!(ScaleShape::named().contains(&ScaleShape::MAJOR));
+        assert!(ScaleShape::named().len() < 2048);
+        for sequence in ScaleSequence::all() {
+            if let Some(scale) = sequence.scale("C".parse().unwrap()) {
+                assert_eq!(ScaleShape::from(&scale), ScaleShape::from(sequence));
+            }
+        }
+    }
+}
@@ -0,0 +1,501 @@
+//
+// Copyright 2024 Hans W. Uhlig, Richard I. Christopher. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use super::{PitchClass, PitchClassSet, Steps};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+/// [SetClass](audiotheorem::types::SetClass) is every transposition (T<sub>n</sub>) and inversion
+/// (T<sub>n</sub>I) of a collection of [PitchClasses](audiotheorem::types::PitchClass), named by
+/// its prime form and Forte number.
+///
+/// Prime forms follow Rahn, packing to the left from the last pitch class, which differs from
+/// Forte's own list for 5-20, 6-Z29, 6-31, 7-Z18, 7-20 and 8-26. Carter's Harmony Book numbers
+/// are not provided yet.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SetClass {
+    // One bit per pitch class of the prime form, always including 0 unless empty
+    prime: u16,
+}
+
+impl SetClass {
+    /// Set class of a collection, duplicates are ignored.
+    pub fn of(pitch_classes: &[PitchClass]) -> SetClass {
+        SetClass { prime: prime(PitchClassSet::from(pitch_classes)) }
+    }
+
+    /// All 224 set classes, from the empty set to the aggregate, in Forte order.
+    pub fn all() -> &'static [SetClass] {
+        static ALL: OnceLock<Vec<SetClass>> = OnceLock::new();
+        ALL.get_or_init(|| {
+            let mut all: Vec<SetClass> = (0..0x1000u16)
+                .filter(|m| prime(PitchClassSet::from_mask(*m)) == *m)
+                .map(|prime| SetClass { prime })
+                .collect();
+            all.sort_by_key(|s| (s.cardinality(), s.forte_number().map(|(n, _)| n), s.prime));
+            all
+        })
+    }
+
+    /// Prime form, starting on [PitchClass::Cn](audiotheorem::types::PitchClass::Cn).
+    pub fn prime_form(&self) -> Vec<PitchClass> { self.pitch_classes().iter().collect() }
+
+    pub fn cardinality(&self) -> usize { self.prime.count_ones() as usize }
+
+    // Pitch classes of the prime form
+    fn pitch_classes(&self) -> PitchClassSet { PitchClassSet::from_mask(self.prime) }
+
+    /// Number of each interval class from the minor second to the tritone between every pair.
+    pub fn interval_vector(&self) -> [u8; 6] {
+        let classes = self.prime_form();
+        let mut vector = [0; 6];
+        for (i, a) in classes.iter().enumerate() {
+            for b in &classes[i + 1..] {
+                let steps = Steps::distance(a, b).value();
+                vector[steps.min(12 - steps) as usize - 1] += 1;
+            }
+        }
+        vector
+    }
+
+    // TODO: Carter's Harmony Book numbers alongside the Forte names
+
+    /// Forte name such as "3-11" or "4-Z15", none for the empty set.
+    pub fn forte(&self) -> Option<String> {
+        let (number, z) = self.forte_number()?;
+        Some(format!("{}-{}{}", self.cardinality(), if z { "Z" } else { "" }, number))
+    }
+
+    // Ordinal in Forte's list and whether it is Z-related
+    fn forte_number(&self) -> Option<(u8, bool)> {
+        match self.cardinality() {
+            0 => None,
+            1 | 11 | 12 => Some((1, false)),
+            2 => Some((self.interval_vector().iter().position(|&ic| ic > 0)? as u8 + 1, false)),
+            3..=6 => FORTE
+                .iter()
+                .find(|(_, prime)| set_of(prime) == self.pitch_classes())
+                .map(|(name, _)| parse_forte(name).map(|(_, number, z)| (number, z)))?,
+            _ => self.complement().forte_number(),
+        }
+    }
+
+    /// Set class of the pitch classes left out by the prime form.
+    pub fn complement(&self) -> SetClass { SetClass { prime: prime(self.pitch_classes().complement()) } }
+
+    /// Other set class sharing this interval vector, if there is one.
+    pub fn z_related(&self) -> Option<SetClass> {
+        let vector = self.interval_vector();
+        SetClass::all()
+            .iter()
+            .find(|s| *s != self && s.cardinality() == self.cardinality() && s.interval_vector() == vector)
+            .copied()
+    }
+
+    /// True if an inversion of the set maps onto itself.
+    pub fn symmetric(&self) -> bool {
+        let set = self.pitch_classes();
+        (0..12).any(|n| set.invert().transpose(Steps::from(n)) == set)
+    }
+
+    /// Every distinct T<sub>n</sub> and T<sub>n</sub>I form, 12 or 24 unless the set is symmetrical.
+    pub fn forms(&self) -> Vec<Vec<PitchClass>> {
+        let set = self.pitch_classes();
+        let mut forms: Vec<PitchClassSet> = Vec::new();
+        for n in 0..12 {
+            for form in [set.transpose(Steps::from(n)), set.invert().transpose(Steps::from(n))] {
+                if !forms.contains(&form) { forms.push(form); }
+            }
+        }
+        forms.into_iter().map(|form| form.iter().collect()).collect()
+    }
+
+    /// Forms of this set class found within `pitch_classes`.
+    pub fn occurrences(&self, pitch_classes: &[PitchClass]) -> Vec<Vec<PitchClass>> {
+        let within = PitchClassSet::from(pitch_classes);
+        self.forms().into_iter().filter(|form| PitchClassSet::from(&form[..]).is_subset(within)).collect()
+    }
+
+    /// True if some form of this set class is contained in some form of `other`.
+    pub fn is_subset_of(&self, other: &SetClass) -> bool {
+        self.cardinality() <= other.cardinality() && !self.occurrences(&other.prime_form()).is_empty()
+    }
+
+    pub fn is_superset_of(&self, other: &SetClass) -> bool { other.is_subset_of(self) }
+
+    /// Set classes held within this one, smallest first.
+    pub fn subsets(&self) -> Vec<SetClass> {
+        SetClass::all().iter().filter(|s| *s != self && s.is_subset_of(self)).copied().collect()
+    }
+
+    /// Set classes holding this one, smallest first.
+    pub fn supersets(&self) -> Vec<SetClass> {
+        SetClass::all().iter().filter(|s| *s != self && self.is_subset_of(s)).copied().collect()
+    }
+
+    /// Most compact ordering of a collection, ties broken from the right as in the prime form.
+    pub fn normal_form(pitch_classes: &[PitchClass]) -> Vec<PitchClass> {
+        let sorted: Vec<u8> = PitchClassSet::from(pitch_classes).iter().map(|pc| pc.to_index()).collect();
+        let span = |start: usize| -> Vec<u8> {
+            (1..sorted.len()).rev().map(|k| (sorted[(start + k) % sorted.len()] + 12 - sorted[start]) % 12).collect()
+        };
+        let start = (0..sorted.len()).min_by_key(|&start| span(start)).unwrap_or(0);
+        (0..sorted.len())
+            .map(|k| PitchClass::from_index(sorted[(start + k) % sorted.len()]))
+            .collect()
+    }
+
+    /// T<sub>n</sub> - every pitch class moved up by `n`.
+    pub fn transpose(pitch_classes: &[PitchClass], n: Steps) -> Vec<PitchClass> {
+        pitch_classes.iter().map(|pc| *pc + n).collect()
+    }
+
+    /// T<sub>n</sub>I - every pitch class inverted around C, then moved up by `n`.
+    pub fn invert(pitch_classes: &[PitchClass], n: Steps) -> Vec<PitchClass> {
+        pitch_classes.iter().map(|pc| PitchClass::Cn + (n - Steps::from(pc.to_index() as i32))).collect()
+    }
+}
+
+impl FromStr for SetClass {
+    type Err = &'static str;
+
+    /// Parses a Forte name ("4-Z15") or a prime form ("(0146)", "[0,1,4,6]", using T and E for 10 and 11).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some((cardinality, number, z)) = parse_forte(s) {
+            let set = SetClass::all()
+                .iter()
+                .find(|set| set.cardinality() == cardinality as usize && set.forte_number().map(|(n, _)| n) == Some(number))
+                .ok_or("Unknown Forte number")?;
+            // The Z belongs to the name, "4-15" is not a set class
+            return if set.forte_number().map(|(_, z)| z) == Some(z) { Ok(*set) } else { Err("Wrong Z flag for this Forte number") };
+        }
+        let mut classes = Vec::new();
+        for c in s.trim_start_matches(['(', '[', '{']).trim_end_matches([')', ']', '}']).chars() {
+            match c {
+                '0'..='9' => classes.push(PitchClass::from_index(c as u8 - b'0')),
+                'T' | 't' | 'A' | 'a' => classes.push(PitchClass::As),
+                'E' | 'e' | 'B' | 'b' => classes.push(PitchClass::Bn),
+                ',' | ' ' => {}
+                _ => return Err("Expected a Forte name or a prime form"),
+            }
+        }
+        if classes.is_empty() { return Err("Expected a Forte name or a prime form"); }
+        Ok(SetClass::of(&classes))
+    }
+}
+
+impl fmt::Display for SetClass {
+    /// Forte name, or the prime form with `{:#}` and for the empty set.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.forte() {
+            Some(name) if !f.alternate() => write!(f, "{}", name),
+            _ => {
+                write!(f, "(")?;
+                for pc in self.prime_form() {
+                    match pc.to_index() {
+                        10 => write!(f, "T")?,
+                        11 => write!(f, "E")?,
+                        index => write!(f, "{}", index)?,
+                    }
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+impl fmt::Debug for SetClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "SetClass({} {:#})", self, self) }
+}
+
+// Pitch classes from the indices in the Forte table
+fn set_of(indices: &[u8]) -> PitchClassSet { indices.iter().map(|i| PitchClass::from_index(*i)).collect() }
+
+// Smallest transposition or inversion that includes 0 - the highest pitch class weighs the most,
+// so the smallest value is the one most packed from the right
+fn prime(set: PitchClassSet) -> u16 {
+    (0..12)
+        .flat_map(|n| [set.transpose(Steps::from(n)), set.invert().transpose(Steps::from(n))])
+        .map(|form| form.mask())
+        .filter(|m| *m == 0 || m & 1 != 0)
+        .min()
+        .unwrap_or(0)
+}
+
+// "4-Z15" into (4, 15, true)
+fn parse_forte(name: &str) -> Option<(u8, u8, bool)> {
+    let (cardinality, number) = name.split_once('-')?;
+    let (z, number) = match number.strip_prefix(['Z', 'z']) {
+        Some(number) => (true, number),
+        None => (false, number),
+    };
+    Some((cardinality.parse().ok()?, number.parse().ok()?, z))
+}
+
+// Forte's list up to the hexachords - larger sets are named after their complements
+const FORTE: [(&str, &[u8]); 129] = [
+    ("3-1", &[0, 1, 2]),
+    ("3-2", &[0, 1, 3]),
+    ("3-3", &[0, 1, 4]),
+    ("3-4", &[0, 1, 5]),
+    ("3-5", &[0, 1, 6]),
+    ("3-6", &[0, 2, 4]),
+    ("3-7", &[0, 2, 5]),
+    ("3-8", &[0, 2, 6]),
+    ("3-9", &[0, 2, 7]),
+    ("3-10", &[0, 3, 6]),
+    ("3-11", &[0, 3, 7]),
+    ("3-12", &[0, 4, 8]),
+    ("4-1", &[0, 1, 2, 3]),
+    ("4-2", &[0, 1, 2, 4]),
+    ("4-3", &[0, 1, 3, 4]),
+    ("4-4", &[0, 1, 2, 5]),
+    ("4-5", &[0, 1, 2, 6]),
+    ("4-6", &[0, 1, 2, 7]),
+    ("4-7", &[0, 1, 4, 5]),
+    ("4-8", &[0, 1, 5, 6]),
+    ("4-9", &[0, 1, 6, 7]),
+    ("4-10", &[0, 2, 3, 5]),
+    ("4-11", &[0, 1, 3, 5]),
+    ("4-12", &[0, 2, 3, 6]),
+    ("4-13", &[0, 1, 3, 6]),
+    ("4-14", &[0, 2, 3, 7]),
+    ("4-Z15", &[0, 1, 4, 6]),
+    ("4-16", &[0, 1, 5, 7]),
+    ("4-17", &[0, 3, 4, 7]),
+    ("4-18", &[0, 1, 4, 7]),
+    ("4-19", &[0, 1, 4, 8]),
+    ("4-20", &[0, 1, 5, 8]),
+    ("4-21", &[0, 2, 4, 6]),
+    ("4-22", &[0, 2, 4, 7]),
+    ("4-23", &[0, 2, 5, 7]),
+    ("4-24", &[0, 2, 4, 8]),
+    ("4-25", &[0, 2, 6, 8]),
+    ("4-26", &[0, 3, 5, 8]),
+    ("4-27", &[0, 2, 5, 8]),
+    ("4-28", &[0, 3, 6, 9]),
+    ("4-Z29", &[0, 1, 3, 7]),
+    ("5-1", &[0, 1, 2, 3, 4]),
+    ("5-2", &[0, 1, 2, 3, 5]),
+    ("5-3", &[0, 1, 2, 4, 5]),
+    ("5-4", &[0, 1, 2, 3, 6]),
+    ("5-5", &[0, 1, 2, 3, 7]),
+    ("5-6", &[0, 1, 2, 5, 6]),
+    ("5-7", &[0, 1, 2, 6, 7]),
+    ("5-8", &[0, 2, 3, 4, 6]),
+    ("5-9", &[0, 1, 2, 4, 6]),
+    ("5-10", &[0, 1, 3, 4, 6]),
+    ("5-11", &[0, 2, 3, 4, 7]),
+    ("5-Z12", &[0, 1, 3, 5, 6]),
+    ("5-13", &[0, 1, 2, 4, 8]),
+    ("5-14", &[0, 1, 2, 5, 7]),
+    ("5-15", &[0, 1, 2, 6, 8]),
+    ("5-16", &[0, 1, 3, 4, 7]),
+    ("5-Z17", &[0, 1, 3, 4, 8]),
+    ("5-Z18", &[0, 1, 4, 5, 7]),
+    ("5-19", &[0, 1, 3, 6, 7]),
+    ("5-20", &[0, 1, 5, 6, 8]),
+    ("5-21", &[0, 1, 4, 5, 8]),
+    ("5-22", &[0, 1, 4, 7, 8]),
+    ("5-23", &[0, 2, 3, 5, 7]),
+    ("5-24", &[0, 1, 3, 5, 7]),
+    ("5-25", &[0, 2, 3, 5, 8]),
+    ("5-26", &[0, 2, 4, 5, 8]),
+    ("5-27", &[0, 1, 3, 5, 8]),
+    ("5-28", &[0, 2, 3, 6, 8]),
+    ("5-29", &[0, 1, 3, 6, 8]),
+    ("5-30", &[0, 1, 4, 6, 8]),
+    ("5-31", &[0, 1, 3, 6, 9]),
+    ("5-32", &[0, 1, 4, 6, 9]),
+    ("5-33", &[0, 2, 4, 6, 8]),
+    ("5-34", &[0, 2, 4, 6, 9]),
+    ("5-35", &[0, 2, 4, 7, 9]),
+    ("5-Z36", &[0, 1, 2, 4, 7]),
+    ("5-Z37", &[0, 3, 4, 5, 8]),
+    ("5-Z38", &[0, 1, 2, 5, 8]),
+    ("6-1", &[0, 1, 2, 3, 4, 5]),
+    ("6-2", &[0, 1, 2, 3, 4, 6]),
+    ("6-Z3", &[0, 1, 2, 3, 5, 6]),
+    ("6-Z4", &[0, 1, 2, 4, 5, 6]),
+    ("6-5", &[0, 1, 2, 3, 6, 7]),
+    ("6-Z6", &[0, 1, 2, 5, 6, 7]),
+    ("6-7", &[0, 1, 2, 6, 7, 8]),
+    ("6-8", &[0, 2, 3, 4, 5, 7]),
+    ("6-9", &[0, 1, 2, 3, 5, 7]),
+    ("6-Z10", &[0, 1, 3, 4, 5, 7]),
+    ("6-Z11", &[0, 1, 2, 4, 5, 7]),
+    ("6-Z12", &[0, 1, 2, 4, 6, 7]),
+    ("6-Z13", &[0, 1, 3, 4, 6, 7]),
+    ("6-14", &[0, 1, 3, 4, 5, 8]),
+    ("6-15", &[0, 1, 2, 4, 5, 8]),
+    ("6-16", &[0, 1, 4, 5, 6, 8]),
+    ("6-Z17", &[0, 1, 2, 4, 7, 8]),
+    ("6-18", &[0, 1, 2, 5, 7, 8]),
+    ("6-Z19", &[0, 1, 3, 4, 7, 8]),
+    ("6-20", &[0, 1, 4, 5, 8, 9]),
+    ("6-21", &[0, 2, 3, 4, 6, 8]),
+    ("6-22", &[0, 1, 2, 4, 6, 8]),
+    ("6-Z23", &[0, 2, 3, 5, 6, 8]),
+    ("6-Z24", &[0, 1, 3, 4, 6, 8]),
+    ("6-Z25", &[0, 1, 3, 5, 6, 8]),
+    ("6-Z26", &[0, 1, 3, 5, 7, 8]),
+    ("6-27", &[0, 1, 3, 4, 6, 9]),
+    ("6-Z28", &[0, 1, 3, 5, 6, 9]),
+    ("6-Z29", &[0, 2, 3, 6, 7, 9]),
+    ("6-30", &[0, 1, 3, 6, 7, 9]),
+    ("6-31", &[0, 1, 4, 5, 7, 9]),
+    ("6-32", &[0, 2, 4, 5, 7, 9]),
+    ("6-33", &[0, 2, 3, 5, 7, 9]),
+    ("6-34", &[0, 1, 3, 5, 7, 9]),
+    ("6-35", &[0, 2, 4, 6, 8, 10]),
+    ("6-Z36", &[0, 1, 2, 3, 4, 7]),
+    ("6-Z37", &[0, 1, 2, 3, 4, 8]),
+    ("6-Z38", &[0, 1, 2, 3, 7, 8]),
+    ("6-Z39", &[0, 2, 3, 4, 5, 8]),
+    ("6-Z40", &[0, 1, 2, 3, 5, 8]),
+    ("6-Z41", &[0, 1, 2, 3, 6, 8]),
+    ("6-Z42", &[0, 1, 2, 3, 6, 9]),
+    ("6-Z43", &[0, 1, 2, 5, 6, 8]),
+    ("6-Z44", &[0, 1, 2, 5, 6, 9]),
+    ("6-Z45", &[0, 2, 3, 4, 6, 9]),
+    ("6-Z46", &[0, 1, 2, 4, 6, 9]),
+    ("6-Z47", &[0, 1, 2, 4, 7, 9]),
+    ("6-Z48", &[0, 1, 2, 5, 7, 9]),
+    ("6-Z49", &[0, 1, 3, 4, 7, 9]),
+    ("6-Z50", &[0, 1, 4, 6, 7, 9]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::PitchClass::*;
+
+    #[test]
+    fn test_forte_table() {
+        for (name, prime) in FORTE.iter() {
+            let set = SetClass::of(&prime.iter().map(|i| PitchClass::from_index(*i)).collect::<Vec<_>>());
+            assert_eq!(set.prime, set_of(prime).mask(), "{} is not in prime form", name);
+            assert_eq!(set.forte().as_deref(), Some(*name));
+            // Z marks exactly the sets that share an interval vector
+            assert_eq!(set.z_related().is_some(), name.contains('Z'), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_all() {
+        let all = SetClass::all();
+        assert_eq!(all.len(), 224);
+        let counts: Vec<usize> = (0..=12).map(|n| all.iter().filter(|s| s.cardinality() == n).count()).collect();
+        assert_eq!(counts, vec![1, 1, 6, 12, 29, 38, 50, 38, 29, 12, 6, 1, 1]);
+        // Every name is unique
+        let names: std::collections::HashSet<String> = all.iter().filter_map(|s| s.forte()).collect();
+        assert_eq!(names.len(), 223);
+    }
+
+    #[test]
+    fn test_prime_form() {
+        // C major and A minor triads, either way up
+        assert_eq!(SetClass::of(&[Cn, En, Gn]).prime_form(), vec![Cn, Ds, Gn]);
+        assert_eq!(SetClass::of(&[An, Cn, En]), SetClass::of(&[Cn, En, Gn]));
+        assert_eq!(SetClass::of(&[Gn, Bn, Dn, Fn]).to_string(), "4-27");
+        assert_eq!(SetClass::of(&[Cn, Dn, En, Fs, Gs, As]).to_string(), "6-35");
+        assert_eq!(SetClass::of(&[Cn, Dn, En, Fn, Gn, An, Bn]).to_string(), "7-35");
+        assert_eq!(SetClass::of(&[Cn, Cs, Ds, En, Fs, Gn, An, As]).to_string(), "8-28");
+        assert_eq!(SetClass::of(&[Cn, Fs]).to_string(), "2-6");
+        assert_eq!(format!("{:#}", SetClass::of(&[Cn, Dn, En, Fs, Gs, As])), "(02468T)");
+        assert_eq!(SetClass::of(&[]).to_string(), "()");
+    }
+
+    #[test]
+    fn test_interval_vector() {
+        assert_eq!(SetClass::of(&[Cn, En, Gn]).interval_vector(), [0, 0, 1, 1, 1, 0]);
+        assert_eq!(SetClass::of(&[Cn, Dn, En, Fn, Gn, An, Bn]).interval_vector(), [2, 5, 4, 3, 6, 1]);
+        // All-interval tetrachords
+        let z15: SetClass = "4-Z15".parse().unwrap();
+        assert_eq!(z15.interval_vector(), [1, 1, 1, 1, 1, 1]);
+        assert_eq!(z15.z_related(), Some("4-Z29".parse().unwrap()));
+        assert_eq!(SetClass::of(&[Cn, En, Gn]).z_related(), None);
+    }
+
+    #[test]
+    fn test_complement() {
+        let diatonic = SetClass::of(&[Cn, Dn, En, Fn, Gn, An, Bn]);
+        assert_eq!(diatonic.complement().to_string(), "5-35");
+        assert_eq!("7-Z12".parse::<SetClass>().unwrap().complement().to_string(), "5-Z12");
+        // Z-related hexachords are each other's complements
+        let z3: SetClass = "6-Z3".parse().unwrap();
+        assert_eq!(z3.complement().to_string(), "6-Z36");
+        assert_eq!("6-20".parse::<SetClass>().unwrap().complement().to_string(), "6-20");
+    }
+
+    #[test]
+    fn test_normal_form() {
+        assert_eq!(SetClass::normal_form(&[Gn, Cn, En]), vec![Cn, En, Gn]);
+        assert_eq!(SetClass::normal_form(&[En, Gn, Cn, As]), vec![En, Gn, As, Cn]);
+        assert_eq!(SetClass::normal_form(&[Bn, Dn, Fn, Gs]), vec![Dn, Fn, Gs, Bn]);
+        // Rahn breaks the tie from the right, Forte would start on F
+        assert_eq!(SetClass::normal_form(&[Fn, Fs, Gs, Cn, Cs]), vec![Cn, Cs, Fn, Fs, Gs]);
+    }
+
+    #[test]
+    fn test_transformations() {
+        let triad = [Cn, En, Gn];
+        assert_eq!(SetClass::transpose(&triad, Steps::from(2)), vec![Dn, Fs, An]);
+        assert_eq!(SetClass::transpose(&triad, Steps::from(-1)), vec![Bn, Ds, Fs]);
+        // T7I maps C major onto C minor
+        assert_eq!(SetClass::invert(&triad, Steps::from(7)), vec![Gn, Ds, Cn]);
+        assert_eq!(SetClass::of(&SetClass::invert(&triad, Steps::from(7))), SetClass::of(&triad));
+    }
+
+    #[test]
+    fn test_forms() {
+        let triads: SetClass = "3-11".parse().unwrap();
+        assert_eq!(triads.forms().len(), 24);
+        assert!(!triads.symmetric());
+        let diminished: SetClass = "4-28".parse().unwrap();
+        assert_eq!(diminished.forms().len(), 3);
+        assert!(diminished.symmetric());
+        // Major and minor triads in the C major scale
+        assert_eq!(triads.occurrences(&[Cn, Dn, En, Fn, Gn, An, Bn]).len(), 6);
+    }
+
+    #[test]
+    fn test_subsets() {
+        let diatonic: SetClass = "7-35".parse().unwrap();
+        let triads: SetClass = "3-11".parse().unwrap();
+        assert!(triads.is_subset_of(&diatonic));
+        assert!(diatonic.is_superset_of(&triads));
+        assert!(!"3-12".parse::<SetClass>().unwrap().is_subset_of(&diatonic));
+        assert!(diatonic.subsets().contains(&"5-35".parse().unwrap()));
+        assert!(triads.supersets().contains(&diatonic));
+        assert!(triads.supersets().iter().all(|s| s.cardinality() > 3));
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("(037)".parse::<SetClass>().unwrap().to_string(), "3-11");
+        assert_eq!("[0,1,4,6]".parse::<SetClass>().unwrap().to_string(), "4-Z15");
+        assert_eq!("4-z15".parse::<SetClass>().unwrap().to_string(), "4-Z15");
+        assert_eq!("4-15".parse::<SetClass>(), Err("Wrong Z flag for this Forte number"));
+        assert_eq!("4-Z16".parse::<SetClass>(), Err("Wrong Z flag for this Forte number"));
+        assert_eq!("7-Z12".parse::<SetClass>().unwrap().to_string(), "7-Z12");
+        assert_eq!("9-12".parse::<SetClass>().unwrap().prime_form().len(), 9);
+        assert!("4-30".parse::<SetClass>().is_err());
+        assert!("chord".parse::<SetClass>().is_err());
+    }
+}
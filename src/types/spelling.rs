@@ -0,0 +1,204 @@
+//
+// Copyright 2024 Hans W. Uhlig, Richard I. Christopher. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+
+use super::{Interval, KeySignature, MajorQuality, Note, PerfectQuality, Pitch, PitchClass, PitchClassSet, Steps, Tone};
+use std::cmp::{Ordering, Reverse};
+
+/// [Speller](audiotheorem::types::Speller) chooses the [Note](audiotheorem::types::Note) for each
+/// [Pitch](audiotheorem::types::Pitch) of a passage, where [Pitch::note](audiotheorem::types::Pitch::note)
+/// only knows the pitch class.
+///
+/// Each pitch is spelled by the first of:
+/// * the [KeySignature](audiotheorem::types::KeySignature), when the pitch class is diatonic
+/// * the local chord, so the G# of an E7 in A minor follows its root
+/// * the melodic direction, raising the note below on the way up (C C# D) and lowering the note
+///   above on the way down (D Db C), unless that takes more accidentals than the key's spelling
+/// * the key's spelling of a chromatic pitch class, see [KeySignature::spell](audiotheorem::types::KeySignature::spell)
+#[derive(Clone, Debug, PartialEq)]
+pub struct Speller {
+    key: KeySignature,
+    chord: Vec<Note>,
+    previous: Option<Pitch>,
+}
+
+impl Speller {
+    pub fn new(key: KeySignature) -> Speller {
+        Speller { key, chord: Vec::new(), previous: None }
+    }
+
+    pub fn key(&self) -> KeySignature { self.key }
+
+    /// Change key, e.g. at a modulation. The melodic line carries on.
+    pub fn set_key(&mut self, key: KeySignature) { self.key = key; }
+
+    /// Spell chromatic tones against the [Notes](audiotheorem::types::Note) of the chord now sounding.
+    pub fn set_chord(&mut self, notes: &[Note]) { self.chord = notes.to_vec(); }
+
+    pub fn clear_chord(&mut self) { self.chord.clear(); }
+
+    /// Forget the last pitch, e.g. at the start of a new phrase or voice.
+    pub fn reset(&mut self) { self.previous = None; }
+
+    /// [Note](audiotheorem::types::Note) for a [PitchClass](audiotheorem::types::PitchClass) approached
+    /// in a direction, Greater being upward.
+    pub fn note(&self, pc: &PitchClass, direction: Option<Ordering>) -> Note {
+        let spelled = self.key.spell(pc);
+        if self.key.contains(&spelled) { return spelled; }
+        if let Some(note) = self.chord.iter().find(|n| n.pitch_class() == *pc) { return *note; }
+        let neighbour = match direction {
+            Some(Ordering::Greater) => self.key.spell(&(*pc - Steps::from(1))).fifths() + 7,
+            Some(Ordering::Less) => self.key.spell(&(*pc + Steps::from(1))).fifths() - 7,
+            _ => return spelled,
+        };
+        let accidentals = |n: &Note| n.accidental().offset().abs();
+        Note::from_fifths(neighbour).filter(|n| accidentals(n) <= accidentals(&spelled)).unwrap_or(spelled)
+    }
+
+    /// Spell the next [Pitch](audiotheorem::types::Pitch) of a melodic line, moving from the last one.
+    pub fn spell(&mut self, pitch: &Pitch) -> Tone {
+        let direction = self.previous.map(|previous| pitch.to_index().cmp(&previous.to_index()));
+        self.previous = Some(*pitch);
+        self.tone(pitch, direction)
+    }
+
+    /// Spell [Pitches](audiotheorem::types::Pitch) sounding together as a stack of thirds, so Ab C Eb
+    /// never reads G# C Eb. A chord given to [set_chord](Speller::set_chord) is used as it is.
+    /// The line carries on from the highest.
+    pub fn spell_chord(&mut self, pitches: &[Pitch]) -> Vec<Tone> {
+        let tones = if self.chord.is_empty() {
+            let notes = self.stack(pitches);
+            pitches.iter()
+                .map(|pitch| {
+                    let note = notes.iter().find(|n| n.pitch_class() == pitch.pitch_class()).copied();
+                    note.and_then(|note| Tone::spelled(pitch, note)).unwrap_or_else(|| self.tone(pitch, None))
+                })
+                .collect()
+        } else {
+            pitches.iter().map(|pitch| self.tone(pitch, None)).collect()
+        };
+        if let Some(highest) = pitches.iter().max_by_key(|p| p.to_index()) { self.previous = Some(*highest); }
+        tones
+    }
+
+    /// Spell a melodic line from start to finish.
+    pub fn spell_all(&mut self, pitches: &[Pitch]) -> Vec<Tone> {
+        pitches.iter().map(|pitch| self.spell(pitch)).collect()
+    }
+
+    // Read the pitch classes as a chord on every spelling of each of them, keeping the reading with the
+    // most chord tones, then the fewest accidentals, then the fewest departures from the key
+    fn stack(&self, pitches: &[Pitch]) -> Vec<Note> {
+        let classes: PitchClassSet = pitches.iter().map(Pitch::pitch_class).collect();
+        classes.iter()
+            .flat_map(|pc| pc.names().iter())
+            .filter_map(|root| stack(*root, classes))
+            .min_by_key(|(chord_tones, notes)| {
+                let accidentals: i32 = notes.iter().map(|n| n.accidental().offset().abs()).sum();
+                let departures = notes.iter().filter(|n| self.note(&n.pitch_class(), None) != **n).count();
+                (Reverse(*chord_tones), accidentals, departures)
+            })
+            .map(|(_, notes)| notes)
+            .unwrap_or_default()
+    }
+
+    fn tone(&self, pitch: &Pitch, direction: Option<Ordering>) -> Tone {
+        Tone::spelled(pitch, self.note(&pitch.pitch_class(), direction)).unwrap_or_else(|| pitch.tone())
+    }
+}
+
+// Every pitch class spelled above a root as its third, fifth and seventh, with anything else read as the
+// tension it would be over them, and how strongly the thirds hold - None if a note can't be spelled
+fn stack(root: Note, classes: PitchClassSet) -> Option<(u8, Vec<Note>)> {
+    use Interval::*;
+    let has = |steps: i32| classes.contains(&(root.pitch_class() + Steps::from(steps)));
+    let third = [(4, Third(MajorQuality::Major)), (3, Third(MajorQuality::Minor))].into_iter().find(|(s, _)| has(*s));
+    let fifth = [(7, Fifth(PerfectQuality::Perfect)), (6, Fifth(PerfectQuality::Diminished))].into_iter()
+        .chain(third.filter(|(s, _)| *s == 4).map(|_| (8, Fifth(PerfectQuality::Augmented))))
+        .find(|(s, _)| has(*s));
+    let diminished = third.map(|(s, _)| s) == Some(3) && fifth.map(|(s, _)| s) == Some(6);
+    let seventh = [(10, Seventh(MajorQuality::Minor)), (11, Seventh(MajorQuality::Major))].into_iter()
+        .chain(diminished.then_some((9, Seventh(MajorQuality::Diminished))))
+        .find(|(s, _)| has(*s));
+    let tensions = [
+        (1, Second(MajorQuality::Minor)), (2, Second(MajorQuality::Major)), (3, Second(MajorQuality::Augmented)),
+        (5, Fourth(PerfectQuality::Perfect)), (6, Fourth(PerfectQuality::Augmented)),
+        (8, Sixth(MajorQuality::Minor)), (9, Sixth(MajorQuality::Major)),
+    ];
+
+    let mut intervals: Vec<(i32, Interval)> = vec![(0, First(PerfectQuality::Perfect))];
+    intervals.extend([third, fifth, seventh].into_iter().flatten());
+    for (steps, interval) in tensions {
+        if has(steps) && !intervals.iter().any(|(s, _)| *s == steps) { intervals.push((steps, interval)); }
+    }
+    let chord_tones = 2 * third.is_some() as u8 + 2 * fifth.is_some() as u8 + seventh.is_some() as u8;
+    let notes = intervals.into_iter().map(|(_, interval)| root + interval).collect::<Option<Vec<Note>>>()?;
+    Some((chord_tones, notes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Speller;
+    use crate::types::{KeySignature, Pitch, Tone};
+
+    fn line(key: i8, pitches: &str) -> String {
+        let pitches: Vec<Pitch> = pitches.split_whitespace().map(|p| p.parse().unwrap()).collect();
+        let tones: Vec<String> = Speller::new(KeySignature::new(key).unwrap()).spell_all(&pitches).iter().map(Tone::to_string).collect();
+        tones.join(" ")
+    }
+
+    #[test]
+    fn test_key() {
+        // Eb minor no longer comes out in sharps
+        assert_eq!(line(-6, "Eb4 F4 F#4 G#4 A#4 B4 C#5 D#5"), "Eb4 F4 Gb4 Ab4 Bb4 Cb5 Db5 Eb5");
+        assert_eq!(line(5, "B3 C#4 D#4 E4 F#4 G#4 A#4 B4"), "B3 C#4 D#4 E4 F#4 G#4 A#4 B4");
+        // The leading tone of Eb minor is D natural, and the B below is Cb
+        assert_eq!(line(-6, "D4 B3"), "D4 Cb4");
+    }
+
+    #[test]
+    fn test_direction() {
+        assert_eq!(line(0, "C4 C#4 D4 D#4 E4"), "C4 C#4 D4 D#4 E4");
+        assert_eq!(line(0, "E4 Eb4 D4 Db4 C4"), "E4 Eb4 D4 Db4 C4");
+        assert_eq!(line(-3, "G4 G#4 A4 Ab4 G4"), "G4 Ab4 A4 Ab4 G4");
+        // Fb on the way down would be harder to read than E
+        assert_eq!(line(-3, "D4 E4 F4 E4 Eb4"), "D4 E4 F4 E4 Eb4");
+        assert_eq!(line(0, "F#4"), "F#4");
+    }
+
+    #[test]
+    fn test_chord() {
+        let key = KeySignature::new(0).unwrap();
+        let mut speller = Speller::new(key);
+        // E7 in A minor, descending onto the G# would otherwise read Ab
+        speller.set_chord(&["E", "G#", "B", "D"].map(|n| n.parse().unwrap()));
+        let pitches: Vec<Pitch> = ["A4", "G#4"].iter().map(|p| p.parse().unwrap()).collect();
+        assert_eq!(speller.spell_all(&pitches).last().unwrap().to_string(), "G#4");
+        speller.clear_chord();
+        let mut chord = |pitches: &[&str]| -> Vec<String> {
+            let pitches: Vec<Pitch> = pitches.iter().map(|p| p.parse().unwrap()).collect();
+            speller.spell_chord(&pitches).iter().map(Tone::to_string).collect()
+        };
+        // Sounding together, the pitches are spelled as the thirds of a chord
+        assert_eq!(chord(&["Ab3", "C4", "Eb4"]), ["Ab3", "C4", "Eb4"]);
+        assert_eq!(chord(&["C4", "Ab4", "Eb5"]), ["C4", "Ab4", "Eb5"]);
+        assert_eq!(chord(&["E3", "G#3", "B3", "D4"]), ["E3", "G#3", "B3", "D4"]);
+        assert_eq!(chord(&["F#3", "A#3", "C#4"]), ["F#3", "A#3", "C#4"]);
+        assert_eq!(chord(&["G#3", "B3", "D4", "F4"]), ["G#3", "B3", "D4", "F4"]);
+        assert_eq!(chord(&["C4", "E4", "G#4"]), ["C4", "E4", "G#4"]);
+        assert_eq!(chord(&["D4", "F4", "Ab4", "C5"]), ["D4", "F4", "Ab4", "C5"]);
+    }
+}
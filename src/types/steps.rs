@@ -16,36 +16,34 @@
 
 use super::Cents;
 use crate::types::PitchClass;
-use std::fmt;
+use std::{fmt, ops};
 
-/// [Steps](audiotheorem::types::Steps) is the distance in semitones between two
-/// [PitchClasses](audiotheorem::types::PitchClass).
-#[derive(Copy, Clone, PartialOrd, PartialEq)]
-pub struct Steps(u16);
+/// [Steps](audiotheorem::types::Steps) is the signed distance in semitones between two
+/// [PitchClasses](audiotheorem::types::PitchClass) or [Pitches](audiotheorem::types::Pitch).
+#[derive(Copy, Clone, Default, PartialOrd, Ord, PartialEq, Eq, Hash)]
+pub struct Steps(i32);
 
 impl Steps {
-    /// Get the distance between two [PitchClasses](audiotheorem::types::PitchClass).
+    /// Get the distance up from one [PitchClass](audiotheorem::types::PitchClass) to another, from 0 to 11.
     pub fn distance(l: &PitchClass, r: &PitchClass) -> Steps {
-        let l = l.to_index() as u16;
-        let r = r.to_index() as u16;
-        Steps(if r > l {
-            (r - l) % 12
-        } else {
-            (12 + r - l) % 12
-        })
+        Steps((r.to_index() as i32 - l.to_index() as i32).rem_euclid(12))
     }
     /// [Cents](audiotheorem::types::Cents) represented by this [Steps](audiotheorem::types::Steps).
     pub fn cents(&self) -> Cents {
-        Cents::from(self.0 * 100)
+        Cents::from(self.0 as f64 * 100.0)
     }
-    /// Number of [Steps](audiotheorem::types::Steps).
-    pub fn value(&self) -> u16 {
+    /// Number of [Steps](audiotheorem::types::Steps) - negative when descending.
+    pub fn value(&self) -> i32 {
         self.0
     }
+    /// Absolute size of these [Steps](audiotheorem::types::Steps), ignoring direction.
+    pub fn abs(&self) -> Steps {
+        Steps(self.0.abs())
+    }
 }
 
-impl From<u16> for Steps {
-    fn from(value: u16) -> Steps {
+impl From<i32> for Steps {
+    fn from(value: i32) -> Steps {
         Steps(value)
     }
 }
@@ -56,6 +54,27 @@ impl From<Cents> for Steps {
     }
 }
 
+impl ops::Add for Steps {
+    type Output = Steps;
+    fn add(self, other: Steps) -> Steps {
+        Steps(self.0 + other.0)
+    }
+}
+
+impl ops::Sub for Steps {
+    type Output = Steps;
+    fn sub(self, other: Steps) -> Steps {
+        Steps(self.0 - other.0)
+    }
+}
+
+impl ops::Neg for Steps {
+    type Output = Steps;
+    fn neg(self) -> Steps {
+        Steps(-self.0)
+    }
+}
+
 impl fmt::Display for Steps {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "Steps({})", self.value())
@@ -67,3 +86,18 @@ impl fmt::Debug for Steps {
         write!(f, "Steps(steps: {} cents: {})", self.value(), self.cents())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signed() {
+        assert_eq!(Steps::distance(&PitchClass::An, &PitchClass::Cn), Steps::from(3));
+        assert_eq!(Steps::distance(&PitchClass::Cn, &PitchClass::An), Steps::from(9));
+        assert_eq!(Steps::from(3) - Steps::from(7), Steps::from(-4));
+        assert_eq!(-Steps::from(5) + Steps::from(12), Steps::from(7));
+        assert_eq!(Steps::from(-4).abs(), Steps::from(4));
+        assert_eq!(Steps::from(-4).cents(), Cents::from(-400.0));
+    }
+}
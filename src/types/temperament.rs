@@ -0,0 +1,270 @@
+//
+// Copyright 2024 Hans W. Uhlig, Richard I. Christopher. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+
+use super::{Cents, Pitch, PitchClass, Steps, Tuning};
+use std::fmt;
+
+/// Pure fifth (3/2) in cents.
+pub const PURE_FIFTH: f64 = 701.955_000_865_387_4;
+/// Syntonic comma (81/80) in cents.
+pub const SYNTONIC_COMMA: f64 = 21.506_289_959_773_3;
+/// Pythagorean comma (531441/524288) in cents.
+pub const PYTHAGOREAN_COMMA: f64 = 23.460_010_384_649_4;
+
+/// Ways of dividing the octave into the 12 [PitchClasses](audiotheorem::types::PitchClass) relative to a root.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Temperament {
+    /// 12 tone equal temperament.
+    Equal,
+    /// 5-limit just intonation - 1/1 16/15 9/8 6/5 5/4 4/3 45/32 3/2 8/5 5/3 16/9 15/8.
+    Just,
+    /// Pure fifths from the flat third to the sharp fifth, leaving the wolf between them.
+    Pythagorean,
+    /// Fifths narrowed by the given fraction of a syntonic comma - 0.25 is quarter-comma meantone.
+    Meantone(f64),
+    /// Werckmeister III - C-G, G-D, D-A and B-F# narrowed by 1/4 Pythagorean comma.
+    Werckmeister,
+    /// Vallotti - F-C through E-B narrowed by 1/6 Pythagorean comma.
+    Vallotti,
+    /// Equal division of the octave into n steps, each pitch class taking the nearest step.
+    Edo(u16),
+}
+
+impl Temperament {
+    /// Quarter-comma meantone, where major thirds are pure.
+    pub const QUARTER_COMMA_MEANTONE: Temperament = Temperament::Meantone(0.25);
+
+    // Fifths of the chain Eb-Bb-F-C-G-D-A-E-B-F#-C#-G# in cents, positioned from the root
+    fn chain(fifths: [f64; 11]) -> [f64; 12] {
+        // The root is the fourth link, with Eb three fifths below it
+        let mut positions = [0.0; 12];
+        for i in 0..12 {
+            let cents = if i >= 3 { fifths[3..i].iter().sum::<f64>() } else { -fifths[i..3].iter().sum::<f64>() };
+            positions[(i * 7 + 3) % 12] = cents.rem_euclid(1200.0);
+        }
+        positions
+    }
+
+    /// Position of each chromatic step above the root in cents.
+    pub fn cents(&self) -> [f64; 12] {
+        let ratios = |r: [f64; 12]| r.map(|r| 1200.0 * r.log2());
+        match self {
+            Temperament::Equal => std::array::from_fn(|i| i as f64 * 100.0),
+            Temperament::Just => ratios([1.0, 16.0 / 15.0, 9.0 / 8.0, 6.0 / 5.0, 5.0 / 4.0, 4.0 / 3.0, 45.0 / 32.0, 3.0 / 2.0, 8.0 / 5.0, 5.0 / 3.0, 16.0 / 9.0, 15.0 / 8.0]),
+            Temperament::Pythagorean => Temperament::chain([PURE_FIFTH; 11]),
+            Temperament::Meantone(fraction) => Temperament::chain([PURE_FIFTH - fraction * SYNTONIC_COMMA; 11]),
+            Temperament::Werckmeister => {
+                let tempered = PURE_FIFTH - PYTHAGOREAN_COMMA / 4.0;
+                let mut fifths = [PURE_FIFTH; 11];
+                for i in [3, 4, 5, 8] { fifths[i] = tempered; }     // C-G, G-D, D-A, B-F#
+                Temperament::chain(fifths)
+            }
+            Temperament::Vallotti => {
+                let tempered = PURE_FIFTH - PYTHAGOREAN_COMMA / 6.0;
+                let mut fifths = [PURE_FIFTH; 11];
+                for fifth in fifths.iter_mut().take(8).skip(2) { *fifth = tempered; }     // F-C through E-B
+                Temperament::chain(fifths)
+            }
+            Temperament::Edo(n) => {
+                let n = (*n).max(1) as f64;
+                std::array::from_fn(|i| (i as f64 * n / 12.0).round() * 1200.0 / n)
+            }
+        }
+    }
+
+    /// Frequency ratio of each chromatic step above the root.
+    pub fn ratios(&self) -> [f64; 12] { self.cents().map(|c| 2f64.powf(c / 1200.0)) }
+
+    /// All temperaments with their common parameters.
+    pub fn all() -> Vec<Temperament> {
+        vec![
+            Temperament::Equal,
+            Temperament::Just,
+            Temperament::Pythagorean,
+            Temperament::QUARTER_COMMA_MEANTONE,
+            Temperament::Meantone(1.0 / 6.0),
+            Temperament::Werckmeister,
+            Temperament::Vallotti,
+            Temperament::Edo(19),
+            Temperament::Edo(24),
+            Temperament::Edo(31),
+            Temperament::Edo(53),
+        ]
+    }
+}
+
+impl fmt::Display for Temperament {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Temperament::Equal => write!(f, "12 Tone Equal Temperament"),
+            Temperament::Just => write!(f, "Just Intonation"),
+            Temperament::Pythagorean => write!(f, "Pythagorean"),
+            Temperament::Meantone(fraction) => write!(f, "1/{} Comma Meantone", (1.0 / fraction).round()),
+            Temperament::Werckmeister => write!(f, "Werckmeister III"),
+            Temperament::Vallotti => write!(f, "Vallotti"),
+            Temperament::Edo(n) => write!(f, "{}-EDO", n),
+        }
+    }
+}
+
+/// A [Temperament] built on a root [PitchClass](audiotheorem::types::PitchClass) and pinned to a reference frequency.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TuningSystem {
+    pub temperament: Temperament,
+    pub root: PitchClass,
+    /// Pitch sounding at the reference frequency.
+    pub reference: Pitch,
+    /// Frequency of the reference pitch in Hz.
+    pub frequency: f64,
+}
+
+impl TuningSystem {
+    /// Temperament on the given root, with A4 at 440Hz.
+    pub fn new(temperament: Temperament, root: PitchClass) -> TuningSystem {
+        TuningSystem { temperament, root, reference: Pitch::from_index(69), frequency: 440.0 }
+    }
+
+    /// Pin any pitch to any frequency.
+    pub fn with_reference(mut self, reference: Pitch, frequency: f64) -> TuningSystem {
+        self.reference = reference;
+        self.frequency = frequency;
+        self
+    }
+
+    /// Equal temperament at one of the fixed A4 [Tunings](audiotheorem::types::Tuning).
+    pub fn from_tuning(tuning: Tuning) -> TuningSystem {
+        TuningSystem::new(Temperament::Equal, PitchClass::Cn).with_reference(Pitch::from_index(69), 432.0 + 2.0 * tuning as u8 as f64)
+    }
+
+    // Cents of a pitch above the root of octave -1
+    fn position(&self, pitch: Pitch) -> f64 {
+        let distance = pitch.to_index() as i32 - self.root.to_index() as i32;
+        1200.0 * distance.div_euclid(12) as f64 + self.temperament.cents()[distance.rem_euclid(12) as usize]
+    }
+
+    /// Frequency of a [Pitch](audiotheorem::types::Pitch) in Hz.
+    pub fn frequency(&self, pitch: Pitch) -> f64 {
+        self.frequency * 2f64.powf((self.position(pitch) - self.position(self.reference)) / 1200.0)
+    }
+
+    /// Frequencies of all 144 [Pitches](audiotheorem::types::Pitch).
+    pub fn frequencies(&self) -> Vec<f64> { (0..144).map(|i| self.frequency(Pitch::from_index(i))).collect() }
+
+    /// Distance of a [PitchClass](audiotheorem::types::PitchClass) above the root in [Cents](audiotheorem::types::Cents).
+    pub fn cents(&self, pitch_class: PitchClass) -> Cents {
+        Cents::from(self.offset(pitch_class))
+    }
+
+    // Distance of a pitch class above the root in cents
+    fn offset(&self, pitch_class: PitchClass) -> f64 {
+        let step = (12 + pitch_class.to_index() - self.root.to_index()) % 12;
+        self.temperament.cents()[step as usize]
+    }
+
+    /// Signed deviation of a [PitchClass](audiotheorem::types::PitchClass) from equal temperament on the same root.
+    pub fn deviation(&self, pitch_class: PitchClass) -> Cents {
+        self.cents(pitch_class) - Steps::distance(&self.root, &pitch_class).cents()
+    }
+
+    /// Signed deviation of a [Pitch](audiotheorem::types::Pitch) from equal temperament with A4 at 440Hz.
+    pub fn deviation_from_standard(&self, pitch: Pitch) -> Cents {
+        Cents::between(TuningSystem::from_tuning(Tuning::A4_440Hz).frequency(pitch), self.frequency(pitch))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interval(system: &TuningSystem, low: u8, high: u8) -> f64 {
+        1200.0 * (system.frequency(Pitch::from_index(high)) / system.frequency(Pitch::from_index(low))).log2()
+    }
+
+    #[test]
+    fn test_equal_matches_table() {
+        for (i, tuning) in [Tuning::A4_432Hz, Tuning::A4_440Hz, Tuning::A4_446Hz].into_iter().enumerate() {
+            let system = TuningSystem::from_tuning(tuning);
+            for index in 0..144 {
+                let pitch = Pitch::from_index(index);
+                let expected = pitch.frequency(tuning) as f64;
+                // The table is rounded, so compare within half a cent
+                assert!((1200.0 * (system.frequency(pitch) / expected).log2()).abs() < 0.5, "{} {} {} {}", i, index, system.frequency(pitch), expected);
+            }
+        }
+        assert!(Temperament::Edo(12).cents().iter().zip(Temperament::Equal.cents()).all(|(a, b)| (a - b).abs() < 1e-9));
+    }
+
+    #[test]
+    fn test_temperament_intervals() {
+        let c = |temperament| TuningSystem::new(temperament, PitchClass::Cn);
+
+        // Pythagorean - pure fifths and wide thirds, with the wolf between G# and Eb
+        assert!((interval(&c(Temperament::Pythagorean), 60, 67) - PURE_FIFTH).abs() < 1e-9);
+        assert!((interval(&c(Temperament::Pythagorean), 60, 64) - 407.82).abs() < 0.01);
+        assert!((interval(&c(Temperament::Pythagorean), 68, 75) - (PURE_FIFTH - PYTHAGOREAN_COMMA)).abs() < 1e-9);
+
+        // Just and quarter-comma meantone have pure major thirds
+        assert!((interval(&c(Temperament::Just), 60, 64) - 386.31).abs() < 0.01);
+        assert!((interval(&c(Temperament::QUARTER_COMMA_MEANTONE), 60, 64) - 386.31).abs() < 0.01);
+        assert!((interval(&c(Temperament::QUARTER_COMMA_MEANTONE), 60, 67) - 696.58).abs() < 0.01);
+
+        // Well temperaments close the circle, so no fifth is a wolf
+        assert!((interval(&c(Temperament::Werckmeister), 60, 64) - 390.22).abs() < 0.01);
+        assert!((interval(&c(Temperament::Vallotti), 60, 64) - 392.18).abs() < 0.01);
+        for temperament in [Temperament::Werckmeister, Temperament::Vallotti] {
+            for low in 60..72 {
+                let fifth = interval(&c(temperament), low, low + 7);
+                assert!(fifth > PURE_FIFTH - PYTHAGOREAN_COMMA / 4.0 - 1e-9 && fifth < PURE_FIFTH + 1e-9, "{} {} {}", temperament, low, fifth);
+            }
+        }
+
+        // 19-EDO maps E to the sixth of its 19 steps
+        assert!((interval(&c(Temperament::Edo(19)), 60, 64) - 6.0 * 1200.0 / 19.0).abs() < 1e-9);
+        assert!((interval(&c(Temperament::Edo(24)), 60, 67) - 700.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_root_and_reference() {
+        // Just intonation on G makes A-C# a pure major third, where on C it is a diminished fourth
+        let g = TuningSystem::new(Temperament::Just, PitchClass::Gn);
+        assert!((interval(&g, 69, 73) - 386.31).abs() < 0.01);
+        assert!((interval(&TuningSystem::new(Temperament::Just, PitchClass::Cn), 69, 73) - 427.37).abs() < 0.01);
+
+        // The reference pitch sounds at the reference frequency in every temperament, and octaves stay pure
+        for temperament in Temperament::all() {
+            let system = TuningSystem::new(temperament, PitchClass::Ds).with_reference(Pitch::from_index(60), 256.0);
+            assert!((system.frequency(Pitch::from_index(60)) - 256.0).abs() < 1e-9);
+            assert!((system.frequency(Pitch::from_index(72)) - 512.0).abs() < 1e-9);
+            assert!((system.frequency(Pitch::from_index(5)) * 2.0 - system.frequency(Pitch::from_index(17))).abs() < 1e-9);
+            assert_eq!(system.frequencies().len(), 144);
+        }
+    }
+
+    #[test]
+    fn test_cents_and_deviation() {
+        let just = TuningSystem::new(Temperament::Just, PitchClass::Cn);
+        assert_eq!(just.cents(PitchClass::Cn).cents(), 0.0);
+        assert!((just.cents(PitchClass::Gn).cents() - 701.955).abs() < 0.001);
+        assert!((just.deviation(PitchClass::En).cents() + 13.69).abs() < 0.01);
+        assert!((just.deviation(PitchClass::Gn).cents() - 1.96).abs() < 0.01);
+
+        let equal = TuningSystem::new(Temperament::Equal, PitchClass::Fs);
+        assert!(PitchClass::Cn.tones().iter().all(|p| equal.deviation_from_standard(*p).cents().abs() < 1e-9));
+        let flat = TuningSystem::from_tuning(Tuning::A4_432Hz);
+        assert!((flat.deviation_from_standard(Pitch::from_index(69)).cents() + 31.77).abs() < 0.01);
+    }
+}
@@ -20,6 +20,7 @@ use super::{octave, Dynamic, Note, Octave, Pitch, PitchClass};
 use crate::types::interval::PerfectQuality::Augmented;
 use crate::types::{Interval, PerfectQuality};
 use std::fmt;
+use std::str::FromStr;
 
 
 // Velocity and Harmony were amended as part of a proprietary system as part of the Nexus Project under Big Stick Studio on behalf of Ancillary, Inc. 2024
@@ -44,6 +45,15 @@ impl Tone {
     pub fn from_parts(octave: Octave, note: Note) -> Tone 
         { Tone { octave, note } }
 
+    /// Spell a [Pitch](audiotheorem::types::Pitch) as the given [Note](audiotheorem::types::Note), with the
+    /// [Octave](audiotheorem::types::Octave) following the letter so the 72nd pitch spelled B# is B#4.
+    /// None if the note does not name the pitch or the octave falls outside the range.
+    pub fn spelled(pitch: &Pitch, note: Note) -> Option<Tone> {
+        let height = pitch.to_index() as i32 - note.height();
+        if height.rem_euclid(12) != 0 { return None; }
+        Octave::from_index(u8::try_from(height / 12).ok()?).map(|octave| Tone { octave, note })
+    }
+
     /// Convert a [Tone](audiotheorem::types::Tone) into a [Note](audiotheorem::types::Note).
     pub fn note(&self) -> Note { self.note }
 
@@ -58,6 +68,20 @@ impl Tone {
     /// Convert a [Tone](audiotheorem::types::Tone) into an [Octave](audiotheorem::types::Octave).
     pub fn octave(&self) -> Octave 
         { self.octave }
+
+    // Pitch index with the octave following the letter, so B#4 is 72 and Cb4 is 59 - may fall outside 0 to 143
+    pub(crate) fn height(&self) -> i32 
+        { 12 * self.octave.to_index() as i32 + self.note.height() }
+}
+
+/// Parse scientific pitch notation, e.g. "A4", "C-1" or "Bb7".
+impl FromStr for Tone {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Tone, Self::Err> {
+        let (note, octave) = Note::parse_prefix(s.trim())?;
+        if octave.is_empty() { return Err("Missing octave"); }
+        Ok(Tone::from_parts(octave.parse()?, note))
+    }
 }
 
 impl fmt::Display for Tone {
@@ -144,6 +168,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse() {
+        use crate::types::Accidental;
+        assert_eq!("A4".parse::<Tone>(), Ok(Tone::from_parts(Octave::OneLine, Note::A(Accidental::Natural))));
+        assert_eq!("C-1".parse::<Tone>(), Ok(Tone::from_parts(Octave::DoubleContra, Note::C(Accidental::Natural))));
+        assert_eq!("Bb7".parse::<Tone>(), Ok(Tone::from_parts(Octave::FourLine, Note::B(Accidental::Flat))));
+        assert_eq!("F##10".parse::<Tone>(), Ok(Tone::from_parts(Octave::SevenLine, Note::F(Accidental::DoubleSharp))));
+        assert!("A".parse::<Tone>().is_err());
+        assert!("A11".parse::<Tone>().is_err());
+        assert!("A-2".parse::<Tone>().is_err());
+        assert!("X4".parse::<Tone>().is_err());
+        for octave in Octave::all().iter() {
+            assert_eq!(octave.scientific().to_string().parse::<Octave>(), Ok(*octave));
+            for note in Note::sharps().iter().chain(Note::flats().iter()) {
+                let tone = Tone::from_parts(*octave, *note);
+                assert_eq!(tone.to_string().parse::<Tone>(), Ok(tone));
+            }
+        }
+    }
+
     #[test]
     fn test_addition() {
         use crate::types::{Accidental, Interval, Note, Octave, Tone};
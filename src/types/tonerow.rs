@@ -0,0 +1,300 @@
+//
+// Copyright 2024 Hans W. Uhlig, Richard I. Christopher. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use super::{PitchClass, PitchClassSet, Steps};
+use std::fmt;
+
+/// The four ways a [ToneRow](audiotheorem::types::ToneRow) can be read.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RowTransform {
+    /// The row as written
+    Prime,
+    /// The row backwards
+    Retrograde,
+    /// Every interval of the row turned upside down
+    Inversion,
+    /// The inversion backwards
+    RetrogradeInversion,
+}
+
+impl RowTransform {
+    pub fn all() -> [RowTransform; 4] {
+        [
+            RowTransform::Prime,
+            RowTransform::Retrograde,
+            RowTransform::Inversion,
+            RowTransform::RetrogradeInversion,
+        ]
+    }
+}
+
+impl fmt::Display for RowTransform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RowTransform::Prime => write!(f, "P"),
+            RowTransform::Retrograde => write!(f, "R"),
+            RowTransform::Inversion => write!(f, "I"),
+            RowTransform::RetrogradeInversion => write!(f, "RI"),
+        }
+    }
+}
+
+/// One of the 48 forms of a [ToneRow](audiotheorem::types::ToneRow), such as P0 or RI5.
+///
+/// The transposition counts semitones from the first pitch class of the row as written, so P0 is
+/// the row itself. Retrogrades carry the number of the form they reverse, so R0 ends where P0 starts.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RowForm {
+    pub transform: RowTransform,
+    pub transposition: u8,
+}
+
+impl RowForm {
+    pub fn new(transform: RowTransform, transposition: u8) -> RowForm {
+        RowForm { transform, transposition: transposition % 12 }
+    }
+
+    /// All 48 forms, P0 to P11, then R, I and RI.
+    pub fn all() -> Vec<RowForm> {
+        RowTransform::all()
+            .iter()
+            .flat_map(|transform| (0..12).map(move |n| RowForm::new(*transform, n)))
+            .collect()
+    }
+}
+
+impl fmt::Display for RowForm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.transform, self.transposition)
+    }
+}
+
+/// [ToneRow](audiotheorem::types::ToneRow) is an ordering of all 12
+/// [PitchClasses](audiotheorem::types::PitchClass) used as the basis of serial composition.
+///
+/// Tone Row Matrix - rows read left to right are the P forms, right to left the R forms,
+/// columns read top to bottom are the I forms and bottom to top the RI forms.
+///
+/// |     | I0 | I1 | I3 | I9 | I2 | I11 | I4 | I10 | I7 | I8 | I5 | I6 |
+/// |-----|----|----|----|----|----|-----|----|-----|----|----|----|----|
+/// | P0  | En | Fn | Gn | Cs | Fs | Ds  | Gs | Dn  | Bn | Cn | An | As |
+/// | P11 | Ds | En | Fs | Cn | Fn | Dn  | Gn | Cs  | As | Bn | Gs | An |
+/// | ... |    |    |    |    |    |     |    |     |    |    |    |    |
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ToneRow {
+    row: [PitchClass; 12],
+}
+
+impl ToneRow {
+    /// Build a row from exactly 12 distinct [PitchClasses](audiotheorem::types::PitchClass).
+    pub fn new(pitch_classes: &[PitchClass]) -> Result<ToneRow, &'static str> {
+        if pitch_classes.len() != 12 {
+            return Err("A tone row needs exactly 12 pitch classes");
+        }
+        let mut row = [PitchClass::Cn; 12];
+        for (i, pc) in pitch_classes.iter().enumerate() {
+            if pitch_classes[..i].contains(pc) {
+                return Err("A tone row cannot repeat a pitch class");
+            }
+            row[i] = *pc;
+        }
+        Ok(ToneRow { row })
+    }
+
+    /// The row as written - P0.
+    pub fn pitch_classes(&self) -> [PitchClass; 12] { self.row }
+
+    /// The row read as the given form.
+    pub fn form(&self, form: RowForm) -> [PitchClass; 12] {
+        let first = self.row[0];
+        let steps = Steps::from(form.transposition as i32);
+        let mut row = self.row.map(|pc| match form.transform {
+            RowTransform::Prime | RowTransform::Retrograde => pc + steps,
+            RowTransform::Inversion | RowTransform::RetrogradeInversion => first - Steps::distance(&first, &pc) + steps,
+        });
+        if let RowTransform::Retrograde | RowTransform::RetrogradeInversion = form.transform {
+            row.reverse();
+        }
+        row
+    }
+
+    /// All 48 forms, P0 to P11, then R, I and RI.
+    pub fn forms(&self) -> Vec<(RowForm, [PitchClass; 12])> {
+        RowForm::all().into_iter().map(|form| (form, self.form(form))).collect()
+    }
+
+    /// The 12x12 matrix, each row being the P form starting on the pitch class of I0 in that row.
+    pub fn matrix(&self) -> [[PitchClass; 12]; 12] {
+        let inversion = self.form(RowForm::new(RowTransform::Inversion, 0));
+        inversion.map(|pc| self.form(RowForm::new(RowTransform::Prime, Steps::distance(&self.row[0], &pc).value() as u8)))
+    }
+
+    /// Labels for the rows of the [matrix](ToneRow::matrix), top to bottom.
+    pub fn matrix_labels(&self) -> [RowForm; 12] {
+        self.form(RowForm::new(RowTransform::Inversion, 0))
+            .map(|pc| RowForm::new(RowTransform::Prime, Steps::distance(&self.row[0], &pc).value() as u8))
+    }
+
+    /// Every place a complete form of the row is heard in `pitch_classes`, as the index of its
+    /// first pitch class. Repeated notes within the row are allowed.
+    pub fn find(&self, pitch_classes: &[PitchClass]) -> Vec<(usize, RowForm)> {
+        let mut heard: Vec<(usize, PitchClass)> = Vec::new();
+        for (i, pc) in pitch_classes.iter().enumerate() {
+            if heard.last().map(|(_, last)| last) != Some(pc) {
+                heard.push((i, *pc));
+            }
+        }
+
+        let forms = self.forms();
+        let mut found = Vec::new();
+        for window in heard.windows(12) {
+            for (form, row) in forms.iter() {
+                if window.iter().zip(row.iter()).all(|((_, a), b)| a == b) {
+                    found.push((window[0].0, *form));
+                }
+            }
+        }
+        found
+    }
+
+    /// Forms of the given transform whose first hexachord holds the pitch classes missing from the
+    /// first hexachord of P0, leaving out R0 which always does.
+    pub fn combinatorial(&self, transform: RowTransform) -> Vec<RowForm> {
+        let first = PitchClassSet::from(&self.row[..6]);
+        (0..12)
+            .map(|n| RowForm::new(transform, n))
+            .filter(|form| *form != RowForm::new(RowTransform::Retrograde, 0))
+            .filter(|form| PitchClassSet::from(&self.form(*form)[..6]) == first.complement())
+            .collect()
+    }
+
+    /// True if the row is hexachordally combinatorial under some form other than R0.
+    pub fn is_combinatorial(&self) -> bool {
+        RowTransform::all().iter().any(|transform| !self.combinatorial(*transform).is_empty())
+    }
+
+    /// True if the row is combinatorial under transposition, inversion and retrograde inversion at once.
+    pub fn is_all_combinatorial(&self) -> bool {
+        [RowTransform::Prime, RowTransform::Inversion, RowTransform::RetrogradeInversion]
+            .iter()
+            .all(|transform| !self.combinatorial(*transform).is_empty())
+    }
+}
+
+impl fmt::Display for ToneRow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<String> = self.row.iter().map(|pc| pc.to_string()).collect();
+        write!(f, "{}", names.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::PitchClass::*;
+
+    // Webern - Symphonie Op. 21
+    const WEBERN: [PitchClass; 12] = [An, Fs, Gn, Gs, En, Fn, Bn, As, Dn, Cs, Cn, Ds];
+    // Berg - Violin Concerto
+    const BERG: [PitchClass; 12] = [Gn, As, Dn, Fs, An, Cn, En, Gs, Bn, Cs, Ds, Fn];
+
+    #[test]
+    fn test_new() {
+        assert!(ToneRow::new(&WEBERN).is_ok());
+        assert!(ToneRow::new(&WEBERN[..11]).is_err());
+        let mut repeated = WEBERN;
+        repeated[11] = An;
+        assert!(ToneRow::new(&repeated).is_err());
+    }
+
+    #[test]
+    fn test_forms() {
+        let row = ToneRow::new(&BERG).unwrap();
+        assert_eq!(row.form(RowForm::new(RowTransform::Prime, 0)), BERG);
+        assert_eq!(row.form(RowForm::new(RowTransform::Prime, 2))[0], An);
+        let mut retrograde = BERG;
+        retrograde.reverse();
+        assert_eq!(row.form(RowForm::new(RowTransform::Retrograde, 0)), retrograde);
+        assert_eq!(
+            row.form(RowForm::new(RowTransform::Inversion, 0)),
+            [Gn, En, Cn, Gs, Fn, Dn, As, Fs, Ds, Cs, Bn, An]
+        );
+        let mut retrograde_inversion = row.form(RowForm::new(RowTransform::Inversion, 5));
+        retrograde_inversion.reverse();
+        assert_eq!(row.form(RowForm::new(RowTransform::RetrogradeInversion, 5)), retrograde_inversion);
+
+        let forms = row.forms();
+        assert_eq!(forms.len(), 48);
+        // Every form is still a row
+        assert!(forms.iter().all(|(_, form)| ToneRow::new(form).is_ok()));
+        assert_eq!(RowForm::new(RowTransform::RetrogradeInversion, 11).to_string(), "RI11");
+    }
+
+    #[test]
+    fn test_matrix() {
+        let schoenberg = ToneRow::new(&[En, Fn, Gn, Cs, Fs, Ds, Gs, Dn, Bn, Cn, An, As]).unwrap();
+        assert_eq!(schoenberg.matrix()[1], [Ds, En, Fs, Cn, Fn, Dn, Gn, Cs, As, Bn, Gs, An]);
+        assert_eq!(schoenberg.matrix_labels()[1], RowForm::new(RowTransform::Prime, 11));
+
+        let row = ToneRow::new(&BERG).unwrap();
+        let matrix = row.matrix();
+        assert_eq!(matrix[0], BERG);
+        // The diagonal holds the first pitch class of the row
+        assert!((0..12).all(|i| matrix[i][i] == Gn));
+        // Columns are the inversions
+        let column: Vec<PitchClass> = matrix.iter().map(|r| r[0]).collect();
+        assert_eq!(column, row.form(RowForm::new(RowTransform::Inversion, 0)).to_vec());
+        let labels = row.matrix_labels();
+        assert!((0..12).all(|i| row.form(labels[i]) == matrix[i]));
+    }
+
+    #[test]
+    fn test_find() {
+        let row = ToneRow::new(&BERG).unwrap();
+        let mut played = vec![Cn, Cn];
+        played.extend(row.form(RowForm::new(RowTransform::Inversion, 3)));
+        // A repeated note inside the row
+        played.insert(5, played[4]);
+        played.extend(row.form(RowForm::new(RowTransform::Retrograde, 5)));
+        let found = row.find(&played);
+        assert!(found.contains(&(2, RowForm::new(RowTransform::Inversion, 3))));
+        assert!(found.contains(&(15, RowForm::new(RowTransform::Retrograde, 5))));
+        assert!(row.find(&BERG[..11]).is_empty());
+    }
+
+    #[test]
+    fn test_combinatoriality() {
+        // The chromatic hexachord is all-combinatorial
+        let chromatic = ToneRow::new(&[Cn, Cs, Dn, Ds, En, Fn, Fs, Gn, Gs, An, As, Bn]).unwrap();
+        assert!(chromatic.is_all_combinatorial());
+        assert_eq!(chromatic.combinatorial(RowTransform::Prime), vec![RowForm::new(RowTransform::Prime, 6)]);
+        assert_eq!(chromatic.combinatorial(RowTransform::Inversion), vec![RowForm::new(RowTransform::Inversion, 11)]);
+
+        let webern = ToneRow::new(&WEBERN).unwrap();
+        assert!(webern.is_all_combinatorial());
+
+        // Schoenberg - Suite Op. 25
+        let schoenberg = ToneRow::new(&[En, Fn, Gn, Cs, Fs, Ds, Gs, Dn, Bn, Cn, An, As]).unwrap();
+        assert_eq!(schoenberg.combinatorial(RowTransform::Inversion), vec![RowForm::new(RowTransform::Inversion, 7)]);
+        assert!(schoenberg.combinatorial(RowTransform::Prime).is_empty());
+        assert!(!schoenberg.is_all_combinatorial());
+
+        let berg = ToneRow::new(&BERG).unwrap();
+        assert!(!berg.is_combinatorial());
+        assert!(!berg.is_all_combinatorial());
+    }
+}